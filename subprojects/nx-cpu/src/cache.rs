@@ -0,0 +1,78 @@
+//! Cache maintenance operations.
+//!
+//! Code written through a data alias must be pushed out of the data cache and evicted from the
+//! instruction cache before it can be executed. [`sync_instruction_cache`] performs the whole
+//! sequence; the individual steps are also exposed for callers that write and execute through
+//! different virtual aliases of the same memory.
+
+use core::arch::asm;
+
+use crate::{
+    barrier::{self, ISH, SY},
+    control_regs,
+};
+
+/// Returns the smallest data cache line size, in bytes (`CTR_EL0.DminLine`).
+fn dcache_line_size() -> usize {
+    let ctr = unsafe { control_regs::ctr_el0() };
+    4 << ((ctr >> 16) & 0xF)
+}
+
+/// Returns the smallest instruction cache line size, in bytes (`CTR_EL0.IminLine`).
+fn icache_line_size() -> usize {
+    let ctr = unsafe { control_regs::ctr_el0() };
+    4 << (ctr & 0xF)
+}
+
+/// Cleans the data cache lines covering `addr..addr + size` to the point of unification.
+///
+/// Issues `dc cvau` for every line in the range, followed by a `dsb ish`.
+///
+/// # Safety
+///
+/// The whole range must be mapped and readable in the current process.
+pub unsafe fn clean_dcache_to_pou(addr: *const u8, size: usize) {
+    let line = dcache_line_size();
+    let start = addr as usize & !(line - 1);
+    let end = addr as usize + size;
+
+    for line_addr in (start..end).step_by(line) {
+        unsafe { asm!("dc cvau, {0}", in(reg) line_addr, options(nostack, preserves_flags)) };
+    }
+
+    barrier::dsb(ISH);
+}
+
+/// Invalidates the instruction cache lines covering `addr..addr + size`.
+///
+/// Issues `ic ivau` for every line in the range, followed by a `dsb ish` and an `isb`.
+///
+/// # Safety
+///
+/// The whole range must be mapped and readable in the current process.
+pub unsafe fn invalidate_icache(addr: *const u8, size: usize) {
+    let line = icache_line_size();
+    let start = addr as usize & !(line - 1);
+    let end = addr as usize + size;
+
+    for line_addr in (start..end).step_by(line) {
+        unsafe { asm!("ic ivau, {0}", in(reg) line_addr, options(nostack, preserves_flags)) };
+    }
+
+    barrier::dsb(ISH);
+    barrier::isb(SY);
+}
+
+/// Makes freshly written code at `addr..addr + size` visible to instruction fetches.
+///
+/// Equivalent to [`clean_dcache_to_pou`] followed by [`invalidate_icache`] on the same range.
+///
+/// # Safety
+///
+/// The whole range must be mapped and readable in the current process.
+pub unsafe fn sync_instruction_cache(addr: *const u8, size: usize) {
+    unsafe {
+        clean_dcache_to_pou(addr, size);
+        invalidate_icache(addr, size);
+    }
+}
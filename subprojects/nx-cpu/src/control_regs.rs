@@ -94,3 +94,30 @@ pub unsafe extern "C" fn tpidrro_el0() -> usize {
         "ret",
     );
 }
+
+/// Read the `ctr_el0` system register.
+///
+/// This function reads the `ctr_el0` system register, which describes the cache architecture,
+/// including the minimum data and instruction cache line sizes.
+///
+/// Returns the raw register value.
+///
+/// # References
+///
+/// - [ARM CTR-EL0 Register](https://developer.arm.com/documentation/ddi0601/2024-12/AArch64-Registers/CTR-EL0--Cache-Type-Register)
+/// - [rust-embedded/aarch64-cpu: ctr_el0.rs](https://github.com/rust-embedded/aarch64-cpu/blob/main/src/registers/ctr_el0.rs)
+///
+/// # SAFETY
+///
+/// This function is `naked`, and its body is written in assembly.
+/// The assembly code reads the `ctr_el0` system register and returns
+/// its value in `x0`, according to the AArch64 procedure call standard.
+/// The `noreturn` option is used to prevent the compiler from generating
+/// a function prologue and epilogue.
+#[unsafe(naked)]
+pub unsafe extern "C" fn ctr_el0() -> u64 {
+    naked_asm!(
+        "mrs x0, ctr_el0", // Move the value of `ctr_el0` into the return register `x0`
+        "ret",
+    );
+}
@@ -12,4 +12,5 @@ compile_error!("nx-cpu only supports aarch64 CPUs");
 use nx_svc as _;
 
 pub mod barrier;
+pub mod cache;
 pub mod control_regs;
@@ -0,0 +1,307 @@
+//! # nx-svc-derive
+//!
+//! Derive macros for the error types of `nx-svc` and the crates built on top of it.
+//!
+//! ## `#[derive(ResultCodeError)]`
+//!
+//! Most SVC and IPC wrappers map the raw result code they receive onto a typed error enum, fall
+//! back to an `Unknown(Error)` variant for anything unforeseen, and convert back into the raw
+//! result code. This derive generates all of that from per-variant `#[rc(...)]` attributes:
+//!
+//! ```ignore
+//! use nx_svc::{error::ResultCodeError, result::Error};
+//!
+//! #[derive(Debug, ResultCodeError)]
+//! pub enum CloseHandleError {
+//!     /// The handle is not valid.
+//!     #[rc(module = Kernel, desc = InvalidHandle)]
+//!     InvalidHandle,
+//!     /// Any unforeseen error.
+//!     Unknown(Error),
+//! }
+//! ```
+//!
+//! It generates:
+//! - `From<nx_svc::result::Error>`: maps the error on the variant with the same module and
+//!   description, or on the *unknown* variant if none matches.
+//! - `From<nx_svc::error::ResultCode>`: the same mapping, from a raw (failure) result code.
+//! - `nx_svc::error::ToRawResultCode`: the reverse conversion.
+//! - `core::fmt::Display` and `core::error::Error`.
+//!
+//! ### Attributes
+//!
+//! On the enum:
+//! - `#[rc(module = <Module>)]`: default module of the variants (`Kernel` if omitted).
+//!
+//! On unit variants:
+//! - `module = <Module>`: overrides the default module, a variant of `nx_svc::error::Module`.
+//! - `desc = <Name>` or `desc = <integer>`: the description. Names are resolved against
+//!   `nx_svc::error::KernelError`, so they are only accepted for the `Kernel` module.
+//! - `msg = "<text>"`: the `Display` message. Defaults to the variant name split into words, e.g.
+//!   `InvalidHandle` displays as `Invalid handle`.
+//!
+//! On the *unknown* variant, which must have a single `nx_svc::result::Error` field:
+//! - `#[rc(unknown)]`. May be omitted if the variant is named `Unknown`. Displays as
+//!   `Unknown error: <code>`.
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{
+    Attribute, Data, DeriveInput, Fields, Ident, LitInt, LitStr, Variant, parse_macro_input,
+    spanned::Spanned,
+};
+
+/// Derives the result code conversions, `Display` and `Error` for an error enum.
+///
+/// See the [crate-level documentation](crate) for the supported attributes.
+#[proc_macro_derive(ResultCodeError, attributes(rc))]
+pub fn derive_result_code_error(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(&input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+fn expand(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    let Data::Enum(data) = &input.data else {
+        return Err(syn::Error::new(
+            input.span(),
+            "ResultCodeError can only be derived for enums",
+        ));
+    };
+
+    let default_module = parse_enum_attrs(&input.attrs)?;
+
+    let mut known = Vec::new();
+    let mut unknown: Option<&Ident> = None;
+    for variant in &data.variants {
+        match parse_variant(variant, &default_module)? {
+            VariantKind::Known(known_variant) => known.push(known_variant),
+            VariantKind::Unknown => {
+                if unknown.is_some() {
+                    return Err(syn::Error::new(
+                        variant.span(),
+                        "only one variant can be the unknown fallback",
+                    ));
+                }
+                unknown = Some(&variant.ident);
+            }
+        }
+    }
+    let Some(unknown) = unknown else {
+        return Err(syn::Error::new(
+            input.span(),
+            "missing the unknown fallback variant: add an `Unknown(Error)` variant, \
+             or mark one with `#[rc(unknown)]`",
+        ));
+    };
+
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    let idents = known.iter().map(|v| &v.ident).collect::<Vec<_>>();
+    let codes = known.iter().map(KnownVariant::code).collect::<Vec<_>>();
+    let msgs = known.iter().map(|v| &v.msg);
+
+    Ok(quote! {
+        impl #impl_generics ::core::convert::From<::nx_svc::result::Error> for #name #ty_generics
+        #where_clause
+        {
+            fn from(err: ::nx_svc::result::Error) -> Self {
+                match err.to_raw() {
+                    #(rc if rc == #codes => Self::#idents,)*
+                    _ => Self::#unknown(err),
+                }
+            }
+        }
+
+        impl #impl_generics ::core::convert::From<::nx_svc::error::ResultCode> for #name #ty_generics
+        #where_clause
+        {
+            fn from(rc: ::nx_svc::error::ResultCode) -> Self {
+                Self::from(::nx_svc::result::Error::from_raw(rc))
+            }
+        }
+
+        impl #impl_generics ::nx_svc::error::ToRawResultCode for #name #ty_generics
+        #where_clause
+        {
+            fn to_rc(self) -> ::nx_svc::error::ResultCode {
+                match self {
+                    #(Self::#idents => #codes,)*
+                    Self::#unknown(err) => err.to_raw(),
+                }
+            }
+        }
+
+        impl #impl_generics ::core::fmt::Display for #name #ty_generics #where_clause {
+            fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                match self {
+                    #(Self::#idents => f.write_str(#msgs),)*
+                    Self::#unknown(err) => ::core::write!(f, "Unknown error: {}", err),
+                }
+            }
+        }
+
+        impl #impl_generics ::core::error::Error for #name #ty_generics #where_clause {}
+    })
+}
+
+/// A variant mapped onto a specific result code.
+struct KnownVariant {
+    ident: Ident,
+    module: Ident,
+    desc: Desc,
+    msg: LitStr,
+}
+
+impl KnownVariant {
+    /// Returns the expression evaluating to the variant's raw result code.
+    fn code(&self) -> TokenStream2 {
+        let module = &self.module;
+        let desc = match &self.desc {
+            Desc::Named(name) => quote!(::nx_svc::error::KernelError::#name as u32),
+            Desc::Value(value) => quote!(#value),
+        };
+        quote! {
+            ::nx_svc::error::ToRawResultCode::to_rc((::nx_svc::error::Module::#module, #desc))
+        }
+    }
+}
+
+/// A variant's description, as given in its `desc` attribute.
+enum Desc {
+    /// A `KernelError` variant name.
+    Named(Ident),
+    /// A raw description value.
+    Value(LitInt),
+}
+
+enum VariantKind {
+    Known(KnownVariant),
+    Unknown,
+}
+
+/// Parses the enum-level attributes, returning the default module.
+fn parse_enum_attrs(attrs: &[Attribute]) -> syn::Result<Ident> {
+    let mut module = None;
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("rc")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("module") {
+                module = Some(meta.value()?.parse::<Ident>()?);
+                Ok(())
+            } else {
+                Err(meta.error("unsupported enum attribute, expected `module`"))
+            }
+        })?;
+    }
+
+    Ok(module.unwrap_or_else(|| Ident::new("Kernel", proc_macro2::Span::call_site())))
+}
+
+fn parse_variant(variant: &Variant, default_module: &Ident) -> syn::Result<VariantKind> {
+    let mut module = None;
+    let mut desc = None;
+    let mut msg = None;
+    let mut is_unknown = false;
+
+    for attr in variant
+        .attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("rc"))
+    {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("module") {
+                module = Some(meta.value()?.parse::<Ident>()?);
+            } else if meta.path.is_ident("desc") {
+                let value = meta.value()?;
+                desc = Some(if value.peek(LitInt) {
+                    Desc::Value(value.parse()?)
+                } else {
+                    Desc::Named(value.parse()?)
+                });
+            } else if meta.path.is_ident("msg") {
+                msg = Some(meta.value()?.parse::<LitStr>()?);
+            } else if meta.path.is_ident("unknown") {
+                is_unknown = true;
+            } else {
+                return Err(meta.error(
+                    "unsupported variant attribute, expected `module`, `desc`, `msg` or `unknown`",
+                ));
+            }
+            Ok(())
+        })?;
+    }
+
+    // The fallback variant: `#[rc(unknown)]`, or a variant named `Unknown` without a description
+    if is_unknown || (desc.is_none() && variant.ident == "Unknown") {
+        if desc.is_some() || module.is_some() || msg.is_some() {
+            return Err(syn::Error::new(
+                variant.span(),
+                "the unknown fallback variant cannot have a `module`, `desc` or `msg`",
+            ));
+        }
+        if !matches!(&variant.fields, Fields::Unnamed(fields) if fields.unnamed.len() == 1) {
+            return Err(syn::Error::new(
+                variant.fields.span(),
+                "the unknown fallback variant must have a single `Error` field",
+            ));
+        }
+        return Ok(VariantKind::Unknown);
+    }
+
+    if !matches!(variant.fields, Fields::Unit) {
+        return Err(syn::Error::new(
+            variant.fields.span(),
+            "result code variants cannot have fields",
+        ));
+    }
+    let Some(desc) = desc else {
+        return Err(syn::Error::new(
+            variant.span(),
+            "missing `#[rc(desc = ...)]` attribute",
+        ));
+    };
+
+    let module = module.unwrap_or_else(|| default_module.clone());
+    if let Desc::Named(name) = &desc
+        && module != "Kernel"
+    {
+        return Err(syn::Error::new(
+            name.span(),
+            "named descriptions are only available for the `Kernel` module, use an integer",
+        ));
+    }
+
+    let msg = msg.unwrap_or_else(|| LitStr::new(&humanize(&variant.ident), variant.ident.span()));
+
+    Ok(VariantKind::Known(KnownVariant {
+        ident: variant.ident.clone(),
+        module,
+        desc,
+        msg,
+    }))
+}
+
+/// Splits a `CamelCase` identifier into a sentence, e.g. `InvalidHandle` into `Invalid handle`.
+fn humanize(ident: &Ident) -> String {
+    let ident = ident.to_string();
+    let mut out = String::with_capacity(ident.len() + 4);
+    let mut prev_lower = false;
+    for c in ident.chars() {
+        if c.is_uppercase() {
+            if prev_lower {
+                out.push(' ');
+                out.extend(c.to_lowercase());
+            } else {
+                out.push(c);
+            }
+            prev_lower = false;
+        } else {
+            out.push(c);
+            prev_lower = true;
+        }
+    }
+    out
+}
@@ -1697,4 +1697,18 @@ Result __nx_svc_unmap_insecure_physical_memory(void *address, u64 size);
 
 ///@}
 
+///@name Exception handling
+///@{
+
+/// Context of a faulting thread, layout-compatible with libnx's `ThreadExceptionDump`.
+typedef struct ThreadExceptionDump ThreadExceptionDump;
+
+/**
+ * @brief Handles a user-mode exception, overriding libnx's `__libnx_exception_handler`.
+ * @param[in] ctx Context of the faulting thread.
+ */
+void NX_NORETURN __nx_svc_exception_handler(ThreadExceptionDump *ctx);
+
+///@}
+
 #endif // SWITCH_SVC_H
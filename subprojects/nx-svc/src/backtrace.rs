@@ -0,0 +1,237 @@
+//! Frame-pointer based stack unwinding
+//!
+//! On AArch64, every function that sets up a frame pushes a _frame record_ onto
+//! the stack: a pair of 64-bit words holding the caller's frame pointer (`x29`)
+//! and the return address (`x30`), and points `x29` at it. Following the chain of
+//! frame records from a frame pointer yields the return address of every active
+//! call, without needing any unwind tables.
+//!
+//! The chain may be corrupt (e.g. after a stack overflow), so every frame record
+//! address is checked with [`query_memory`] before being read: the walk stops at
+//! the first record that is misaligned, not mapped as read-write memory, or not
+//! above the previous one.
+//!
+//! Frame records are only emitted for code compiled with frame pointers
+//! (`-C force-frame-pointers=yes`); frames of functions built without them are
+//! skipped.
+//!
+//! ```ignore
+//! let bt = nx_svc::backtrace::Backtrace::capture();
+//! nx_svc::dbg_println!("{bt}");
+//! ```
+//!
+//! ## References
+//! - [ARM: Procedure Call Standard for the Arm 64-bit Architecture, The Frame Pointer](https://github.com/ARM-software/abi-aa/blob/main/aapcs64/aapcs64.rst#the-frame-pointer)
+
+use core::fmt;
+
+use crate::{
+    mem::core::{MemoryInfo, MemoryType, query_memory},
+    raw,
+    symbolize::{self, Location},
+};
+
+/// Maximum number of frames recorded by a [`Backtrace`].
+pub const MAX_FRAMES: usize = 32;
+
+/// Size of an AArch64 frame record (frame pointer and return address).
+const FRAME_RECORD_SIZE: usize = 16;
+
+/// A stack frame, identified by its return address.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Frame {
+    /// The return address (or the program counter, for the innermost frame of a
+    /// thread context).
+    pub address: usize,
+    /// Base address of the executable region containing `address`, or `None` if
+    /// it does not lie in executable memory.
+    pub module_base: Option<usize>,
+}
+
+impl Frame {
+    /// Creates a frame for `address`, looking up the base of the module it belongs to.
+    fn new(address: usize) -> Self {
+        Self {
+            address,
+            module_base: module_base(address),
+        }
+    }
+
+    /// Returns the offset of the address into its module.
+    pub fn offset(&self) -> Option<usize> {
+        self.module_base.map(|base| self.address - base)
+    }
+
+    /// Resolves the address to its module and nearest exported function.
+    ///
+    /// See [`symbolize::resolve`].
+    pub fn symbolize(&self) -> Option<Location> {
+        symbolize::resolve(self.address)
+    }
+}
+
+impl fmt::Display for Frame {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:#018x}", self.address)?;
+        if let Some(base) = self.module_base {
+            write!(f, " ({base:#x} + {:#x})", self.address - base)?;
+        }
+        Ok(())
+    }
+}
+
+/// A fixed-capacity list of stack frames, innermost first.
+#[derive(Debug, Clone)]
+pub struct Backtrace {
+    frames: [Frame; MAX_FRAMES],
+    len: usize,
+    truncated: bool,
+}
+
+impl Backtrace {
+    /// Captures the backtrace of the calling function.
+    ///
+    /// The innermost frame is the return address into the caller of `capture`.
+    #[inline(never)]
+    pub fn capture() -> Self {
+        let fp: usize;
+        // SAFETY: Reading the frame pointer has no side effects.
+        unsafe { core::arch::asm!("mov {}, x29", out(reg) fp, options(nomem, nostack)) };
+
+        let mut bt = Self::empty();
+        bt.walk(fp);
+        bt
+    }
+
+    /// Unwinds the stack starting from the frame record at `fp`.
+    ///
+    /// The innermost frame is the return address stored in that record.
+    pub fn from_frame_pointer(fp: usize) -> Self {
+        let mut bt = Self::empty();
+        bt.walk(fp);
+        bt
+    }
+
+    /// Unwinds the stack of a thread from its saved register context.
+    ///
+    /// The innermost frame is the thread's program counter, followed by the return
+    /// addresses found by following the frame records from its frame pointer. The
+    /// result is empty for AArch32 threads.
+    pub fn from_thread_context(ctx: &raw::ThreadContext) -> Self {
+        let mut bt = Self::empty();
+        if !ctx.is_aarch64() {
+            return bt;
+        }
+
+        // SAFETY: The 64-bit view is the valid one for AArch64 threads.
+        let pc = unsafe { ctx.pc.x } as usize;
+        bt.push(pc);
+        bt.walk(ctx.fp as usize);
+        bt
+    }
+
+    /// Returns the recorded frames, innermost first.
+    pub fn frames(&self) -> &[Frame] {
+        &self.frames[..self.len]
+    }
+
+    /// Returns `true` if the walk stopped because [`MAX_FRAMES`] frames were recorded,
+    /// rather than at the end of the chain.
+    pub fn is_truncated(&self) -> bool {
+        self.truncated
+    }
+
+    fn empty() -> Self {
+        Self {
+            frames: [Frame::default(); MAX_FRAMES],
+            len: 0,
+            truncated: false,
+        }
+    }
+
+    /// Records a frame, returning `false` if the backtrace is full.
+    fn push(&mut self, address: usize) -> bool {
+        if self.len == MAX_FRAMES {
+            self.truncated = true;
+            return false;
+        }
+        self.frames[self.len] = Frame::new(address);
+        self.len += 1;
+        true
+    }
+
+    /// Follows the chain of frame records starting at `fp`.
+    fn walk(&mut self, mut fp: usize) {
+        // The stack usually spans a single region, so cache the last one queried
+        let mut region: Option<MemoryInfo> = None;
+
+        while fp != 0 && fp.is_multiple_of(FRAME_RECORD_SIZE) {
+            let valid = match region {
+                Some(ref info) if contains_record(info, fp) => true,
+                _ => match query_memory(fp) {
+                    Ok((info, _)) => {
+                        let valid = is_stack_like(&info) && contains_record(&info, fp);
+                        region = Some(info);
+                        valid
+                    }
+                    Err(_) => false,
+                },
+            };
+            if !valid {
+                break;
+            }
+
+            // SAFETY: The frame record lies within readable memory, as checked above.
+            let (next_fp, lr) = unsafe {
+                let record = fp as *const usize;
+                (record.read_volatile(), record.add(1).read_volatile())
+            };
+
+            // The outermost frame record holds a null return address
+            if lr == 0 || !self.push(lr) {
+                break;
+            }
+
+            // The stack grows downwards, so callers' records are at higher addresses
+            if next_fp <= fp {
+                break;
+            }
+            fp = next_fp;
+        }
+    }
+}
+
+impl fmt::Display for Backtrace {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, frame) in self.frames().iter().enumerate() {
+            writeln!(f, "  #{i:<2} {frame}")?;
+        }
+        if self.truncated {
+            writeln!(f, "  ...")?;
+        }
+        Ok(())
+    }
+}
+
+/// Returns whether a frame record at `fp` lies entirely within the region.
+fn contains_record(info: &MemoryInfo, fp: usize) -> bool {
+    fp >= info.addr && fp - info.addr <= info.size.saturating_sub(FRAME_RECORD_SIZE)
+}
+
+/// Returns whether a region may hold frame records: mapped, readable and writable
+/// normal memory.
+fn is_stack_like(info: &MemoryInfo) -> bool {
+    !matches!(
+        info.typ,
+        MemoryType::Unmapped | MemoryType::Io | MemoryType::Reserved
+    ) && info.perm.is_read_write()
+}
+
+/// Returns the base address of the executable region containing `addr`.
+///
+/// The text segment is the first one of every module, so its base is the module's
+/// load address.
+fn module_base(addr: usize) -> Option<usize> {
+    let (info, _) = query_memory(addr).ok()?;
+    (info.typ != MemoryType::Unmapped && info.perm.is_executable()).then_some(info.addr)
+}
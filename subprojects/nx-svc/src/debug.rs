@@ -1,4 +1,24 @@
-use super::raw;
+//! Debugging facilities
+//!
+//! This module provides the `svcBreak` debug event trigger and a debug text sink over
+//! `svcOutputDebugString`:
+//! - [`DebugWriter`] implements [`core::fmt::Write`] and emits its output line by line.
+//! - The [`dbg_print!`](crate::dbg_print) and [`dbg_println!`](crate::dbg_println) macros format
+//!   directly into it.
+//! - With the `log` feature, [`logger`] provides a [`log::Log`] implementation on top of it.
+//!
+//! The text ends up in the attached debugger or, when running under an emulator, in its log.
+
+use core::fmt;
+
+use super::{
+    error::ResultCodeError,
+    raw,
+    result::{Error, raw::Result as RawResult},
+};
+
+#[cfg(feature = "log")]
+pub mod logger;
 
 /// Trigger a debug event
 ///
@@ -50,3 +70,201 @@ impl From<BreakReason> for raw::BreakReason {
         }
     }
 }
+
+/// Outputs a string to the debug output.
+///
+/// This is a safe wrapper around [`raw::output_debug_string`]. The string is emitted as is: no
+/// newline is appended.
+pub fn output_debug_string(s: &str) -> Result<(), OutputDebugStringError> {
+    let rc = unsafe { raw::output_debug_string(s.as_ptr().cast(), s.len() as u64) };
+    RawResult::from_raw(rc).map((), |rc| Error::from(rc).into())
+}
+
+/// Error type for [`output_debug_string`].
+#[derive(Debug, ResultCodeError)]
+pub enum OutputDebugStringError {
+    /// The string memory cannot be read by the kernel —
+    /// `KernelError::InvalidCurrentMemory` (raw code `0xD401`).
+    #[rc(desc = InvalidCurrentMemory)]
+    InvalidMemState,
+    /// Any unforeseen kernel error. Contains the original [`Error`] so callers
+    /// can inspect the raw result (`Error::to_raw`).
+    Unknown(Error),
+}
+
+/// Size of the [`DebugWriter`] line buffer.
+pub const DEBUG_LINE_BUF_SIZE: usize = 256;
+
+/// A line-buffered [`fmt::Write`] sink over [`output_debug_string`].
+///
+/// Text is accumulated until a newline is written, and each line (including its trailing newline)
+/// is then emitted with a single `svcOutputDebugString` call, so that lines written by different
+/// threads are not interleaved. Lines longer than [`DEBUG_LINE_BUF_SIZE`] are emitted in chunks.
+///
+/// Any pending partial line is emitted when the writer is [flushed](Self::flush) or dropped.
+pub struct DebugWriter {
+    buf: [u8; DEBUG_LINE_BUF_SIZE],
+    len: usize,
+}
+
+impl DebugWriter {
+    /// Creates a new, empty writer.
+    pub const fn new() -> Self {
+        Self {
+            buf: [0; DEBUG_LINE_BUF_SIZE],
+            len: 0,
+        }
+    }
+
+    /// Emits the buffered text, if any.
+    pub fn flush(&mut self) {
+        if self.len == 0 {
+            return;
+        }
+
+        // Debug output is best-effort: there is nothing sensible to do on failure.
+        // SAFETY: Chunks are only split at character boundaries, see `next_chunk`.
+        let _ =
+            output_debug_string(unsafe { core::str::from_utf8_unchecked(&self.buf[..self.len]) });
+        self.len = 0;
+    }
+
+    /// Appends `s`, which must fit in the remaining buffer space.
+    fn push(&mut self, s: &str) {
+        self.buf[self.len..self.len + s.len()].copy_from_slice(s.as_bytes());
+        self.len += s.len();
+    }
+}
+
+impl Default for DebugWriter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Write for DebugWriter {
+    fn write_str(&mut self, mut s: &str) -> fmt::Result {
+        while !s.is_empty() {
+            let (chunk, rest, flush) = next_chunk(s, DEBUG_LINE_BUF_SIZE - self.len);
+            self.push(chunk);
+            if flush {
+                self.flush();
+            }
+            s = rest;
+        }
+        Ok(())
+    }
+}
+
+/// Splits off the next piece of `s` to append to a line buffer with `space` bytes left.
+///
+/// The piece runs up to the end of the current line if it fits, and is otherwise cut at the last
+/// character boundary within `space` (possibly leaving it empty). Returns the piece, the remaining
+/// text, and whether the buffer must be flushed after appending the piece: when it ends a line, or
+/// when it fills the buffer.
+fn next_chunk(s: &str, space: usize) -> (&str, &str, bool) {
+    let line_len = s.find('\n').map_or(s.len(), |pos| pos + 1);
+    if line_len <= space {
+        let (line, rest) = s.split_at(line_len);
+        return (line, rest, line.ends_with('\n'));
+    }
+
+    let mut split = space;
+    while !s.is_char_boundary(split) {
+        split -= 1;
+    }
+    let (chunk, rest) = s.split_at(split);
+    (chunk, rest, true)
+}
+
+impl Drop for DebugWriter {
+    fn drop(&mut self) {
+        self.flush();
+    }
+}
+
+/// Prints to the debug output, see [`DebugWriter`].
+///
+/// Equivalent to [`dbg_println!`](crate::dbg_println) except that a newline is not printed at the
+/// end of the message.
+#[macro_export]
+macro_rules! dbg_print {
+    ($($arg:tt)*) => {{
+        let mut writer = $crate::debug::DebugWriter::new();
+        let _ = ::core::fmt::Write::write_fmt(&mut writer, ::core::format_args!($($arg)*));
+    }};
+}
+
+/// Prints to the debug output, with a newline, see [`DebugWriter`].
+#[macro_export]
+macro_rules! dbg_println {
+    () => {
+        $crate::dbg_print!("\n")
+    };
+    ($($arg:tt)*) => {{
+        let mut writer = $crate::debug::DebugWriter::new();
+        let _ = ::core::fmt::Write::write_fmt(&mut writer, ::core::format_args!($($arg)*));
+        let _ = ::core::fmt::Write::write_str(&mut writer, "\n");
+    }};
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate alloc;
+
+    use alloc::{string::String, vec::Vec};
+
+    use super::*;
+
+    /// Feeds `s` to a simulated [`DebugWriter`] buffer, returning the emitted chunks.
+    fn emit(s: &str) -> Vec<String> {
+        let mut chunks = Vec::new();
+        let mut buf = String::new();
+        let mut s = s;
+        while !s.is_empty() {
+            let (chunk, rest, flush) = next_chunk(s, DEBUG_LINE_BUF_SIZE - buf.len());
+            buf.push_str(chunk);
+            if flush && !buf.is_empty() {
+                chunks.push(core::mem::take(&mut buf));
+            }
+            s = rest;
+        }
+        if !buf.is_empty() {
+            chunks.push(buf);
+        }
+        chunks
+    }
+
+    #[test]
+    fn lines() {
+        assert_eq!(next_chunk("ab\ncd", 8), ("ab\n", "cd", true));
+        assert_eq!(next_chunk("cd", 8), ("cd", "", false));
+        assert_eq!(next_chunk("abc\n", 4), ("abc\n", "", true));
+        assert_eq!(next_chunk("abcdef", 4), ("abcd", "ef", true));
+    }
+
+    #[test]
+    fn multi_byte_at_chunk_edge() {
+        // 'é' is 2 bytes, '€' is 3 bytes and '🦀' is 4 bytes: none of them fit in 1 byte
+        assert_eq!(next_chunk("aé", 2), ("a", "é", true));
+        assert_eq!(next_chunk("é", 1), ("", "é", true));
+        assert_eq!(next_chunk("x€y", 3), ("x", "€y", true));
+        assert_eq!(next_chunk("€y", 3), ("€", "y", true));
+        assert_eq!(next_chunk("🦀", 3), ("", "🦀", true));
+        assert_eq!(next_chunk("🦀\n", 4), ("🦀", "\n", true));
+    }
+
+    #[test]
+    fn long_lines_are_split_at_char_boundaries() {
+        // Length of the first chunk, for each number of bytes left after the leading ASCII run
+        for (left, first_len) in [(0, 256), (1, 255), (2, 256), (3, 255)] {
+            let mut line = "a".repeat(DEBUG_LINE_BUF_SIZE - left);
+            line.push_str("é€🦀\n");
+
+            let chunks = emit(&line);
+            assert_eq!(chunks.concat(), line);
+            assert_eq!(chunks.len(), 2);
+            assert_eq!(chunks[0].len(), first_len);
+        }
+    }
+}
@@ -0,0 +1,60 @@
+//! [`log`] backend over the debug output
+//!
+//! Each record is emitted as a single line through a [`DebugWriter`], formatted as
+//! `[LEVEL target] message`.
+//!
+//! ```ignore
+//! nx_svc::debug::logger::init(log::LevelFilter::Debug).unwrap();
+//! log::info!("Hello from the Switch");
+//! ```
+
+use core::fmt::Write as _;
+
+use log::{LevelFilter, Log, Metadata, Record, SetLoggerError};
+
+use super::DebugWriter;
+
+/// The logger installed by [`init`].
+static LOGGER: DebugLogger = DebugLogger;
+
+/// Installs the debug output logger, and sets the maximum enabled log level.
+///
+/// Returns an error if a logger has already been installed.
+pub fn init(level: LevelFilter) -> Result<(), SetLoggerError> {
+    log::set_logger(&LOGGER)?;
+    log::set_max_level(level);
+    Ok(())
+}
+
+/// Changes the maximum enabled log level.
+pub fn set_level(level: LevelFilter) {
+    log::set_max_level(level);
+}
+
+/// A [`Log`] implementation writing to the debug output.
+///
+/// Records above the maximum log level (see [`set_level`]) are discarded.
+pub struct DebugLogger;
+
+impl Log for DebugLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= log::max_level()
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+
+        let mut writer = DebugWriter::new();
+        let _ = writeln!(
+            writer,
+            "[{} {}] {}",
+            record.level(),
+            record.target(),
+            record.args()
+        );
+    }
+
+    fn flush(&self) {}
+}
@@ -0,0 +1,738 @@
+//! Debugger system calls for Horizon OS (Nintendo Switch)
+//!
+//! A debugger attaches to a running process with [`debug_active_process`] and receives a
+//! [`DebugHandle`]. The handle is signalled whenever the kernel has a debug event pending. Events
+//! are fetched with [`get_event`] and decoded into [`DebugEvent`] values. The debugged process
+//! stays stopped until the debugger calls [`continue_event`].
+//!
+//! While the process is stopped, its threads' registers can be inspected and modified with
+//! [`get_thread_context`]/[`set_thread_context`], and its memory with [`query_memory`],
+//! [`read_memory`] and [`write_memory`].
+//!
+//! Hardware breakpoints and watchpoints are programmed with [`set_hardware_breakpoint`], using the
+//! encodings built by the [`breakpoint`] module.
+//!
+//! Closing the debug handle (see [`detach`]) detaches the debugger and resumes the process.
+//!
+//! All of these SVCs require the `DebugActiveProcess` kernel capability (or a debug-mode
+//! kernel).
+//!
+//! ## References
+//! - [Switchbrew Wiki: SVC](https://switchbrew.org/wiki/SVC)
+
+use core::ffi::c_void;
+
+use self::breakpoint::{Encoding, Register};
+use crate::{
+    error::{KernelError as KError, ResultCodeError},
+    handle::OwnedHandle,
+    mem::{MemoryInfo, PageInfo},
+    process::ProcessId,
+    raw,
+    result::{Error, raw::Result as RawResult},
+    thread::ThreadId,
+};
+
+pub mod breakpoint;
+
+/// Maximum number of thread IDs accepted by [`continue_event`].
+pub const MAX_CONTINUE_THREAD_IDS: usize = 64;
+
+define_waitable_handle_type! {
+    /// A handle to a debug session attached to a process.
+    ///
+    /// It is signalled while a debug event is pending. See [`get_event`].
+    pub struct DebugHandle
+}
+
+/// Attaches a debugger to the running process with the given ID.
+///
+/// This is a safe wrapper around [`raw::debug_active_process`]. The kernel reports the current
+/// state of the process as a series of [`DebugEvent::AttachProcess`] and
+/// [`DebugEvent::AttachThread`] events, followed by a [`DebugException::DebuggerAttached`]
+/// exception.
+pub fn debug_active_process(
+    process_id: ProcessId,
+) -> Result<OwnedHandle<DebugHandle>, DebugActiveProcessError> {
+    let mut handle = raw::INVALID_HANDLE;
+    let rc = unsafe { raw::debug_active_process(&mut handle, process_id.0) };
+    RawResult::from_raw(rc).map_err(|rc| match rc.description() {
+        desc if KError::InvalidId == desc => DebugActiveProcessError::InvalidProcessId,
+        desc if KError::NotFound == desc => DebugActiveProcessError::InvalidProcessId,
+        desc if KError::InvalidState == desc => DebugActiveProcessError::InvalidState,
+        desc if KError::OutOfResource == desc => DebugActiveProcessError::OutOfResource,
+        desc if KError::LimitReached == desc => DebugActiveProcessError::LimitReached,
+        desc if KError::OutOfHandles == desc => DebugActiveProcessError::OutOfHandles,
+        _ => DebugActiveProcessError::Unknown(rc.into()),
+    })?;
+
+    // SAFETY: On success, the kernel returned a new handle that is not owned elsewhere.
+    Ok(unsafe { OwnedHandle::new(DebugHandle(handle)) })
+}
+
+/// Error type for [`debug_active_process`].
+#[derive(Debug, ResultCodeError)]
+pub enum DebugActiveProcessError {
+    /// No process with the given ID exists —
+    /// `KernelError::InvalidId` (raw code `0x40E01`).
+    #[rc(desc = InvalidId)]
+    InvalidProcessId,
+    /// The process is already being debugged, or cannot be debugged —
+    /// `KernelError::InvalidState` (raw code `0xFA01`).
+    #[rc(desc = InvalidState)]
+    InvalidState,
+    /// The kernel could not allocate the debug object —
+    /// `KernelError::OutOfResource` (raw code `0xCE01`).
+    #[rc(desc = OutOfResource)]
+    OutOfResource,
+    /// The per-process debug object quota has been exhausted —
+    /// `KernelError::LimitReached` (raw code `0x10801`).
+    #[rc(desc = LimitReached)]
+    LimitReached,
+    /// The process handle table contains no free slots —
+    /// `KernelError::OutOfHandles` (raw code `0xD201`).
+    #[rc(desc = OutOfHandles, msg = "Handle table full")]
+    OutOfHandles,
+    /// Any unforeseen kernel error. Contains the original [`Error`] so callers
+    /// can inspect the raw result (`Error::to_raw`).
+    Unknown(Error),
+}
+
+/// Stops all threads of the debugged process.
+///
+/// This is a safe wrapper around [`raw::break_debug_process`]. The kernel then reports a
+/// [`DebugException::DebuggerBreak`] exception event.
+pub fn break_process(handle: &DebugHandle) -> Result<(), BreakProcessError> {
+    let rc = unsafe { raw::break_debug_process(handle.to_raw()) };
+    RawResult::from_raw(rc).map((), |rc| Error::from(rc).into())
+}
+
+/// Error type for [`break_process`].
+#[derive(Debug, ResultCodeError)]
+pub enum BreakProcessError {
+    /// The supplied handle is not a valid debug handle —
+    /// `KernelError::InvalidHandle` (raw code `0xE401`).
+    #[rc(desc = InvalidHandle)]
+    InvalidHandle,
+    /// The debugged process is already stopped or has terminated —
+    /// `KernelError::InvalidState` (raw code `0xFA01`).
+    #[rc(desc = InvalidState)]
+    InvalidState,
+    /// Any unforeseen kernel error. Contains the original [`Error`] so callers
+    /// can inspect the raw result (`Error::to_raw`).
+    Unknown(Error),
+}
+
+/// Terminates the debugged process.
+///
+/// This is a safe wrapper around [`raw::terminate_debug_process`].
+pub fn terminate_process(handle: &DebugHandle) -> Result<(), TerminateProcessError> {
+    let rc = unsafe { raw::terminate_debug_process(handle.to_raw()) };
+    RawResult::from_raw(rc).map((), |rc| Error::from(rc).into())
+}
+
+/// Error type for [`terminate_process`].
+#[derive(Debug, ResultCodeError)]
+pub enum TerminateProcessError {
+    /// The supplied handle is not a valid debug handle —
+    /// `KernelError::InvalidHandle` (raw code `0xE401`).
+    #[rc(desc = InvalidHandle)]
+    InvalidHandle,
+    /// The debugged process has already terminated —
+    /// `KernelError::InvalidState` (raw code `0xFA01`).
+    #[rc(desc = InvalidState)]
+    InvalidState,
+    /// Any unforeseen kernel error. Contains the original [`Error`] so callers
+    /// can inspect the raw result (`Error::to_raw`).
+    Unknown(Error),
+}
+
+/// Fetches the next pending debug event.
+///
+/// This is a safe wrapper around [`raw::get_debug_event`]. Returns
+/// [`GetEventError::NoEventPending`] if no event is queued; wait on the [`DebugHandle`] first to
+/// block until one is.
+pub fn get_event(handle: &DebugHandle) -> Result<DebugEventInfo, GetEventError> {
+    let mut raw_event = RawDebugEventInfo::default();
+    let rc = unsafe {
+        raw::get_debug_event(
+            &mut raw_event as *mut RawDebugEventInfo as *mut c_void,
+            handle.to_raw(),
+        )
+    };
+    RawResult::from_raw(rc).map(raw_event.decode(), |rc| Error::from(rc).into())
+}
+
+/// Error type for [`get_event`].
+#[derive(Debug, ResultCodeError)]
+pub enum GetEventError {
+    /// The supplied handle is not a valid debug handle —
+    /// `KernelError::InvalidHandle` (raw code `0xE401`).
+    #[rc(desc = InvalidHandle)]
+    InvalidHandle,
+    /// No debug event is pending —
+    /// `KernelError::NotFound` (raw code `0xF201`).
+    #[rc(desc = NotFound, msg = "No debug event pending")]
+    NoEventPending,
+    /// Any unforeseen kernel error. Contains the original [`Error`] so callers
+    /// can inspect the raw result (`Error::to_raw`).
+    Unknown(Error),
+}
+
+bitflags::bitflags! {
+    /// Flags for [`continue_event`].
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+    #[repr(transparent)]
+    pub struct ContinueFlags: u32 {
+        /// The pending exception has been handled by the debugger.
+        const EXCEPTION_HANDLED = 1 << 0;
+        /// Keep reporting exceptions as debug events.
+        const ENABLE_EXCEPTION_EVENT = 1 << 1;
+        /// Resume all threads of the process.
+        const CONTINUE_ALL = 1 << 2;
+        /// Resume all threads except the listed ones.
+        const CONTINUE_OTHERS = 1 << 3;
+    }
+}
+
+/// Resumes the debugged process after a debug event. \[3.0.0+\]
+///
+/// This is a safe wrapper around [`raw::continue_debug_event`]. `thread_ids` selects the threads
+/// to resume (or to keep stopped, with [`ContinueFlags::CONTINUE_OTHERS`]); it may hold at most
+/// [`MAX_CONTINUE_THREAD_IDS`] entries.
+pub fn continue_event(
+    handle: &DebugHandle,
+    flags: ContinueFlags,
+    thread_ids: &[ThreadId],
+) -> Result<(), ContinueEventError> {
+    if thread_ids.len() > MAX_CONTINUE_THREAD_IDS {
+        return Err(ContinueEventError::OutOfRange);
+    }
+
+    let rc = unsafe {
+        raw::continue_debug_event(
+            handle.to_raw(),
+            flags.bits(),
+            thread_ids.as_ptr() as *mut u64,
+            thread_ids.len() as u32,
+        )
+    };
+    RawResult::from_raw(rc).map((), |rc| Error::from(rc).into())
+}
+
+/// Error type for [`continue_event`].
+#[derive(Debug, ResultCodeError)]
+pub enum ContinueEventError {
+    /// The supplied handle is not a valid debug handle —
+    /// `KernelError::InvalidHandle` (raw code `0xE401`).
+    #[rc(desc = InvalidHandle)]
+    InvalidHandle,
+    /// The flags contain unknown bits —
+    /// `KernelError::InvalidEnumValue` (raw code `0xF001`).
+    #[rc(desc = InvalidEnumValue, msg = "Invalid continue flags")]
+    InvalidFlags,
+    /// More than [`MAX_CONTINUE_THREAD_IDS`] thread IDs were supplied —
+    /// `KernelError::OutOfRange` (raw code `0xEE01`).
+    #[rc(desc = OutOfRange, msg = "Too many thread ids")]
+    OutOfRange,
+    /// The debugged process has terminated —
+    /// `KernelError::InvalidState` (raw code `0xFA01`).
+    #[rc(desc = InvalidState)]
+    InvalidState,
+    /// There are still unread debug events —
+    /// `KernelError::Busy` (raw code `0xF401`).
+    #[rc(desc = Busy, msg = "Debug events pending")]
+    Busy,
+    /// Any unforeseen kernel error. Contains the original [`Error`] so callers
+    /// can inspect the raw result (`Error::to_raw`).
+    Unknown(Error),
+}
+
+bitflags::bitflags! {
+    /// Selects the parts of a [`raw::ThreadContext`] accessed by [`get_thread_context`] and
+    /// [`set_thread_context`].
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+    #[repr(transparent)]
+    pub struct ContextFlags: u32 {
+        /// General-purpose registers `x0`..`x28`.
+        const GENERAL = 1 << 0;
+        /// Control registers: `fp`, `lr`, `sp`, `pc`, `psr` and `tpidr`.
+        const CONTROL = 1 << 1;
+        /// NEON registers `v0`..`v31`.
+        const FPU = 1 << 2;
+        /// Floating-point control registers `fpcr` and `fpsr`.
+        const FPU_CONTROL = 1 << 3;
+        /// All of the above.
+        const ALL = Self::GENERAL.bits() | Self::CONTROL.bits() | Self::FPU.bits() | Self::FPU_CONTROL.bits();
+    }
+}
+
+/// Reads the registers of a thread in the debugged process.
+///
+/// This is a safe wrapper around [`raw::get_debug_thread_context`]. The thread must be stopped.
+/// Only the parts selected by `flags` are filled in; the rest of the returned context is zeroed.
+pub fn get_thread_context(
+    handle: &DebugHandle,
+    thread_id: ThreadId,
+    flags: ContextFlags,
+) -> Result<raw::ThreadContext, ThreadContextError> {
+    let mut ctx = raw::ThreadContext::zeroed();
+    let rc = unsafe {
+        raw::get_debug_thread_context(&mut ctx, handle.to_raw(), thread_id.0, flags.bits())
+    };
+    RawResult::from_raw(rc).map(ctx, |rc| Error::from(rc).into())
+}
+
+/// Writes the registers of a thread in the debugged process.
+///
+/// This is a safe wrapper around [`raw::set_debug_thread_context`]. The thread must be stopped.
+/// Only the parts selected by `flags` are written.
+pub fn set_thread_context(
+    handle: &DebugHandle,
+    thread_id: ThreadId,
+    ctx: &raw::ThreadContext,
+    flags: ContextFlags,
+) -> Result<(), ThreadContextError> {
+    // The kernel only reads the context; the raw binding merely declares the pointer as mutable.
+    let ctx = ctx as *const raw::ThreadContext as *mut raw::ThreadContext;
+    let rc =
+        unsafe { raw::set_debug_thread_context(handle.to_raw(), thread_id.0, ctx, flags.bits()) };
+    RawResult::from_raw(rc).map((), |rc| Error::from(rc).into())
+}
+
+/// Error type for [`get_thread_context`] and [`set_thread_context`].
+#[derive(Debug, ResultCodeError)]
+pub enum ThreadContextError {
+    /// The supplied handle is not a valid debug handle —
+    /// `KernelError::InvalidHandle` (raw code `0xE401`).
+    #[rc(desc = InvalidHandle)]
+    InvalidHandle,
+    /// The thread does not belong to the debugged process —
+    /// `KernelError::InvalidId` (raw code `0x40E01`).
+    #[rc(desc = InvalidId)]
+    InvalidThreadId,
+    /// The context flags contain unknown bits —
+    /// `KernelError::InvalidEnumValue` (raw code `0xF001`).
+    #[rc(desc = InvalidEnumValue, msg = "Invalid context flags")]
+    InvalidFlags,
+    /// The thread is not stopped —
+    /// `KernelError::InvalidState` (raw code `0xFA01`).
+    #[rc(desc = InvalidState)]
+    InvalidState,
+    /// Any unforeseen kernel error. Contains the original [`Error`] so callers
+    /// can inspect the raw result (`Error::to_raw`).
+    Unknown(Error),
+}
+
+/// Queries the memory region that contains `addr` in the debugged process.
+///
+/// This is a safe wrapper around [`raw::query_debug_process_memory`]. It is the remote
+/// counterpart of [`mem::query_memory`](crate::mem::query_memory).
+pub fn query_memory(
+    handle: &DebugHandle,
+    addr: u64,
+) -> Result<(MemoryInfo, PageInfo), QueryMemoryError> {
+    let mut mem_info = Default::default();
+    let mut page_info = Default::default();
+    let rc = unsafe {
+        raw::query_debug_process_memory(&mut mem_info, &mut page_info, handle.to_raw(), addr)
+    };
+    RawResult::from_raw(rc).map((mem_info.into(), page_info), |rc| Error::from(rc).into())
+}
+
+/// Error type for [`query_memory`].
+#[derive(Debug, ResultCodeError)]
+pub enum QueryMemoryError {
+    /// The supplied handle is not a valid debug handle —
+    /// `KernelError::InvalidHandle` (raw code `0xE401`).
+    #[rc(desc = InvalidHandle)]
+    InvalidHandle,
+    /// The address is outside the debugged process's address space —
+    /// `KernelError::InvalidAddress` (raw code `0xCC01`).
+    #[rc(desc = InvalidAddress)]
+    InvalidAddress,
+    /// Any unforeseen kernel error. Contains the original [`Error`] so callers
+    /// can inspect the raw result (`Error::to_raw`).
+    Unknown(Error),
+}
+
+/// Reads `buf.len()` bytes at `addr` in the debugged process into `buf`.
+///
+/// This is a safe wrapper around [`raw::read_debug_process_memory`]. The kernel ignores the
+/// target's page permissions, but the whole range must be mapped.
+pub fn read_memory(
+    handle: &DebugHandle,
+    addr: u64,
+    buf: &mut [u8],
+) -> Result<(), MemoryAccessError> {
+    let rc = unsafe {
+        raw::read_debug_process_memory(
+            buf.as_mut_ptr() as *mut c_void,
+            handle.to_raw(),
+            addr,
+            buf.len() as u64,
+        )
+    };
+    RawResult::from_raw(rc).map((), |rc| Error::from(rc).into())
+}
+
+/// Writes `buf` to `addr` in the debugged process.
+///
+/// This is a safe wrapper around [`raw::write_debug_process_memory`]. The kernel ignores the
+/// target's page permissions, so this can be used to patch code (e.g. to insert software
+/// breakpoints), but the whole range must be mapped.
+pub fn write_memory(handle: &DebugHandle, addr: u64, buf: &[u8]) -> Result<(), MemoryAccessError> {
+    let rc = unsafe {
+        raw::write_debug_process_memory(
+            handle.to_raw(),
+            buf.as_ptr() as *const c_void,
+            addr,
+            buf.len() as u64,
+        )
+    };
+    RawResult::from_raw(rc).map((), |rc| Error::from(rc).into())
+}
+
+/// Error type for [`read_memory`] and [`write_memory`].
+#[derive(Debug, ResultCodeError)]
+pub enum MemoryAccessError {
+    /// The supplied handle is not a valid debug handle —
+    /// `KernelError::InvalidHandle` (raw code `0xE401`).
+    #[rc(desc = InvalidHandle)]
+    InvalidHandle,
+    /// The remote address range wraps around the address space —
+    /// `KernelError::InvalidAddress` (raw code `0xCC01`).
+    #[rc(desc = InvalidAddress)]
+    InvalidAddress,
+    /// The size is zero —
+    /// `KernelError::InvalidSize` (raw code `0xCA01`).
+    #[rc(desc = InvalidSize)]
+    InvalidSize,
+    /// Part of the remote range is not mapped —
+    /// `KernelError::InvalidCurrentMemory` (raw code `0xD401`).
+    #[rc(desc = InvalidCurrentMemory, msg = "Invalid memory state")]
+    InvalidCurrentMemory,
+    /// The local buffer could not be accessed by the kernel —
+    /// `KernelError::InvalidPointer` (raw code `0xE601`).
+    #[rc(desc = InvalidPointer)]
+    InvalidPointer,
+    /// Any unforeseen kernel error. Contains the original [`Error`] so callers
+    /// can inspect the raw result (`Error::to_raw`).
+    Unknown(Error),
+}
+
+/// Programs a hardware breakpoint or watchpoint register.
+///
+/// This is a safe wrapper around [`raw::set_hardware_breakpoint`]. Build `encoding` with the
+/// types in [`breakpoint`]; pass [`Encoding::DISABLED`] to clear the register.
+pub fn set_hardware_breakpoint(
+    register: Register,
+    encoding: Encoding,
+) -> Result<(), SetHardwareBreakpointError> {
+    if !register.is_valid() {
+        return Err(SetHardwareBreakpointError::InvalidRegister);
+    }
+
+    let rc = unsafe {
+        raw::set_hardware_breakpoint(register.to_raw(), encoding.control, encoding.value)
+    };
+    RawResult::from_raw(rc).map((), |rc| Error::from(rc).into())
+}
+
+/// Error type for [`set_hardware_breakpoint`].
+#[derive(Debug, ResultCodeError)]
+pub enum SetHardwareBreakpointError {
+    /// The register does not exist on this CPU —
+    /// `KernelError::InvalidEnumValue` (raw code `0xF001`).
+    #[rc(desc = InvalidEnumValue, msg = "Invalid debug register")]
+    InvalidRegister,
+    /// The control register value is not accepted for this register (e.g. a context-ID
+    /// breakpoint in a register without context matching) —
+    /// `KernelError::InvalidCombination` (raw code `0xE801`).
+    #[rc(desc = InvalidCombination)]
+    InvalidCombination,
+    /// The debug handle of a context-ID breakpoint is invalid —
+    /// `KernelError::InvalidHandle` (raw code `0xE401`).
+    #[rc(desc = InvalidHandle)]
+    InvalidHandle,
+    /// Any unforeseen kernel error. Contains the original [`Error`] so callers
+    /// can inspect the raw result (`Error::to_raw`).
+    Unknown(Error),
+}
+
+/// Detaches the debugger by closing the debug handle.
+///
+/// The debugged process resumes execution once the last handle to its debug session is closed.
+pub fn detach(handle: DebugHandle) -> Result<(), CloseHandleError> {
+    let rc = unsafe { raw::close_handle(handle.to_raw()) };
+    RawResult::from_raw(rc).map((), |rc| Error::from(rc).into())
+}
+
+/// Error type for [`detach`].
+#[derive(Debug, ResultCodeError)]
+pub enum CloseHandleError {
+    /// The supplied handle is not a valid debug handle —
+    /// `KernelError::InvalidHandle` (raw code `0xE401`).
+    #[rc(desc = InvalidHandle)]
+    InvalidHandle,
+    /// Any unforeseen kernel error. Contains the original [`Error`] so callers
+    /// can inspect the raw result (`Error::to_raw`).
+    Unknown(Error),
+}
+
+/// A debug event together with the thread that raised it.
+///
+/// Returned by [`get_event`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DebugEventInfo {
+    /// ID of the thread that raised the event, or `ThreadId(0)` for process-wide events.
+    pub thread_id: ThreadId,
+    /// Whether the event stopped the process. A stopped process must be resumed with
+    /// [`continue_event`].
+    pub stopped: bool,
+    /// The decoded event.
+    pub event: DebugEvent,
+}
+
+/// A decoded debug event.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DebugEvent {
+    /// The debugger attached to the process.
+    AttachProcess {
+        /// Program ID (title ID) of the process.
+        program_id: u64,
+        /// ID of the process.
+        process_id: ProcessId,
+        /// Process name, NUL-padded.
+        name: [u8; 12],
+        /// Process creation flags.
+        flags: u32,
+        /// Address of the user exception context \[5.0.0+\].
+        user_exception_context_address: u64,
+    },
+    /// A thread was created, or already existed when the debugger attached.
+    AttachThread {
+        /// ID of the thread.
+        thread_id: ThreadId,
+        /// Address of the thread's TLS region.
+        tls_address: u64,
+        /// Entry point of the thread \[1.0.0-10.2.0\]; `0` on newer firmware.
+        entrypoint: u64,
+    },
+    /// The process exited.
+    ExitProcess {
+        /// Why the process exited.
+        reason: ProcessExitReason,
+    },
+    /// A thread exited.
+    ExitThread {
+        /// Why the thread exited.
+        reason: ThreadExitReason,
+    },
+    /// A thread raised an exception.
+    Exception {
+        /// Address of the faulting instruction.
+        address: u64,
+        /// The kind of exception, with its specific information.
+        exception: DebugException,
+    },
+    /// An event type unknown to this crate.
+    Unknown(u32),
+}
+
+/// Reason reported by [`DebugEvent::ExitProcess`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProcessExitReason {
+    /// The process called `svcExitProcess`.
+    ExitProcess,
+    /// The process was terminated.
+    TerminateProcess,
+    /// The process was terminated by an unhandled exception.
+    Exception,
+    /// A reason unknown to this crate.
+    Unknown(u32),
+}
+
+impl From<u32> for ProcessExitReason {
+    fn from(value: u32) -> Self {
+        match value {
+            0 => Self::ExitProcess,
+            1 => Self::TerminateProcess,
+            2 => Self::Exception,
+            other => Self::Unknown(other),
+        }
+    }
+}
+
+/// Reason reported by [`DebugEvent::ExitThread`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ThreadExitReason {
+    /// The thread called `svcExitThread`.
+    ExitThread,
+    /// The thread was terminated.
+    TerminateThread,
+    /// The owning process exited.
+    ExitProcess,
+    /// The owning process was terminated.
+    TerminateProcess,
+    /// A reason unknown to this crate.
+    Unknown(u32),
+}
+
+impl From<u32> for ThreadExitReason {
+    fn from(value: u32) -> Self {
+        match value {
+            0 => Self::ExitThread,
+            1 => Self::TerminateThread,
+            2 => Self::ExitProcess,
+            3 => Self::TerminateProcess,
+            other => Self::Unknown(other),
+        }
+    }
+}
+
+/// Exception subtype reported by [`DebugEvent::Exception`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DebugException {
+    /// An undefined instruction was executed.
+    UndefinedInstruction {
+        /// The instruction's encoding.
+        instruction: u32,
+    },
+    /// An instruction fetch faulted.
+    InstructionAbort,
+    /// A data access faulted.
+    DataAbort {
+        /// The faulting data address.
+        fault_address: u64,
+    },
+    /// A misaligned access faulted.
+    AlignmentFault {
+        /// The faulting data address.
+        fault_address: u64,
+    },
+    /// The debugger finished attaching. The process is stopped.
+    DebuggerAttached,
+    /// A hardware breakpoint or watchpoint was hit.
+    BreakPoint {
+        /// `true` for a watchpoint (data breakpoint), `false` for an instruction breakpoint.
+        is_watchpoint: bool,
+        /// The address that triggered the breakpoint.
+        address: u64,
+    },
+    /// The process called `svcBreak`.
+    UserBreak {
+        /// The raw break reason (see [`raw::BreakReason`]).
+        reason: u32,
+        /// Address of the user-supplied payload.
+        address: u64,
+        /// Size of the user-supplied payload.
+        size: u64,
+    },
+    /// The debugger stopped the process with [`break_process`].
+    DebuggerBreak {
+        /// IDs of the threads that were running on each core.
+        active_thread_ids: [ThreadId; 4],
+    },
+    /// An unknown SVC was called.
+    UndefinedSystemCall {
+        /// The SVC number.
+        id: u32,
+    },
+    /// A memory system error occurred.
+    MemorySystemError,
+    /// An exception type unknown to this crate.
+    Unknown(u32),
+}
+
+/// Raw layout of the event returned by `svcGetDebugEvent` (0x40 bytes).
+#[derive(Default)]
+#[repr(C)]
+struct RawDebugEventInfo {
+    typ: u32,
+    flags: u32,
+    thread_id: u64,
+    info: [u64; 6],
+}
+
+impl RawDebugEventInfo {
+    /// Returns the 32-bit word at `index` (in 4-byte units) of the event-specific data.
+    fn info_u32(&self, index: usize) -> u32 {
+        (self.info[index / 2] >> ((index % 2) * 32)) as u32
+    }
+
+    fn decode(&self) -> DebugEventInfo {
+        let info = &self.info;
+        let event = match self.typ {
+            0 => {
+                let mut name = [0u8; 12];
+                name[..8].copy_from_slice(&info[2].to_le_bytes());
+                name[8..].copy_from_slice(&self.info_u32(6).to_le_bytes());
+                DebugEvent::AttachProcess {
+                    program_id: info[0],
+                    process_id: ProcessId(info[1]),
+                    name,
+                    flags: self.info_u32(7),
+                    user_exception_context_address: info[4],
+                }
+            }
+            1 => DebugEvent::AttachThread {
+                thread_id: ThreadId(info[0]),
+                tls_address: info[1],
+                entrypoint: info[2],
+            },
+            2 => DebugEvent::ExitProcess {
+                reason: self.info_u32(0).into(),
+            },
+            3 => DebugEvent::ExitThread {
+                reason: self.info_u32(0).into(),
+            },
+            4 => DebugEvent::Exception {
+                address: info[1],
+                exception: self.decode_exception(),
+            },
+            other => DebugEvent::Unknown(other),
+        };
+
+        DebugEventInfo {
+            thread_id: ThreadId(self.thread_id),
+            stopped: self.flags & 1 != 0,
+            event,
+        }
+    }
+
+    /// Decodes the exception-specific data, which starts at `info[2]`.
+    fn decode_exception(&self) -> DebugException {
+        let info = &self.info;
+        match self.info_u32(0) {
+            0 => DebugException::UndefinedInstruction {
+                instruction: self.info_u32(4),
+            },
+            1 => DebugException::InstructionAbort,
+            2 => DebugException::DataAbort {
+                fault_address: info[2],
+            },
+            3 => DebugException::AlignmentFault {
+                fault_address: info[2],
+            },
+            4 => DebugException::DebuggerAttached,
+            5 => DebugException::BreakPoint {
+                is_watchpoint: self.info_u32(4) != 0,
+                address: info[3],
+            },
+            6 => DebugException::UserBreak {
+                reason: self.info_u32(4),
+                address: info[3],
+                size: info[4],
+            },
+            7 => DebugException::DebuggerBreak {
+                active_thread_ids: [info[2], info[3], info[4], info[5]].map(ThreadId),
+            },
+            8 => DebugException::UndefinedSystemCall {
+                id: self.info_u32(4),
+            },
+            9 => DebugException::MemorySystemError,
+            other => DebugException::Unknown(other),
+        }
+    }
+}
@@ -0,0 +1,400 @@
+//! Hardware breakpoint and watchpoint encoding.
+//!
+//! The kernel programs the AArch64 debug registers on behalf of the debugger:
+//! `svcSetHardwareBreakPoint` takes the register to program, the value of its control register
+//! (`DBGBCR<n>_EL1` or `DBGWCR<n>_EL1`) and the value of its value register (`DBGBVR<n>_EL1` or
+//! `DBGWVR<n>_EL1`).
+//!
+//! The builders in this module produce that pair of values as an [`Encoding`], to be passed to
+//! [`set_hardware_breakpoint`](super::set_hardware_breakpoint):
+//!
+//! - [`InstructionBreakpoint`]: stops on execution of an address, optionally only within the
+//!   debugged process (linked to a context-ID breakpoint).
+//! - [`ContextIdBreakpoint`]: matches the context ID of a debugged process; only useful as the link
+//!   target of other breakpoints and watchpoints.
+//! - [`Watchpoint`]: stops on loads and/or stores to a range of up to eight bytes.
+//!
+//! All breakpoints and watchpoints are configured to match at EL0 only.
+//!
+//! ## References
+//! - [Switchbrew Wiki: SVC](https://switchbrew.org/wiki/SVC#SetHardwareBreakPoint)
+//! - Arm Architecture Reference Manual, D13.3 (DBGBCR/DBGWCR)
+
+use super::DebugHandle;
+
+/// Number of hardware breakpoint registers addressable through the SVC.
+pub const MAX_BREAKPOINTS: u8 = 16;
+
+/// Number of hardware watchpoint registers addressable through the SVC.
+pub const MAX_WATCHPOINTS: u8 = 16;
+
+/// Control register `E` bit: enables the breakpoint or watchpoint.
+const ENABLE: u64 = 1 << 0;
+
+/// `PMC`/`PAC` field value matching EL0 only.
+const PRIV_EL0: u64 = 0b10 << 1;
+
+/// `BAS` field value matching a whole A64 instruction.
+const BAS_A64: u64 = 0b1111 << 5;
+
+/// Bit position of the `LBN` (linked breakpoint number) field.
+const LBN_SHIFT: u32 = 16;
+
+/// Bit position of the `BT` (breakpoint type) field.
+const BT_SHIFT: u32 = 20;
+
+/// `BT`: unlinked instruction address match.
+const BT_ADDRESS: u64 = 0b0000;
+
+/// `BT`: linked instruction address match.
+const BT_LINKED_ADDRESS: u64 = 0b0001;
+
+/// `BT`: linked context ID match.
+const BT_LINKED_CONTEXT_ID: u64 = 0b0011;
+
+/// Bit position of the watchpoint `BAS` (byte address select) field.
+const WATCH_BAS_SHIFT: u32 = 5;
+
+/// Bit position of the watchpoint `LSC` (load/store control) field.
+const LSC_SHIFT: u32 = 3;
+
+/// Watchpoint `WT` bit: the watchpoint is linked.
+const WT_LINKED: u64 = 1 << 20;
+
+/// A debug register programmable through `svcSetHardwareBreakPoint`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Register {
+    /// Breakpoint register `n` (`DBGBCR<n>`/`DBGBVR<n>`), `n < 16`.
+    Breakpoint(u8),
+    /// Watchpoint register `n` (`DBGWCR<n>`/`DBGWVR<n>`), `n < 16`.
+    Watchpoint(u8),
+}
+
+impl Register {
+    /// Returns the register name as understood by the kernel.
+    ///
+    /// Breakpoints are numbered `0..16` and watchpoints `16..32`.
+    pub const fn to_raw(self) -> u32 {
+        match self {
+            Self::Breakpoint(n) => n as u32,
+            Self::Watchpoint(n) => 0x10 + n as u32,
+        }
+    }
+
+    /// Returns `true` if the register index is addressable.
+    pub const fn is_valid(self) -> bool {
+        match self {
+            Self::Breakpoint(n) => n < MAX_BREAKPOINTS,
+            Self::Watchpoint(n) => n < MAX_WATCHPOINTS,
+        }
+    }
+}
+
+/// Values of a control register and its value register.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Encoding {
+    /// Value of the control register (`DBGBCR<n>` or `DBGWCR<n>`).
+    pub control: u64,
+    /// Value of the value register (`DBGBVR<n>` or `DBGWVR<n>`).
+    ///
+    /// For a [`ContextIdBreakpoint`] this is the raw debug handle; the kernel substitutes the
+    /// context ID of the debugged process.
+    pub value: u64,
+}
+
+impl Encoding {
+    /// Encoding that disables the register.
+    pub const DISABLED: Self = Self {
+        control: 0,
+        value: 0,
+    };
+}
+
+/// A hardware breakpoint on instruction execution.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InstructionBreakpoint {
+    address: u64,
+    linked: Option<u8>,
+}
+
+impl InstructionBreakpoint {
+    /// Creates a breakpoint on the instruction at `address`.
+    ///
+    /// An unlinked breakpoint matches in every process running at EL0; link it to a
+    /// [`ContextIdBreakpoint`] with [`linked_to`](Self::linked_to) to restrict it to the debugged
+    /// process.
+    pub const fn new(address: u64) -> Self {
+        Self {
+            address,
+            linked: None,
+        }
+    }
+
+    /// Only matches when the context-ID breakpoint in breakpoint register `index` also matches.
+    pub const fn linked_to(mut self, index: u8) -> Self {
+        self.linked = Some(index);
+        self
+    }
+
+    /// Encodes the breakpoint into its register values.
+    pub const fn encode(&self) -> Result<Encoding, EncodeError> {
+        if !self.address.is_multiple_of(4) {
+            return Err(EncodeError::MisalignedAddress);
+        }
+
+        let link = match self.linked {
+            None => BT_ADDRESS << BT_SHIFT,
+            Some(index) if index < MAX_BREAKPOINTS => {
+                (BT_LINKED_ADDRESS << BT_SHIFT) | ((index as u64) << LBN_SHIFT)
+            }
+            Some(_) => return Err(EncodeError::InvalidLink),
+        };
+
+        Ok(Encoding {
+            control: ENABLE | PRIV_EL0 | BAS_A64 | link,
+            value: self.address,
+        })
+    }
+}
+
+/// A context-ID breakpoint matching the debugged process.
+///
+/// On its own it never stops execution; [`InstructionBreakpoint`]s and [`Watchpoint`]s link to it
+/// to only match inside the debugged process. The kernel only accepts context-ID breakpoints in
+/// the breakpoint registers that implement context matching (the highest-numbered ones, e.g. 4
+/// and 5 on Cortex-A57).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ContextIdBreakpoint {
+    debug: u32,
+}
+
+impl ContextIdBreakpoint {
+    /// Creates a context-ID breakpoint for the process attached to `handle`.
+    pub fn new(handle: &DebugHandle) -> Self {
+        Self {
+            debug: handle.to_raw(),
+        }
+    }
+
+    /// Encodes the breakpoint into its register values.
+    pub const fn encode(&self) -> Encoding {
+        Encoding {
+            control: ENABLE | PRIV_EL0 | BAS_A64 | (BT_LINKED_CONTEXT_ID << BT_SHIFT),
+            value: self.debug as u64,
+        }
+    }
+}
+
+/// Memory accesses that trigger a [`Watchpoint`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WatchAccess {
+    /// Loads only.
+    Load,
+    /// Stores only.
+    Store,
+    /// Loads and stores.
+    LoadStore,
+}
+
+impl WatchAccess {
+    /// Returns the `LSC` field value.
+    const fn lsc(self) -> u64 {
+        match self {
+            Self::Load => 0b01,
+            Self::Store => 0b10,
+            Self::LoadStore => 0b11,
+        }
+    }
+}
+
+/// A hardware watchpoint on data accesses.
+///
+/// A watchpoint covers `len` consecutive bytes starting at `address`. The range must not cross an
+/// eight-byte boundary: the value register holds the doubleword-aligned address and the
+/// byte-address-select field picks the bytes within that doubleword.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Watchpoint {
+    address: u64,
+    len: u8,
+    access: WatchAccess,
+    linked: Option<u8>,
+}
+
+impl Watchpoint {
+    /// Creates a watchpoint on `len` bytes at `address`, triggered by `access`.
+    pub const fn new(address: u64, len: u8, access: WatchAccess) -> Self {
+        Self {
+            address,
+            len,
+            access,
+            linked: None,
+        }
+    }
+
+    /// Only matches when the context-ID breakpoint in breakpoint register `index` also matches.
+    pub const fn linked_to(mut self, index: u8) -> Self {
+        self.linked = Some(index);
+        self
+    }
+
+    /// Encodes the watchpoint into its register values.
+    pub const fn encode(&self) -> Result<Encoding, EncodeError> {
+        if self.len == 0 || self.len > 8 {
+            return Err(EncodeError::InvalidLength);
+        }
+
+        let offset = (self.address % 8) as u32;
+        if offset + self.len as u32 > 8 {
+            return Err(EncodeError::CrossesDoubleword);
+        }
+
+        let bas = ((1u64 << self.len) - 1) << offset;
+
+        let link = match self.linked {
+            None => 0,
+            Some(index) if index < MAX_BREAKPOINTS => WT_LINKED | ((index as u64) << LBN_SHIFT),
+            Some(_) => return Err(EncodeError::InvalidLink),
+        };
+
+        Ok(Encoding {
+            control: ENABLE
+                | PRIV_EL0
+                | (self.access.lsc() << LSC_SHIFT)
+                | (bas << WATCH_BAS_SHIFT)
+                | link,
+            value: self.address & !7,
+        })
+    }
+}
+
+/// Error type for [`InstructionBreakpoint::encode`] and [`Watchpoint::encode`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+pub enum EncodeError {
+    /// The breakpoint address is not 4-byte aligned.
+    #[error("Misaligned breakpoint address")]
+    MisalignedAddress,
+    /// The watchpoint length is not between 1 and 8 bytes.
+    #[error("Invalid watchpoint length")]
+    InvalidLength,
+    /// The watched range crosses an eight-byte boundary.
+    #[error("Watchpoint crosses a doubleword boundary")]
+    CrossesDoubleword,
+    /// The linked breakpoint register index is out of range.
+    #[error("Invalid linked breakpoint index")]
+    InvalidLink,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn register_names() {
+        assert_eq!(Register::Breakpoint(0).to_raw(), 0);
+        assert_eq!(Register::Breakpoint(5).to_raw(), 5);
+        assert_eq!(Register::Watchpoint(0).to_raw(), 0x10);
+        assert_eq!(Register::Watchpoint(3).to_raw(), 0x13);
+        assert!(Register::Breakpoint(15).is_valid());
+        assert!(!Register::Watchpoint(16).is_valid());
+    }
+
+    #[test]
+    fn unlinked_instruction_breakpoint() {
+        let enc = InstructionBreakpoint::new(0x7100_0040).encode().unwrap();
+        // E=1, PMC=0b10, BAS=0b1111, BT=0b0000
+        assert_eq!(enc.control, 0x1E5);
+        assert_eq!(enc.value, 0x7100_0040);
+    }
+
+    #[test]
+    fn linked_instruction_breakpoint() {
+        let enc = InstructionBreakpoint::new(0x7100_0040)
+            .linked_to(4)
+            .encode()
+            .unwrap();
+        // BT=0b0001, LBN=4
+        assert_eq!(enc.control, 0x0014_01E5);
+        assert_eq!(enc.value, 0x7100_0040);
+    }
+
+    #[test]
+    fn instruction_breakpoint_errors() {
+        assert_eq!(
+            InstructionBreakpoint::new(0x7100_0042).encode(),
+            Err(EncodeError::MisalignedAddress)
+        );
+        assert_eq!(
+            InstructionBreakpoint::new(0x7100_0040)
+                .linked_to(16)
+                .encode(),
+            Err(EncodeError::InvalidLink)
+        );
+    }
+
+    #[test]
+    fn context_id_breakpoint() {
+        let enc = ContextIdBreakpoint { debug: 0x1234 }.encode();
+        // BT=0b0011
+        assert_eq!(enc.control, 0x0030_01E5);
+        assert_eq!(enc.value, 0x1234);
+    }
+
+    #[test]
+    fn watchpoint_byte_address_select() {
+        // 4 bytes at offset 0: BAS=0b0000_1111
+        let enc = Watchpoint::new(0x1000, 4, WatchAccess::Store)
+            .encode()
+            .unwrap();
+        assert_eq!(enc.control, 1 | (0b10 << 1) | (0b10 << 3) | (0x0F << 5));
+        assert_eq!(enc.value, 0x1000);
+
+        // 2 bytes at offset 6: BAS=0b1100_0000, address aligned down.
+        let enc = Watchpoint::new(0x1006, 2, WatchAccess::Load)
+            .encode()
+            .unwrap();
+        assert_eq!(enc.control, 1 | (0b10 << 1) | (0b01 << 3) | (0xC0 << 5));
+        assert_eq!(enc.value, 0x1000);
+
+        // 8 bytes: BAS=0xFF
+        let enc = Watchpoint::new(0x2008, 8, WatchAccess::LoadStore)
+            .encode()
+            .unwrap();
+        assert_eq!(enc.control, 1 | (0b10 << 1) | (0b11 << 3) | (0xFF << 5));
+        assert_eq!(enc.value, 0x2008);
+    }
+
+    #[test]
+    fn linked_watchpoint() {
+        let enc = Watchpoint::new(0x1001, 1, WatchAccess::Store)
+            .linked_to(5)
+            .encode()
+            .unwrap();
+        assert_eq!(
+            enc.control,
+            1 | (0b10 << 1) | (0b10 << 3) | (0x02 << 5) | (5 << 16) | (1 << 20)
+        );
+        assert_eq!(enc.value, 0x1000);
+    }
+
+    #[test]
+    fn watchpoint_errors() {
+        assert_eq!(
+            Watchpoint::new(0x1000, 0, WatchAccess::Load).encode(),
+            Err(EncodeError::InvalidLength)
+        );
+        assert_eq!(
+            Watchpoint::new(0x1000, 9, WatchAccess::Load).encode(),
+            Err(EncodeError::InvalidLength)
+        );
+        assert_eq!(
+            Watchpoint::new(0x1004, 8, WatchAccess::Load).encode(),
+            Err(EncodeError::CrossesDoubleword)
+        );
+        assert_eq!(
+            Watchpoint::new(0x1000, 4, WatchAccess::Load)
+                .linked_to(16)
+                .encode(),
+            Err(EncodeError::InvalidLink)
+        );
+    }
+}
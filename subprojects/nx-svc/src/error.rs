@@ -1,6 +1,14 @@
 //! Error codes for the SVC.
 //!
 //! This module contains the error codes for the SVC.
+//!
+//! It also contains a catalogue of the well-known result descriptions of the most common
+//! modules (see [`description_name`]), used to print human-readable result codes.
+
+mod catalogue;
+
+pub use catalogue::description_name;
+pub use nx_svc_derive::ResultCodeError;
 
 /// Identifies which module caused an error.
 ///
@@ -178,6 +186,351 @@ pub enum Module {
     ShopN = 811,
 }
 
+impl Module {
+    /// Converts a raw module number into a [`Module`], if it is a known one.
+    pub const fn from_raw(value: u32) -> Option<Module> {
+        Some(match value {
+            1 => Module::Kernel,
+            2 => Module::FS,
+            3 => Module::OS,
+            4 => Module::HTCS,
+            5 => Module::NCM,
+            6 => Module::DD,
+            8 => Module::LR,
+            9 => Module::Loader,
+            10 => Module::CMIF,
+            11 => Module::HIPC,
+            12 => Module::TMA,
+            13 => Module::DMNT,
+            14 => Module::GDS,
+            15 => Module::PM,
+            16 => Module::NS,
+            17 => Module::BSDSockets,
+            18 => Module::HTC,
+            19 => Module::TSC,
+            20 => Module::NCMContent,
+            21 => Module::SM,
+            22 => Module::RO,
+            23 => Module::GC,
+            24 => Module::SDMMC,
+            25 => Module::OVLN,
+            26 => Module::SPL,
+            27 => Module::Socket,
+            29 => Module::HTCLOW,
+            30 => Module::DDSF,
+            31 => Module::HTCFS,
+            32 => Module::Async,
+            33 => Module::Util,
+            35 => Module::TIPC,
+            37 => Module::ANIF,
+            100 => Module::ETHC,
+            101 => Module::I2C,
+            102 => Module::GPIO,
+            103 => Module::UART,
+            104 => Module::CPAD,
+            105 => Module::Settings,
+            106 => Module::FTM,
+            107 => Module::WLAN,
+            108 => Module::XCD,
+            109 => Module::TMP451,
+            110 => Module::NIFM,
+            111 => Module::HwOpus,
+            112 => Module::LSM6DS3,
+            113 => Module::Bluetooth,
+            114 => Module::VI,
+            115 => Module::NFP,
+            116 => Module::Time,
+            117 => Module::FGM,
+            118 => Module::OE,
+            119 => Module::BH1730FVC,
+            120 => Module::PCIe,
+            121 => Module::Friends,
+            122 => Module::BCAT,
+            123 => Module::SSLSrv,
+            124 => Module::Account,
+            125 => Module::News,
+            126 => Module::Mii,
+            127 => Module::NFC,
+            128 => Module::AM,
+            129 => Module::PlayReport,
+            130 => Module::AHID,
+            132 => Module::Qlaunch,
+            133 => Module::PCV,
+            134 => Module::USBPD,
+            135 => Module::BPC,
+            136 => Module::PSM,
+            137 => Module::NIM,
+            138 => Module::PSC,
+            139 => Module::TC,
+            140 => Module::USB,
+            141 => Module::NSD,
+            142 => Module::PCTL,
+            143 => Module::BTM,
+            144 => Module::LA,
+            145 => Module::ETicket,
+            146 => Module::NGC,
+            147 => Module::ERPT,
+            148 => Module::APM,
+            149 => Module::CEC,
+            150 => Module::Profiler,
+            151 => Module::ErrorUpload,
+            152 => Module::LIDBE,
+            153 => Module::Audio,
+            154 => Module::NPNS,
+            155 => Module::NPNSHTTPSTREAM,
+            157 => Module::ARP,
+            158 => Module::SWKBD,
+            159 => Module::BOOT,
+            160 => Module::NetDiag,
+            161 => Module::NFCMifare,
+            162 => Module::UserlandAssert,
+            163 => Module::Fatal,
+            164 => Module::NIMShop,
+            165 => Module::SPSM,
+            167 => Module::BGTC,
+            168 => Module::UserlandCrash,
+            169 => Module::SASBUS,
+            170 => Module::PI,
+            172 => Module::AudioCtrl,
+            173 => Module::LBL,
+            175 => Module::JIT,
+            176 => Module::HDCP,
+            177 => Module::OMM,
+            178 => Module::PDM,
+            179 => Module::OLSC,
+            180 => Module::SREPO,
+            181 => Module::Dauth,
+            182 => Module::STDFU,
+            183 => Module::DBG,
+            186 => Module::DHCPS,
+            187 => Module::SPI,
+            188 => Module::AVM,
+            189 => Module::PWM,
+            191 => Module::RTC,
+            192 => Module::Regulator,
+            193 => Module::LED,
+            195 => Module::SIO,
+            196 => Module::PCM,
+            197 => Module::CLKRST,
+            198 => Module::POWCTL,
+            201 => Module::AudioOld,
+            202 => Module::HID,
+            203 => Module::LDN,
+            204 => Module::CS,
+            205 => Module::Irsensor,
+            206 => Module::Capture,
+            208 => Module::Manu,
+            209 => Module::ATK,
+            210 => Module::WEB,
+            211 => Module::LCS,
+            212 => Module::GRC,
+            213 => Module::Repair,
+            214 => Module::Album,
+            215 => Module::RID,
+            216 => Module::Migration,
+            217 => Module::MigrationLdcServ,
+            218 => Module::HIDBUS,
+            219 => Module::ENS,
+            223 => Module::WebSocket,
+            227 => Module::DCDMTP,
+            228 => Module::PGL,
+            229 => Module::Notification,
+            230 => Module::INS,
+            231 => Module::LP2P,
+            232 => Module::RCD,
+            233 => Module::LCM40607,
+            235 => Module::PRC,
+            237 => Module::TMAHTC,
+            238 => Module::ECTX,
+            239 => Module::MNPP,
+            240 => Module::HSHL,
+            242 => Module::CAPMTP,
+            244 => Module::DP2HDMI,
+            245 => Module::Cradle,
+            246 => Module::SProfile,
+            250 => Module::NDRM,
+            499 => Module::TSPM,
+            500 => Module::DevMenu,
+            800 => Module::GeneralWebApplet,
+            809 => Module::WifiWebAuthApplet,
+            810 => Module::WhitelistedApplet,
+            811 => Module::ShopN,
+            _ => return None,
+        })
+    }
+
+    /// Returns the module's name, e.g. `"FS"`.
+    pub const fn name(self) -> &'static str {
+        match self {
+            Module::Kernel => "Kernel",
+            Module::FS => "FS",
+            Module::OS => "OS",
+            Module::HTCS => "HTCS",
+            Module::NCM => "NCM",
+            Module::DD => "DD",
+            Module::LR => "LR",
+            Module::Loader => "Loader",
+            Module::CMIF => "CMIF",
+            Module::HIPC => "HIPC",
+            Module::TMA => "TMA",
+            Module::DMNT => "DMNT",
+            Module::GDS => "GDS",
+            Module::PM => "PM",
+            Module::NS => "NS",
+            Module::BSDSockets => "BSDSockets",
+            Module::HTC => "HTC",
+            Module::TSC => "TSC",
+            Module::NCMContent => "NCMContent",
+            Module::SM => "SM",
+            Module::RO => "RO",
+            Module::GC => "GC",
+            Module::SDMMC => "SDMMC",
+            Module::OVLN => "OVLN",
+            Module::SPL => "SPL",
+            Module::Socket => "Socket",
+            Module::HTCLOW => "HTCLOW",
+            Module::DDSF => "DDSF",
+            Module::HTCFS => "HTCFS",
+            Module::Async => "Async",
+            Module::Util => "Util",
+            Module::TIPC => "TIPC",
+            Module::ANIF => "ANIF",
+            Module::ETHC => "ETHC",
+            Module::I2C => "I2C",
+            Module::GPIO => "GPIO",
+            Module::UART => "UART",
+            Module::CPAD => "CPAD",
+            Module::Settings => "Settings",
+            Module::FTM => "FTM",
+            Module::WLAN => "WLAN",
+            Module::XCD => "XCD",
+            Module::TMP451 => "TMP451",
+            Module::NIFM => "NIFM",
+            Module::HwOpus => "HwOpus",
+            Module::LSM6DS3 => "LSM6DS3",
+            Module::Bluetooth => "Bluetooth",
+            Module::VI => "VI",
+            Module::NFP => "NFP",
+            Module::Time => "Time",
+            Module::FGM => "FGM",
+            Module::OE => "OE",
+            Module::BH1730FVC => "BH1730FVC",
+            Module::PCIe => "PCIe",
+            Module::Friends => "Friends",
+            Module::BCAT => "BCAT",
+            Module::SSLSrv => "SSLSrv",
+            Module::Account => "Account",
+            Module::News => "News",
+            Module::Mii => "Mii",
+            Module::NFC => "NFC",
+            Module::AM => "AM",
+            Module::PlayReport => "PlayReport",
+            Module::AHID => "AHID",
+            Module::Qlaunch => "Qlaunch",
+            Module::PCV => "PCV",
+            Module::USBPD => "USBPD",
+            Module::BPC => "BPC",
+            Module::PSM => "PSM",
+            Module::NIM => "NIM",
+            Module::PSC => "PSC",
+            Module::TC => "TC",
+            Module::USB => "USB",
+            Module::NSD => "NSD",
+            Module::PCTL => "PCTL",
+            Module::BTM => "BTM",
+            Module::LA => "LA",
+            Module::ETicket => "ETicket",
+            Module::NGC => "NGC",
+            Module::ERPT => "ERPT",
+            Module::APM => "APM",
+            Module::CEC => "CEC",
+            Module::Profiler => "Profiler",
+            Module::ErrorUpload => "ErrorUpload",
+            Module::LIDBE => "LIDBE",
+            Module::Audio => "Audio",
+            Module::NPNS => "NPNS",
+            Module::NPNSHTTPSTREAM => "NPNSHTTPSTREAM",
+            Module::ARP => "ARP",
+            Module::SWKBD => "SWKBD",
+            Module::BOOT => "BOOT",
+            Module::NetDiag => "NetDiag",
+            Module::NFCMifare => "NFCMifare",
+            Module::UserlandAssert => "UserlandAssert",
+            Module::Fatal => "Fatal",
+            Module::NIMShop => "NIMShop",
+            Module::SPSM => "SPSM",
+            Module::BGTC => "BGTC",
+            Module::UserlandCrash => "UserlandCrash",
+            Module::SASBUS => "SASBUS",
+            Module::PI => "PI",
+            Module::AudioCtrl => "AudioCtrl",
+            Module::LBL => "LBL",
+            Module::JIT => "JIT",
+            Module::HDCP => "HDCP",
+            Module::OMM => "OMM",
+            Module::PDM => "PDM",
+            Module::OLSC => "OLSC",
+            Module::SREPO => "SREPO",
+            Module::Dauth => "Dauth",
+            Module::STDFU => "STDFU",
+            Module::DBG => "DBG",
+            Module::DHCPS => "DHCPS",
+            Module::SPI => "SPI",
+            Module::AVM => "AVM",
+            Module::PWM => "PWM",
+            Module::RTC => "RTC",
+            Module::Regulator => "Regulator",
+            Module::LED => "LED",
+            Module::SIO => "SIO",
+            Module::PCM => "PCM",
+            Module::CLKRST => "CLKRST",
+            Module::POWCTL => "POWCTL",
+            Module::AudioOld => "AudioOld",
+            Module::HID => "HID",
+            Module::LDN => "LDN",
+            Module::CS => "CS",
+            Module::Irsensor => "Irsensor",
+            Module::Capture => "Capture",
+            Module::Manu => "Manu",
+            Module::ATK => "ATK",
+            Module::WEB => "WEB",
+            Module::LCS => "LCS",
+            Module::GRC => "GRC",
+            Module::Repair => "Repair",
+            Module::Album => "Album",
+            Module::RID => "RID",
+            Module::Migration => "Migration",
+            Module::MigrationLdcServ => "MigrationLdcServ",
+            Module::HIDBUS => "HIDBUS",
+            Module::ENS => "ENS",
+            Module::WebSocket => "WebSocket",
+            Module::DCDMTP => "DCDMTP",
+            Module::PGL => "PGL",
+            Module::Notification => "Notification",
+            Module::INS => "INS",
+            Module::LP2P => "LP2P",
+            Module::RCD => "RCD",
+            Module::LCM40607 => "LCM40607",
+            Module::PRC => "PRC",
+            Module::TMAHTC => "TMAHTC",
+            Module::ECTX => "ECTX",
+            Module::MNPP => "MNPP",
+            Module::HSHL => "HSHL",
+            Module::CAPMTP => "CAPMTP",
+            Module::DP2HDMI => "DP2HDMI",
+            Module::Cradle => "Cradle",
+            Module::SProfile => "SProfile",
+            Module::NDRM => "NDRM",
+            Module::TSPM => "TSPM",
+            Module::DevMenu => "DevMenu",
+            Module::GeneralWebApplet => "GeneralWebApplet",
+            Module::WifiWebAuthApplet => "WifiWebAuthApplet",
+            Module::WhitelistedApplet => "WhitelistedApplet",
+            Module::ShopN => "ShopN",
+        }
+    }
+}
+
 /// Error description types
 pub type Description = u32;
 
@@ -198,7 +551,11 @@ impl IntoDescription for u32 {
 pub type ResultCode = u32;
 
 /// Converts an error enum into the raw error code
-// TODO: Seal this trait
+///
+/// This trait is intentionally left unsealed: crates built on top of `nx-svc` (e.g. the `nx-sys-*`
+/// crates and service wrappers) implement it for their own error types. Prefer deriving it, along
+/// with the reverse conversion, with [`ResultCodeError`] so that all implementations encode result
+/// codes consistently.
 pub trait ToRawResultCode {
     /// Converts the error enum into a raw error code
     fn to_rc(self) -> ResultCode;
@@ -212,8 +569,8 @@ impl ToRawResultCode for u32 {
 
 impl ToRawResultCode for (Module, Description) {
     fn to_rc(self) -> ResultCode {
-        // The module is shifted left by 9 bits, and the error code is OR'd with it.
-        ((self.0 as u32) << 9) | self.1
+        // The description is shifted left by 9 bits, and the module is OR'd with it.
+        (self.0 as u32) | (self.1 << 9)
     }
 }
 
@@ -248,6 +605,7 @@ pub enum KernelError {
     NotFound = 121,
     Busy = 122,
     SessionClosed = 123,
+    NotHandled = 124,
     InvalidState = 125,
     ReservedUsed = 126,
     PortClosed = 131,
@@ -283,3 +641,91 @@ impl ToRawResultCode for KernelError {
         (Module::Kernel, self.into_value()).to_rc()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    extern crate alloc;
+
+    use alloc::{format, string::ToString};
+
+    use super::*;
+    use crate::result::Error;
+
+    #[derive(Debug, ResultCodeError)]
+    enum DerivedError {
+        #[rc(desc = InvalidHandle)]
+        InvalidHandle,
+        #[rc(desc = OutOfResource, msg = "Kernel out of resources")]
+        OutOfResource,
+        #[rc(module = FS, desc = 1)]
+        PathNotFound,
+        Unknown(Error),
+    }
+
+    #[test]
+    fn module_description_encoding() {
+        // The module takes bits 0..9, the description bits 9..22
+        assert_eq!((Module::Kernel, 114).to_rc(), 0xE401);
+        assert_eq!((Module::FS, 1).to_rc(), 0x202);
+        assert_eq!(KernelError::OutOfResource.to_rc(), 0xCE01);
+        assert_eq!(KernelError::LimitReached.to_rc(), 0x1_0801);
+    }
+
+    #[test]
+    fn derived_to_rc() {
+        assert_eq!(DerivedError::InvalidHandle.to_rc(), 0xE401);
+        assert_eq!(DerivedError::OutOfResource.to_rc(), 0xCE01);
+        assert_eq!(DerivedError::PathNotFound.to_rc(), 0x202);
+        assert_eq!(
+            DerivedError::Unknown(Error::from_raw(0xFA01)).to_rc(),
+            0xFA01
+        );
+    }
+
+    #[test]
+    fn derived_round_trip() {
+        for err in [
+            DerivedError::InvalidHandle,
+            DerivedError::OutOfResource,
+            DerivedError::PathNotFound,
+            DerivedError::Unknown(Error::from_raw(0xFA01)),
+        ] {
+            let rc = err.to_rc();
+            assert_eq!(DerivedError::from(rc).to_rc(), rc);
+            assert_eq!(DerivedError::from(Error::from_raw(rc)).to_rc(), rc);
+        }
+
+        assert!(matches!(
+            DerivedError::from(0xE401),
+            DerivedError::InvalidHandle
+        ));
+        assert!(matches!(
+            DerivedError::from(0x202),
+            DerivedError::PathNotFound
+        ));
+        // Same description, different module
+        assert!(matches!(
+            DerivedError::from(0x3),
+            DerivedError::Unknown(err) if err.to_raw() == 0x3
+        ));
+        assert!(matches!(
+            DerivedError::from((Module::FS, 114).to_rc()),
+            DerivedError::Unknown(_)
+        ));
+    }
+
+    #[test]
+    fn derived_display() {
+        // Messages default to the variant name split into words
+        assert_eq!(DerivedError::InvalidHandle.to_string(), "Invalid handle");
+        assert_eq!(DerivedError::PathNotFound.to_string(), "Path not found");
+        assert_eq!(
+            DerivedError::OutOfResource.to_string(),
+            "Kernel out of resources"
+        );
+        assert_eq!(
+            format!("{}", DerivedError::from(0xFA01)),
+            "Unknown error: 2001-0125 (Kernel: InvalidState)"
+        );
+    }
+}
@@ -0,0 +1,391 @@
+//! Result description catalogue.
+//!
+//! Maps the description values of the most common modules to their symbolic names, as used by
+//! Atmosphère and the Switchbrew wiki. Ranges (e.g. `FS: 4000-4999 DataCorrupted`) are not listed;
+//! only the individual, well-known descriptions are.
+//!
+//! Each table is sorted by description value.
+
+use super::{Description, Module};
+
+/// Returns the symbolic name of `description` within `module`, if known.
+pub fn description_name(module: Module, description: Description) -> Option<&'static str> {
+    let table = match module {
+        Module::Kernel => KERNEL,
+        Module::FS => FS,
+        Module::OS => OS,
+        Module::NCM => NCM,
+        Module::LR => LR,
+        Module::Loader => LOADER,
+        Module::CMIF => CMIF,
+        Module::HIPC => HIPC,
+        Module::PM => PM,
+        Module::NS => NS,
+        Module::SM => SM,
+        Module::RO => RO,
+        Module::SPL => SPL,
+        Module::TIPC => TIPC,
+        Module::Settings => SETTINGS,
+        Module::Time => TIME,
+        _ => return None,
+    };
+
+    table
+        .binary_search_by_key(&description, |&(desc, _)| desc)
+        .ok()
+        .map(|idx| table[idx].1)
+}
+
+type Table = &'static [(Description, &'static str)];
+
+const KERNEL: Table = &[
+    (7, "OutOfSessions"),
+    (14, "InvalidArgument"),
+    (33, "NotImplemented"),
+    (57, "NoSynchronizationObject"),
+    (59, "TerminationRequested"),
+    (101, "InvalidSize"),
+    (102, "InvalidAddress"),
+    (103, "OutOfResource"),
+    (104, "OutOfMemory"),
+    (105, "OutOfHandles"),
+    (106, "InvalidCurrentMemory"),
+    (108, "InvalidNewMemoryPermission"),
+    (110, "InvalidMemoryRegion"),
+    (112, "InvalidPriority"),
+    (113, "InvalidCoreId"),
+    (114, "InvalidHandle"),
+    (115, "InvalidPointer"),
+    (116, "InvalidCombination"),
+    (117, "TimedOut"),
+    (118, "Cancelled"),
+    (119, "OutOfRange"),
+    (120, "InvalidEnumValue"),
+    (121, "NotFound"),
+    (122, "Busy"),
+    (123, "SessionClosed"),
+    (124, "NotHandled"),
+    (125, "InvalidState"),
+    (126, "ReservedUsed"),
+    (131, "PortClosed"),
+    (132, "LimitReached"),
+    (258, "ReceiveListBroken"),
+    (259, "OutOfAddressSpace"),
+    (260, "MessageTooLarge"),
+    (519, "InvalidId"),
+];
+
+const FS: Table = &[
+    (1, "PathNotFound"),
+    (2, "PathAlreadyExists"),
+    (7, "TargetLocked"),
+    (8, "DirectoryNotEmpty"),
+    (13, "DirectoryStatusChanged"),
+    (30, "UsableSpaceNotEnough"),
+    (31, "UsableSpaceNotEnoughForMmc"),
+    (32, "UsableSpaceNotEnoughForMmcCalibration"),
+    (33, "UsableSpaceNotEnoughForMmcSafe"),
+    (34, "UsableSpaceNotEnoughForMmcUser"),
+    (35, "UsableSpaceNotEnoughForMmcSystem"),
+    (36, "UsableSpaceNotEnoughForSdCard"),
+    (50, "UnsupportedSdkVersion"),
+    (60, "MountNameAlreadyExists"),
+    (1001, "PartitionNotFound"),
+    (1002, "TargetNotFound"),
+    (2001, "SdCardNotPresent"),
+    (2520, "GameCardCardNotInserted"),
+    (3001, "NotImplemented"),
+    (3005, "OutOfRange"),
+    (6003, "TooLongPath"),
+    (6004, "InvalidCharacter"),
+    (6005, "InvalidPathFormat"),
+    (6006, "DirectoryUnobtainable"),
+    (6007, "NotNormalized"),
+    (6031, "DirectoryNotDeletable"),
+    (6032, "DirectoryNotRenamable"),
+    (6033, "IncompatiblePath"),
+    (6034, "RenameToOtherFileSystem"),
+    (6061, "InvalidOffset"),
+    (6062, "InvalidSize"),
+    (6063, "NullptrArgument"),
+    (6064, "InvalidAlignment"),
+    (6065, "InvalidMountName"),
+    (6066, "ExtensionSizeTooLarge"),
+    (6067, "ExtensionSizeInvalid"),
+    (6201, "FileExtensionWithoutOpenModeAllowAppend"),
+    (6202, "ReadNotPermitted"),
+    (6203, "WriteNotPermitted"),
+];
+
+const OS: Table = &[
+    (4, "Busy"),
+    (8, "OutOfMemory"),
+    (9, "OutOfResource"),
+    (12, "OutOfVirtualAddressSpace"),
+    (13, "ResourceLimit"),
+    (500, "OutOfHandles"),
+    (501, "InvalidHandle"),
+    (502, "InvalidCurrentMemoryState"),
+    (503, "InvalidTransferMemoryState"),
+    (504, "InvalidTransferMemorySize"),
+    (505, "OutOfTransferMemory"),
+    (506, "OutOfAddressSpace"),
+];
+
+const NCM: Table = &[
+    (1, "InvalidContentStorageBase"),
+    (2, "PlaceHolderAlreadyExists"),
+    (3, "PlaceHolderNotFound"),
+    (4, "ContentAlreadyExists"),
+    (5, "ContentNotFound"),
+    (7, "ContentMetaNotFound"),
+    (8, "AllocationFailed"),
+    (12, "UnknownStorage"),
+    (100, "InvalidContentStorage"),
+    (110, "InvalidContentMetaDatabase"),
+    (130, "InvalidPackageFormat"),
+    (140, "InvalidContentHash"),
+    (160, "InvalidInstallTaskState"),
+    (170, "InvalidPlaceHolderFile"),
+    (180, "BufferInsufficient"),
+    (190, "WriteToReadOnlyContentStorage"),
+    (200, "NotEnoughInstallSpace"),
+    (210, "SystemUpdateNotFoundInPackage"),
+    (220, "ContentInfoNotFound"),
+    (237, "DeltaNotFound"),
+    (240, "InvalidContentMetaKey"),
+    (250, "ContentStorageNotActive"),
+    (251, "GameCardContentStorageNotActive"),
+    (260, "ContentMetaDatabaseNotActive"),
+    (8181, "InvalidArgument"),
+    (8182, "InvalidOffset"),
+];
+
+const LR: Table = &[
+    (2, "ProgramNotFound"),
+    (3, "DataNotFound"),
+    (4, "UnknownStorageId"),
+    (5, "LocationResolverNotFound"),
+    (6, "HtmlDocumentNotFound"),
+    (7, "AddOnContentNotFound"),
+    (8, "ControlNotFound"),
+    (9, "LegalInformationNotFound"),
+    (10, "DebugProgramNotFound"),
+    (90, "TooManyRegisteredPaths"),
+];
+
+const LOADER: Table = &[
+    (1, "TooLongArgument"),
+    (2, "TooManyArguments"),
+    (3, "TooLargeMeta"),
+    (4, "InvalidMeta"),
+    (5, "InvalidNso"),
+    (6, "InvalidPath"),
+    (7, "TooManyProcesses"),
+    (8, "NotPinned"),
+    (9, "InvalidProgramId"),
+    (10, "InvalidVersion"),
+    (11, "InvalidAcidSignature"),
+    (12, "InvalidNcaSignature"),
+    (51, "InsufficientAddressSpace"),
+    (52, "InvalidNro"),
+    (53, "InvalidNrr"),
+    (54, "InvalidSignature"),
+    (55, "InsufficientNroRegistrations"),
+    (56, "InsufficientNrrRegistrations"),
+    (57, "NroAlreadyLoaded"),
+    (81, "InvalidAddress"),
+    (82, "InvalidSize"),
+    (84, "NotLoaded"),
+    (85, "NotRegistered"),
+    (86, "InvalidSession"),
+    (87, "InvalidProcess"),
+    (100, "UnknownCapability"),
+    (103, "InvalidCapabilityKernelFlags"),
+    (104, "InvalidCapabilitySyscallMask"),
+    (106, "InvalidCapabilityMapRange"),
+    (107, "InvalidCapabilityMapPage"),
+    (108, "InvalidCapabilityMapRegion"),
+    (111, "InvalidCapabilityInterruptPair"),
+    (113, "InvalidCapabilityApplicationType"),
+    (114, "InvalidCapabilityKernelVersion"),
+    (115, "InvalidCapabilityHandleTable"),
+    (116, "InvalidCapabilityDebugFlags"),
+    (200, "InternalError"),
+];
+
+const CMIF: Table = &[
+    (1, "NotSupported"),
+    (202, "InvalidHeaderSize"),
+    (211, "InvalidInHeader"),
+    (221, "UnknownCommandId"),
+    (232, "InvalidOutRawSize"),
+    (235, "InvalidNumInObjects"),
+    (236, "InvalidNumOutObjects"),
+    (239, "InvalidInObject"),
+    (261, "TargetNotFound"),
+    (301, "OutOfDomainEntries"),
+    (811, "RequestDeferred"),
+    (812, "RequestDeferredByUser"),
+];
+
+const HIPC: Table = &[
+    (102, "OutOfSessionMemory"),
+    (131, "OutOfSessions"),
+    (141, "PointerBufferTooSmall"),
+    (200, "OutOfDomains"),
+    (301, "SessionClosed"),
+    (402, "InvalidRequestSize"),
+    (403, "UnknownCommandType"),
+    (420, "InvalidCmifRequest"),
+    (491, "TargetNotDomain"),
+    (492, "DomainObjectNotFound"),
+];
+
+const PM: Table = &[
+    (1, "ProcessNotFound"),
+    (2, "AlreadyStarted"),
+    (3, "NotTerminated"),
+    (4, "DebugHookInUse"),
+    (5, "ApplicationRunning"),
+    (6, "InvalidSize"),
+];
+
+const NS: Table = &[(90, "Canceled"), (110, "OutOfMaxRunningTask")];
+
+const SM: Table = &[
+    (1, "OutOfProcesses"),
+    (2, "InvalidClient"),
+    (3, "OutOfSessions"),
+    (4, "AlreadyRegistered"),
+    (5, "OutOfServices"),
+    (6, "InvalidServiceName"),
+    (7, "NotRegistered"),
+    (8, "NotAllowed"),
+    (9, "TooLargeAccessControl"),
+];
+
+const RO: Table = &[
+    (2, "OutOfAddressSpace"),
+    (3, "AlreadyLoaded"),
+    (4, "InvalidNro"),
+    (6, "InvalidNrr"),
+    (7, "TooManyNro"),
+    (8, "TooManyNrr"),
+    (9, "NotAuthorized"),
+    (10, "InvalidNrrKind"),
+    (1023, "InternalError"),
+    (1025, "InvalidAddress"),
+    (1026, "InvalidSize"),
+    (1028, "NotLoaded"),
+    (1029, "NotRegistered"),
+    (1030, "InvalidSession"),
+    (1031, "InvalidProcess"),
+];
+
+const SPL: Table = &[
+    (1, "SecureMonitorNotImplemented"),
+    (2, "SecureMonitorInvalidArgument"),
+    (3, "SecureMonitorBusy"),
+    (4, "SecureMonitorNoAsyncOperation"),
+    (5, "SecureMonitorInvalidAsyncOperation"),
+    (6, "SecureMonitorNotPermitted"),
+    (7, "SecureMonitorNotInitialized"),
+    (100, "InvalidSize"),
+    (101, "UnknownSecureMonitorError"),
+    (102, "DecryptionFailed"),
+    (104, "OutOfKeySlots"),
+    (105, "InvalidKeySlot"),
+    (106, "BootReasonAlreadySet"),
+    (107, "BootReasonNotSet"),
+    (108, "InvalidArgument"),
+];
+
+const TIPC: Table = &[
+    (10, "InvalidMethod"),
+    (15, "InvalidMessageFormat"),
+    (20, "RequestDeferred"),
+    (30, "SessionClosed"),
+];
+
+const SETTINGS: Table = &[
+    (11, "SettingsItemNotFound"),
+    (101, "SettingsItemKeyAllocationFailed"),
+    (102, "SettingsItemValueAllocationFailed"),
+    (201, "SettingsNameNull"),
+    (202, "SettingsItemKeyNull"),
+    (203, "SettingsItemValueNull"),
+    (204, "SettingsItemKeyBufferNull"),
+    (205, "SettingsItemValueBufferNull"),
+    (221, "SettingsNameEmpty"),
+    (222, "SettingsItemKeyEmpty"),
+    (241, "SettingsNameTooLong"),
+    (242, "SettingsItemKeyTooLong"),
+    (261, "SettingsNameInvalidFormat"),
+    (262, "SettingsItemKeyInvalidFormat"),
+    (263, "SettingsItemValueInvalidFormat"),
+];
+
+const TIME: Table = &[
+    (200, "NotComparable"),
+    (201, "Overflowed"),
+    (800, "OutOfMemory"),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TABLES: &[(Module, Table)] = &[
+        (Module::Kernel, KERNEL),
+        (Module::FS, FS),
+        (Module::OS, OS),
+        (Module::NCM, NCM),
+        (Module::LR, LR),
+        (Module::Loader, LOADER),
+        (Module::CMIF, CMIF),
+        (Module::HIPC, HIPC),
+        (Module::PM, PM),
+        (Module::NS, NS),
+        (Module::SM, SM),
+        (Module::RO, RO),
+        (Module::SPL, SPL),
+        (Module::TIPC, TIPC),
+        (Module::Settings, SETTINGS),
+        (Module::Time, TIME),
+    ];
+
+    #[test]
+    fn tables_are_sorted_without_duplicates() {
+        // `description_name` binary-searches the tables
+        for &(module, table) in TABLES {
+            for pair in table.windows(2) {
+                assert!(
+                    pair[0].0 < pair[1].0,
+                    "{module:?}: {} is not followed by a larger description ({})",
+                    pair[0].0,
+                    pair[1].0,
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn every_entry_is_found() {
+        for &(module, table) in TABLES {
+            for &(desc, name) in table {
+                assert_eq!(description_name(module, desc), Some(name));
+            }
+        }
+    }
+
+    #[test]
+    fn lookup() {
+        assert_eq!(description_name(Module::FS, 1), Some("PathNotFound"));
+        assert_eq!(description_name(Module::FS, 6003), Some("TooLongPath"));
+        assert_eq!(description_name(Module::TIPC, 30), Some("SessionClosed"));
+        // Range starts are not listed
+        assert_eq!(description_name(Module::FS, 6000), None);
+        assert_eq!(description_name(Module::GPIO, 1), None);
+    }
+}
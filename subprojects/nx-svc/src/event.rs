@@ -0,0 +1,191 @@
+//! Kernel events for Horizon OS (Nintendo Switch)
+//!
+//! An event is a pair of kernel objects created together by `svcCreateEvent`:
+//!
+//! * A [`WritableEvent`] (the _server_ side), used to signal and clear the event.
+//! * A [`ReadableEvent`] (the _client_ side), which can be waited on and reset.
+//!
+//! Both halves refer to the same signalled state. Only the readable half is [`Waitable`], so it can
+//! be passed directly to [`wait_synchronization_single`] and [`wait_synchronization_multiple`].
+//!
+//! [`Waitable`]: crate::handle::Waitable
+//! [`wait_synchronization_single`]: crate::sync::wait_synchronization_single
+//! [`wait_synchronization_multiple`]: crate::sync::wait_synchronization_multiple
+
+use crate::{
+    error::ResultCodeError,
+    handle::OwnedHandle,
+    raw,
+    result::{Error, raw::Result as RawResult},
+    sync::{self, WaitSyncError},
+};
+
+define_waitable_handle_type! {
+    /// A handle to the readable (client) half of a kernel event.
+    ///
+    /// This is the half that other threads and processes wait on.
+    pub struct ReadableEvent
+}
+
+define_handle_type! {
+    /// A handle to the writable (server) half of a kernel event.
+    ///
+    /// This is the half used to signal and clear the event.
+    pub struct WritableEvent
+}
+
+/// Creates a new kernel event.
+///
+/// This is a safe wrapper around [`raw::create_event`]. The event starts in the *cleared*
+/// (non-signalled) state.
+///
+/// On success, returns the `(writable, readable)` handle pair. Both handles refer to the same event
+/// object and are closed independently when dropped. Use [`close_writable_event`] and
+/// [`close_readable_event`] to close them explicitly and observe errors.
+pub fn create() -> Result<(OwnedHandle<WritableEvent>, OwnedHandle<ReadableEvent>), CreateEventError>
+{
+    let mut writable = raw::INVALID_HANDLE;
+    let mut readable = raw::INVALID_HANDLE;
+    let rc = unsafe { raw::create_event(&mut writable, &mut readable) };
+
+    RawResult::from_raw(rc).map_err(|rc| CreateEventError::from(Error::from(rc)))?;
+
+    // SAFETY: On success, the kernel returned new handles that are not owned elsewhere.
+    Ok(unsafe {
+        (
+            OwnedHandle::new(WritableEvent(writable)),
+            OwnedHandle::new(ReadableEvent(readable)),
+        )
+    })
+}
+
+/// Error type for [`create`].
+#[derive(Debug, ResultCodeError)]
+pub enum CreateEventError {
+    /// The kernel could not allocate a new event object —
+    /// `KernelError::OutOfResource` (raw code `0xCE01`).
+    #[rc(desc = OutOfResource)]
+    OutOfResource,
+    /// The per-process event quota has been exhausted —
+    /// `KernelError::LimitReached` (raw code `0x10801`).
+    #[rc(desc = LimitReached, msg = "Event limit reached for process")]
+    LimitReached,
+    /// The process handle table contains no free slots for the two new handles —
+    /// `KernelError::OutOfHandles` (raw code `0xD201`).
+    #[rc(desc = OutOfHandles, msg = "Handle table full")]
+    OutOfHandles,
+    /// Any unforeseen kernel error. Contains the original [`Error`] so callers
+    /// can inspect the raw result (`Error::to_raw`).
+    Unknown(Error),
+}
+
+/// Puts the event in the *signalled* state.
+///
+/// Wakes up every thread currently waiting on the corresponding [`ReadableEvent`]. The event stays
+/// signalled until it is cleared with [`clear`] or reset with [`reset`].
+pub fn signal(handle: &WritableEvent) -> Result<(), SignalEventError> {
+    let rc = unsafe { raw::signal_event(handle.to_raw()) };
+    RawResult::from_raw(rc).map((), |rc| Error::from(rc).into())
+}
+
+/// Error type for [`signal`].
+#[derive(Debug, ResultCodeError)]
+pub enum SignalEventError {
+    /// The supplied handle is not a valid writable event handle —
+    /// `KernelError::InvalidHandle` (raw code `0xE401`).
+    #[rc(desc = InvalidHandle)]
+    InvalidHandle,
+    /// Any unforeseen kernel error. Contains the original [`Error`] so callers
+    /// can inspect the raw result (`Error::to_raw`).
+    Unknown(Error),
+}
+
+/// Takes the event out of the *signalled* state, if it is signalled.
+///
+/// Unlike [`reset`], clearing an event that is not signalled is not an error.
+pub fn clear(handle: &WritableEvent) -> Result<(), ClearEventError> {
+    let rc = unsafe { raw::clear_event(handle.to_raw()) };
+    RawResult::from_raw(rc).map((), |rc| Error::from(rc).into())
+}
+
+/// Error type for [`clear`].
+#[derive(Debug, ResultCodeError)]
+pub enum ClearEventError {
+    /// The supplied handle is not a valid event handle —
+    /// `KernelError::InvalidHandle` (raw code `0xE401`).
+    #[rc(desc = InvalidHandle)]
+    InvalidHandle,
+    /// Any unforeseen kernel error. Contains the original [`Error`] so callers
+    /// can inspect the raw result (`Error::to_raw`).
+    Unknown(Error),
+}
+
+/// Resets a *signalled* event from its readable half.
+///
+/// This is a safe wrapper around [`raw::reset_signal`]. It is the readable-side counterpart of
+/// [`clear`]. The difference is that the kernel reports [`ResetEventError::NotSignalled`] if the
+/// event was not signalled. This lets a waiter atomically consume a signal.
+pub fn reset(handle: &ReadableEvent) -> Result<(), ResetEventError> {
+    let rc = unsafe { raw::reset_signal(handle.to_raw()) };
+    RawResult::from_raw(rc).map((), |rc| Error::from(rc).into())
+}
+
+/// Error type for [`reset`].
+#[derive(Debug, ResultCodeError)]
+pub enum ResetEventError {
+    /// The supplied handle is not a valid readable event handle —
+    /// `KernelError::InvalidHandle` (raw code `0xE401`).
+    #[rc(desc = InvalidHandle)]
+    InvalidHandle,
+    /// The event was not in the signalled state —
+    /// `KernelError::InvalidState` (raw code `0xFA01`).
+    #[rc(desc = InvalidState, msg = "Event not signalled")]
+    NotSignalled,
+    /// Any unforeseen kernel error. Contains the original [`Error`] so callers
+    /// can inspect the raw result (`Error::to_raw`).
+    Unknown(Error),
+}
+
+/// Blocks the current thread until the event is signalled or `timeout` (in nanoseconds) expires.
+///
+/// This is a convenience wrapper around [`sync::wait_synchronization_single`]. Use `u64::MAX` for
+/// an infinite wait and `0` to poll the current state.
+///
+/// Waiting does **not** consume the signal; call [`reset`] (or [`clear`] on the writable half) to
+/// return the event to the cleared state.
+pub fn wait(handle: &ReadableEvent, timeout: u64) -> Result<(), WaitSyncError> {
+    // SAFETY: `ReadableEvent` can only be obtained from `create` or the unsafe `from_raw`, so the
+    // handle refers to a readable event owned by this process.
+    unsafe { sync::wait_synchronization_single(handle, timeout) }
+}
+
+/// Closes the readable half of an event.
+///
+/// The event object itself is only destroyed once **both** halves have been closed.
+pub fn close_readable_event(handle: ReadableEvent) -> Result<(), CloseHandleError> {
+    close_handle(handle.to_raw())
+}
+
+/// Closes the writable half of an event.
+///
+/// The event object itself is only destroyed once **both** halves have been closed.
+pub fn close_writable_event(handle: WritableEvent) -> Result<(), CloseHandleError> {
+    close_handle(handle.to_raw())
+}
+
+fn close_handle(handle: raw::Handle) -> Result<(), CloseHandleError> {
+    let rc = unsafe { raw::close_handle(handle) };
+    RawResult::from_raw(rc).map((), |rc| Error::from(rc).into())
+}
+
+/// Error type for [`close_readable_event`] and [`close_writable_event`].
+#[derive(Debug, ResultCodeError)]
+pub enum CloseHandleError {
+    /// The supplied handle is not a valid event handle —
+    /// `KernelError::InvalidHandle` (raw code `0xE401`).
+    #[rc(desc = InvalidHandle)]
+    InvalidHandle,
+    /// Any unforeseen kernel error. Contains the original [`Error`] so callers
+    /// can inspect the raw result (`Error::to_raw`).
+    Unknown(Error),
+}
@@ -0,0 +1,466 @@
+//! User-mode exception handling
+//!
+//! When a thread of a process that is not being debugged faults, the kernel saves part of the
+//! thread context into the process' *user exception context* ([`ExceptionInfo`], located at
+//! [`InfoType::UserExceptionContextAddress`]) and re-enters the process at its entrypoint. libnx's
+//! runtime then saves the remaining registers into an [`ExceptionDump`], switches to a dedicated
+//! exception stack, and calls `__libnx_exception_handler`.
+//!
+//! With the `exception-handler` feature, and when linking with `exception_override.ld`,
+//! `__libnx_exception_handler` is overridden to call [`handle`], which dispatches the exception
+//! to the handler registered with [`set_handler`]. The handler inspects (and possibly patches)
+//! the [`ExceptionDump`], and decides whether the faulting thread should
+//! [resume](ExceptionAction::Resume) or the exception should be reported as unhandled
+//! ([abort](ExceptionAction::Abort)), which terminates the process.
+//!
+//! Note that:
+//! - libnx only invokes the handler when no debugger is attached, unless
+//!   `__nx_exception_ignoredebug` is set.
+//! - The handler runs on libnx's exception stack (`__nx_exception_stack`), which is only 1 KiB by
+//!   default.
+//!
+//! ## References
+//! - [Switchbrew Wiki: SVC](https://switchbrew.org/wiki/SVC#ReturnFromException)
+//! - [switchbrew/libnx: `switch_crt0.s`](https://github.com/switchbrew/libnx/blob/60bf943ec14b1fb2ae169e627e64ab93a24c042b/nx/source/runtime/switch_crt0.s)
+
+use core::{
+    mem::{self, offset_of},
+    sync::atomic::{AtomicPtr, Ordering},
+};
+
+use crate::{
+    code::{EXIT_PROCESS, RETURN_FROM_EXCEPTION},
+    error::{KernelError, ToRawResultCode},
+    misc::{self, CUR_PROCESS_HANDLE, InfoType},
+};
+
+/// An exception handler, see [`set_handler`].
+pub type ExceptionHandler = fn(&mut ExceptionDump) -> ExceptionAction;
+
+/// What to do once an exception has been handled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExceptionAction {
+    /// Resume the faulting thread with the (possibly modified) [`ExceptionDump`] registers.
+    ///
+    /// The handler must have fixed the cause of the exception (e.g. by advancing the PC past
+    /// the faulting instruction), or the thread will fault again.
+    Resume,
+    /// Report the exception as unhandled, which terminates the process.
+    Abort,
+}
+
+/// The registered exception handler, or null if none.
+static HANDLER: AtomicPtr<()> = AtomicPtr::new(core::ptr::null_mut());
+
+/// Registers the exception handler, returning the previous one.
+pub fn set_handler(handler: ExceptionHandler) -> Option<ExceptionHandler> {
+    swap_handler(handler as *mut ())
+}
+
+/// Unregisters the exception handler, returning the previous one.
+///
+/// Without a handler, all exceptions are reported as unhandled.
+pub fn clear_handler() -> Option<ExceptionHandler> {
+    swap_handler(core::ptr::null_mut())
+}
+
+fn swap_handler(handler: *mut ()) -> Option<ExceptionHandler> {
+    let prev = HANDLER.swap(handler, Ordering::AcqRel);
+    // SAFETY: Only `ExceptionHandler` pointers (or null) are stored in `HANDLER`.
+    (!prev.is_null()).then(|| unsafe { mem::transmute::<*mut (), ExceptionHandler>(prev) })
+}
+
+/// Dispatches an exception to the registered handler, then resumes or aborts accordingly.
+///
+/// # Safety
+///
+/// Must only be called from the exception entry, with the dump of the exception being handled.
+pub unsafe fn handle(dump: &mut ExceptionDump) -> ! {
+    let handler = HANDLER.load(Ordering::Acquire);
+    if handler.is_null() {
+        abort();
+    }
+
+    // SAFETY: Only `ExceptionHandler` pointers are stored in `HANDLER`.
+    let handler = unsafe { mem::transmute::<*mut (), ExceptionHandler>(handler) };
+    match handler(dump) {
+        ExceptionAction::Resume => unsafe { resume(dump) },
+        ExceptionAction::Abort => abort(),
+    }
+}
+
+/// Reports the exception being handled as unhandled, terminating the process.
+///
+/// `svcReturnFromException` fails when no exception is being handled; the process then exits
+/// through `svcExitProcess` instead.
+pub fn abort() -> ! {
+    // SAFETY: Neither SVC returns on success, and a failing `svcReturnFromException` falls
+    // through to `svcExitProcess`.
+    unsafe {
+        core::arch::asm!(
+            "mov x0, {rc}",
+            "svc {return_from_exception}",
+            "svc {exit_process}",
+            rc = in(reg) KernelError::NotHandled.to_rc() as u64,
+            return_from_exception = const RETURN_FROM_EXCEPTION,
+            exit_process = const EXIT_PROCESS,
+            options(noreturn, nostack),
+        )
+    }
+}
+
+/// Resumes the faulting thread with the registers in `dump`.
+///
+/// The registers saved by the kernel are written back to the user exception context, the others
+/// are restored directly, and the kernel is told the exception was handled. Aborts if the user
+/// exception context cannot be located.
+///
+/// # Safety
+///
+/// Must only be called while handling an exception. The thread resumes with the register values
+/// in `dump`, which must describe a valid execution state.
+pub unsafe fn resume(dump: &ExceptionDump) -> ! {
+    let Ok(addr) = misc::get_info(InfoType::UserExceptionContextAddress, CUR_PROCESS_HANDLE) else {
+        abort();
+    };
+
+    // SAFETY: The kernel reports the address of the process' user exception context, which is
+    // only in use while an exception is being handled.
+    let info = unsafe { &mut *(addr as *mut ExceptionInfo) };
+    info.x.copy_from_slice(&dump.gprs[..9]);
+    info.lr = dump.lr;
+    info.sp = dump.sp;
+    info.pc = dump.pc;
+    info.pstate = dump.pstate;
+
+    unsafe { restore_and_return(dump) }
+}
+
+/// Restores `x9`–`x29` and `q0`–`q31` from the dump, and returns from the exception with a
+/// success result, making the kernel restore the remaining registers from the user exception
+/// context.
+#[unsafe(naked)]
+unsafe extern "C" fn restore_and_return(dump: &ExceptionDump) -> ! {
+    core::arch::naked_asm!(
+        // Restore the NEON registers
+        "add x1, x0, #{fpu}",
+        "ldp q0, q1, [x1, #0x000]",
+        "ldp q2, q3, [x1, #0x020]",
+        "ldp q4, q5, [x1, #0x040]",
+        "ldp q6, q7, [x1, #0x060]",
+        "ldp q8, q9, [x1, #0x080]",
+        "ldp q10, q11, [x1, #0x0A0]",
+        "ldp q12, q13, [x1, #0x0C0]",
+        "ldp q14, q15, [x1, #0x0E0]",
+        "ldp q16, q17, [x1, #0x100]",
+        "ldp q18, q19, [x1, #0x120]",
+        "ldp q20, q21, [x1, #0x140]",
+        "ldp q22, q23, [x1, #0x160]",
+        "ldp q24, q25, [x1, #0x180]",
+        "ldp q26, q27, [x1, #0x1A0]",
+        "ldp q28, q29, [x1, #0x1C0]",
+        "ldp q30, q31, [x1, #0x1E0]",
+        // Restore x9..x29 (x0..x8 are restored by the kernel)
+        "add x1, x0, #{x9}",
+        "ldp x9, x10, [x1, #0x00]",
+        "ldp x11, x12, [x1, #0x10]",
+        "ldp x13, x14, [x1, #0x20]",
+        "ldp x15, x16, [x1, #0x30]",
+        "ldp x17, x18, [x1, #0x40]",
+        "ldp x19, x20, [x1, #0x50]",
+        "ldp x21, x22, [x1, #0x60]",
+        "ldp x23, x24, [x1, #0x70]",
+        "ldp x25, x26, [x1, #0x80]",
+        "ldp x27, x28, [x1, #0x90]",
+        "ldr x29, [x1, #0xA0]",
+        // svcReturnFromException(0): never returns
+        "mov w0, wzr",
+        "svc {code}",
+        "brk #0",
+        fpu = const offset_of!(ExceptionDump, fpu_gprs),
+        x9 = const offset_of!(ExceptionDump, gprs) + 9 * 8,
+        code = const RETURN_FROM_EXCEPTION,
+    );
+}
+
+/// The user exception context saved by the kernel.
+///
+/// Located at [`InfoType::UserExceptionContextAddress`]. On [`resume`], the kernel restores the
+/// faulting thread's `x0`–`x8`, `lr`, `sp`, `pc` and `pstate` from it.
+#[derive(Debug, Clone, Copy)]
+#[repr(C)]
+pub struct ExceptionInfo {
+    /// `x0`–`x8`
+    pub x: [u64; 9],
+    /// Link register (`x30`)
+    pub lr: u64,
+    /// Stack pointer
+    pub sp: u64,
+    /// Program counter
+    pub pc: u64,
+    /// Processor state
+    pub pstate: u32,
+    /// Auxiliary fault status register 0
+    pub afsr0: u32,
+    /// Auxiliary fault status register 1
+    pub afsr1: u32,
+    /// Exception syndrome register
+    pub esr: u32,
+    /// Fault address register
+    pub far: u64,
+}
+
+/// The faulting thread's context, as saved by libnx's exception entry.
+///
+/// Layout-compatible with libnx's `ThreadExceptionDump`.
+#[derive(Debug, Clone, Copy)]
+#[repr(C)]
+pub struct ExceptionDump {
+    /// Exception type, see [`ExceptionDump::exception_type`]
+    pub desc: u32,
+    _pad: [u32; 3],
+    /// General purpose registers `x0`–`x28`
+    pub gprs: [u64; 29],
+    /// Frame pointer (`x29`)
+    pub fp: u64,
+    /// Link register (`x30`)
+    pub lr: u64,
+    /// Stack pointer
+    pub sp: u64,
+    /// Program counter
+    pub pc: u64,
+    _padding: u64,
+    /// NEON registers `q0`–`q31`
+    pub fpu_gprs: [u128; 32],
+    /// Processor state
+    pub pstate: u32,
+    /// Auxiliary fault status register 0
+    pub afsr0: u32,
+    /// Auxiliary fault status register 1
+    pub afsr1: u32,
+    /// Exception syndrome register, see [`ExceptionDump::esr`]
+    pub esr: u32,
+    /// Fault address register
+    pub far: u64,
+}
+
+const _: () = {
+    assert!(offset_of!(ExceptionDump, gprs) == 0x10);
+    assert!(offset_of!(ExceptionDump, fp) == 0xF8);
+    assert!(offset_of!(ExceptionDump, pc) == 0x110);
+    assert!(offset_of!(ExceptionDump, fpu_gprs) == 0x120);
+    assert!(offset_of!(ExceptionDump, far) == 0x330);
+    assert!(mem::size_of::<ExceptionInfo>() == 0x78);
+};
+
+impl ExceptionDump {
+    /// Returns the exception type reported by the kernel, or `None` if unknown.
+    pub const fn exception_type(&self) -> Option<ExceptionType> {
+        ExceptionType::from_raw(self.desc)
+    }
+
+    /// Returns the decoded exception syndrome register.
+    pub const fn esr(&self) -> Esr {
+        Esr(self.esr)
+    }
+
+    /// Returns the fault address (meaningful for aborts and alignment faults).
+    pub const fn far(&self) -> u64 {
+        self.far
+    }
+
+    /// Returns the address of the faulting instruction.
+    pub const fn pc(&self) -> u64 {
+        self.pc
+    }
+}
+
+/// Exception type, as reported by the kernel.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u32)]
+pub enum ExceptionType {
+    /// Instruction abort (e.g. jump to unmapped memory).
+    InstructionAbort = 0x100,
+    /// Data abort (e.g. access to unmapped memory).
+    DataAbort = 0x101,
+    /// Misaligned program counter.
+    UnalignedInstruction = 0x102,
+    /// Misaligned stack pointer or data access.
+    UnalignedData = 0x103,
+    /// Undefined instruction, or trapped instruction (e.g. `brk`).
+    UndefinedInstruction = 0x104,
+    /// Exception-generating instruction.
+    ExceptionInstruction = 0x105,
+    /// Asynchronous memory system error (SError).
+    MemorySystemError = 0x106,
+    /// Floating-point exception.
+    FpuException = 0x200,
+    /// Call to an SVC the process is not permitted to use.
+    InvalidSystemCall = 0x301,
+    /// `svcBreak` without a debugger attached.
+    SystemCallBreak = 0x302,
+}
+
+impl ExceptionType {
+    /// Converts a raw exception type, returning `None` if unknown.
+    pub const fn from_raw(value: u32) -> Option<Self> {
+        Some(match value {
+            0x100 => Self::InstructionAbort,
+            0x101 => Self::DataAbort,
+            0x102 => Self::UnalignedInstruction,
+            0x103 => Self::UnalignedData,
+            0x104 => Self::UndefinedInstruction,
+            0x105 => Self::ExceptionInstruction,
+            0x106 => Self::MemorySystemError,
+            0x200 => Self::FpuException,
+            0x301 => Self::InvalidSystemCall,
+            0x302 => Self::SystemCallBreak,
+            _ => return None,
+        })
+    }
+}
+
+/// Exception syndrome register (`ESR_EL1`) value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Esr(pub u32);
+
+impl Esr {
+    /// Returns the exception class (`EC`, bits 26–31).
+    pub const fn class(&self) -> ExceptionClass {
+        ExceptionClass::from_raw((self.0 >> 26) as u8)
+    }
+
+    /// Returns `true` if the trapped instruction was 32-bit wide (`IL`, bit 25).
+    pub const fn is_32bit_instruction(&self) -> bool {
+        self.0 & (1 << 25) != 0
+    }
+
+    /// Returns the instruction specific syndrome (`ISS`, bits 0–24).
+    pub const fn iss(&self) -> u32 {
+        self.0 & 0x01FF_FFFF
+    }
+}
+
+/// Exception class, decoded from the [`Esr`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExceptionClass {
+    /// Unknown reason (e.g. undefined instruction).
+    Unknown,
+    /// Trapped `WFI` or `WFE` instruction.
+    WfiWfe,
+    /// Trapped SIMD or floating-point access.
+    SimdFpAccess,
+    /// Illegal execution state.
+    IllegalExecutionState,
+    /// `SVC` instruction in AArch64 state.
+    Svc64,
+    /// Trapped `MSR`, `MRS` or system instruction.
+    SystemRegister,
+    /// Instruction abort from EL0.
+    InstructionAbortLowerEl,
+    /// Instruction abort from the same exception level.
+    InstructionAbortSameEl,
+    /// Misaligned program counter.
+    PcAlignment,
+    /// Data abort from EL0.
+    DataAbortLowerEl,
+    /// Data abort from the same exception level.
+    DataAbortSameEl,
+    /// Misaligned stack pointer.
+    SpAlignment,
+    /// Trapped floating-point exception in AArch64 state.
+    FpException64,
+    /// SError interrupt.
+    SError,
+    /// Hardware breakpoint from EL0.
+    BreakpointLowerEl,
+    /// Software step from EL0.
+    SoftwareStepLowerEl,
+    /// Watchpoint from EL0.
+    WatchpointLowerEl,
+    /// `BRK` instruction in AArch64 state.
+    Brk64,
+    /// Any other exception class.
+    Other(u8),
+}
+
+impl ExceptionClass {
+    /// Decodes a raw `EC` value.
+    pub const fn from_raw(ec: u8) -> Self {
+        match ec {
+            0x00 => Self::Unknown,
+            0x01 => Self::WfiWfe,
+            0x07 => Self::SimdFpAccess,
+            0x0E => Self::IllegalExecutionState,
+            0x15 => Self::Svc64,
+            0x18 => Self::SystemRegister,
+            0x20 => Self::InstructionAbortLowerEl,
+            0x21 => Self::InstructionAbortSameEl,
+            0x22 => Self::PcAlignment,
+            0x24 => Self::DataAbortLowerEl,
+            0x25 => Self::DataAbortSameEl,
+            0x26 => Self::SpAlignment,
+            0x2C => Self::FpException64,
+            0x2F => Self::SError,
+            0x30 => Self::BreakpointLowerEl,
+            0x32 => Self::SoftwareStepLowerEl,
+            0x34 => Self::WatchpointLowerEl,
+            0x3C => Self::Brk64,
+            ec => Self::Other(ec),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn esr_decoding() {
+        // `brk #0x1234`
+        let esr = Esr(0xF200_1234);
+        assert_eq!(esr.class(), ExceptionClass::Brk64);
+        assert!(esr.is_32bit_instruction());
+        assert_eq!(esr.iss(), 0x1234);
+
+        // `svc #0x26`
+        let esr = Esr(0x5600_0026);
+        assert_eq!(esr.class(), ExceptionClass::Svc64);
+        assert_eq!(esr.iss(), 0x26);
+
+        // Undefined instruction
+        assert_eq!(Esr(0x0200_0000).class(), ExceptionClass::Unknown);
+    }
+
+    #[test]
+    fn esr_aborts() {
+        // Level 3 translation fault on a read, then on a write (`WnR`, ISS bit 6)
+        let read = Esr(0x9200_0007);
+        let write = Esr(0x9200_0047);
+        assert_eq!(read.class(), ExceptionClass::DataAbortLowerEl);
+        assert_eq!(write.class(), ExceptionClass::DataAbortLowerEl);
+        assert_eq!(read.iss() & 0x3F, 0x07);
+        assert_ne!(write.iss() & (1 << 6), 0);
+
+        assert_eq!(
+            Esr(0x8200_0006).class(),
+            ExceptionClass::InstructionAbortLowerEl
+        );
+        assert_eq!(Esr(0x8A00_0000).class(), ExceptionClass::PcAlignment);
+        assert_eq!(Esr(0x9A00_0000).class(), ExceptionClass::SpAlignment);
+    }
+
+    #[test]
+    fn exception_class_from_raw() {
+        assert_eq!(ExceptionClass::from_raw(0x2F), ExceptionClass::SError);
+        assert_eq!(
+            ExceptionClass::from_raw(0x34),
+            ExceptionClass::WatchpointLowerEl
+        );
+        // Classes this module does not name are kept as-is
+        assert_eq!(ExceptionClass::from_raw(0x3F), ExceptionClass::Other(0x3F));
+        assert_eq!(Esr(0xFE00_0000).class(), ExceptionClass::Other(0x3F));
+        // 16-bit instructions (e.g. trapped from AArch32) clear `IL`
+        assert!(!Esr(0x0000_0000).is_32bit_instruction());
+    }
+}
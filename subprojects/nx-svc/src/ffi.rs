@@ -2,6 +2,8 @@
 
 use core::ffi::{c_char, c_int, c_void};
 
+#[cfg(feature = "exception-handler")]
+use crate::exception::{self, ExceptionDump};
 use crate::{
     raw::{
         self, ArbitrationType, BreakReason, CodeMapOperation, DebugThreadParam, Handle, IoPoolType,
@@ -2967,3 +2969,24 @@ unsafe extern "C" fn __nx_svc_unmap_insecure_physical_memory(
 }
 
 //</editor-fold>
+
+//<editor-fold desc="Exception handling">
+
+/// Handles a user-mode exception.
+///
+/// `void __libnx_exception_handler(ThreadExceptionDump *ctx);`
+///
+/// Called by libnx's exception entry with the faulting thread's context. Dispatches the exception
+/// to the handler registered with [`exception::set_handler`], then resumes the thread or reports
+/// the exception as unhandled.
+///
+/// | Arg | Name | Description |
+/// | --- | --- | --- |
+/// | IN | _ctx_ | Context of the faulting thread |
+#[cfg(feature = "exception-handler")]
+#[unsafe(no_mangle)]
+unsafe extern "C" fn __nx_svc_exception_handler(ctx: *mut ExceptionDump) -> ! {
+    unsafe { exception::handle(&mut *ctx) }
+}
+
+//</editor-fold>
@@ -1,6 +1,15 @@
 //! Handle types.
+//!
+//! The handle types defined throughout this crate (e.g. [`thread::Handle`]) are plain, copyable
+//! values: nothing closes them automatically. [`OwnedHandle`] adds ownership on top of any of them,
+//! closing the handle when dropped, and [`BorrowedHandle`] lends it out without giving ownership
+//! away, much like `std`'s `OwnedFd` and `BorrowedFd`.
+//!
+//! [`thread::Handle`]: crate::thread::Handle
 
-use crate::raw::Handle;
+use core::{fmt, marker::PhantomData, mem::ManuallyDrop};
+
+use crate::raw::{self, Handle};
 
 /// A trait for types that can be waited on by the kernel.
 pub trait Waitable: _priv::Sealed {
@@ -8,6 +17,22 @@ pub trait Waitable: _priv::Sealed {
     fn raw_handle(&self) -> Handle;
 }
 
+/// A typed kernel object handle, as defined by this crate.
+///
+/// Implemented by every handle type, so that they can be wrapped in an [`OwnedHandle`] or a
+/// [`BorrowedHandle`].
+pub trait KernelHandle: Copy + _priv::Sealed {
+    /// Converts a raw handle into the typed handle.
+    ///
+    /// # Safety
+    ///
+    /// Caller must guarantee that the raw handle is valid and refers to an object of this type.
+    unsafe fn from_raw_handle(raw: Handle) -> Self;
+
+    /// Returns the raw handle.
+    fn as_raw_handle(&self) -> Handle;
+}
+
 /// Internal macro to generate [`Handle`] newtypes with common helpers.
 ///
 /// [`Handle`]: crate::raw::Handle
@@ -41,6 +66,20 @@ macro_rules! define_handle_type {
             }
         }
 
+        impl $crate::handle::KernelHandle for $name {
+            #[inline]
+            unsafe fn from_raw_handle(raw: $crate::raw::Handle) -> Self {
+                Self(raw)
+            }
+
+            #[inline]
+            fn as_raw_handle(&self) -> $crate::raw::Handle {
+                self.0
+            }
+        }
+
+        impl $crate::handle::_priv::Sealed for $name {}
+
         impl ::core::cmp::PartialEq<$crate::raw::Handle> for $name {
             fn eq(&self, other: &$crate::raw::Handle) -> bool {
                 &self.0 == other
@@ -75,11 +114,154 @@ macro_rules! define_waitable_handle_type {
                 self.0
             }
         }
-
-        impl $crate::handle::_priv::Sealed for $name {}
     };
 }
 
+/// An owned kernel object handle, closed when dropped.
+///
+/// Wraps any handle type (`T`) of this crate. The handle is closed with `svcCloseHandle` on drop;
+/// use [`into_raw`](Self::into_raw) or [`into_inner`](Self::into_inner) to take it back out
+/// without closing it, e.g. to hand it over through FFI.
+///
+/// Pseudo-handles such as [`raw::CUR_THREAD_HANDLE`] and [`raw::CUR_PROCESS_HANDLE`] cannot be
+/// closed and must not be wrapped.
+#[repr(transparent)]
+pub struct OwnedHandle<T: KernelHandle>(T);
+
+impl<T: KernelHandle> OwnedHandle<T> {
+    /// Takes ownership of `handle`.
+    ///
+    /// # Safety
+    ///
+    /// `handle` must be a valid handle that is not owned elsewhere: it is closed when the returned
+    /// value is dropped, so no copy of it may be used afterwards.
+    pub unsafe fn new(handle: T) -> Self {
+        Self(handle)
+    }
+
+    /// Takes ownership of a raw handle.
+    ///
+    /// # Safety
+    ///
+    /// Same as [`new`](Self::new). In addition, `raw` must refer to an object of type `T`.
+    pub unsafe fn from_raw(raw: Handle) -> Self {
+        Self(unsafe { T::from_raw_handle(raw) })
+    }
+
+    /// Releases ownership of the handle, returning it as a raw handle without closing it.
+    pub fn into_raw(self) -> Handle {
+        self.into_inner().as_raw_handle()
+    }
+
+    /// Releases ownership of the handle, returning it without closing it.
+    pub fn into_inner(self) -> T {
+        ManuallyDrop::new(self).0
+    }
+
+    /// Borrows the handle.
+    pub fn as_handle(&self) -> BorrowedHandle<'_, T> {
+        BorrowedHandle {
+            handle: self.0,
+            _lifetime: PhantomData,
+        }
+    }
+
+    /// Returns a copy of the typed handle, e.g. to pass it to the SVC wrappers.
+    ///
+    /// The copy must not outlive `self`, nor be closed.
+    pub fn get(&self) -> T {
+        self.0
+    }
+
+    /// Returns the raw handle, without releasing ownership.
+    pub fn as_raw(&self) -> Handle {
+        self.0.as_raw_handle()
+    }
+}
+
+impl<T: KernelHandle> Drop for OwnedHandle<T> {
+    fn drop(&mut self) {
+        // SAFETY: The handle is owned, so nothing else can use (or close) it after this point.
+        // Closing can only fail for an invalid handle, which there is nothing to do about here.
+        let _ = unsafe { raw::close_handle(self.0.as_raw_handle()) };
+    }
+}
+
+impl<T: KernelHandle + fmt::Debug> fmt::Debug for OwnedHandle<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("OwnedHandle").field(&self.0).finish()
+    }
+}
+
+impl<T: KernelHandle + Waitable> Waitable for OwnedHandle<T> {
+    #[inline]
+    fn raw_handle(&self) -> Handle {
+        self.0.raw_handle()
+    }
+}
+
+impl<T: KernelHandle> _priv::Sealed for OwnedHandle<T> {}
+
+/// A borrowed kernel object handle.
+///
+/// Ties a handle to the lifetime of the [`OwnedHandle`] it was borrowed from, so that it cannot be
+/// used after being closed. It is never closed itself.
+#[derive(Clone, Copy)]
+pub struct BorrowedHandle<'a, T: KernelHandle> {
+    handle: T,
+    _lifetime: PhantomData<&'a OwnedHandle<T>>,
+}
+
+impl<T: KernelHandle> BorrowedHandle<'_, T> {
+    /// Borrows a handle owned elsewhere.
+    ///
+    /// # Safety
+    ///
+    /// `handle` must be valid, and stay open, for the whole lifetime of the returned value.
+    pub unsafe fn borrow(handle: T) -> Self {
+        Self {
+            handle,
+            _lifetime: PhantomData,
+        }
+    }
+
+    /// Borrows a raw handle owned elsewhere.
+    ///
+    /// # Safety
+    ///
+    /// Same as [`borrow`](Self::borrow). In addition, `raw` must refer to an object of type `T`.
+    pub unsafe fn borrow_raw(raw: Handle) -> Self {
+        unsafe { Self::borrow(T::from_raw_handle(raw)) }
+    }
+
+    /// Returns a copy of the typed handle, e.g. to pass it to the SVC wrappers.
+    ///
+    /// The copy must not outlive the borrow, nor be closed.
+    pub fn get(&self) -> T {
+        self.handle
+    }
+
+    /// Returns the raw handle.
+    pub fn as_raw(&self) -> Handle {
+        self.handle.as_raw_handle()
+    }
+}
+
+impl<T: KernelHandle + fmt::Debug> fmt::Debug for BorrowedHandle<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("BorrowedHandle").field(&self.handle).finish()
+    }
+}
+
+impl<T: KernelHandle + Waitable> Waitable for BorrowedHandle<'_, T> {
+    #[inline]
+    fn raw_handle(&self) -> Handle {
+        self.handle.raw_handle()
+    }
+}
+
+impl<T: KernelHandle> _priv::Sealed for BorrowedHandle<'_, T> {}
+
 #[allow(dead_code)]
 pub(crate) mod _priv {
     /// A trait that is sealed to prevent external implementations.
@@ -0,0 +1,19 @@
+//! Inter-process communication (IPC) system calls for the Horizon OS kernel.
+//!
+//! This module provides safe wrappers around the kernel's IPC SVCs. The kernel only moves
+//! messages between sessions; the message format itself (HIPC/CMIF/TIPC) is not modelled here.
+//!
+//! Every thread owns a 0x100-byte message buffer at the start of its Thread-Local Storage (TLS)
+//! region. Plain requests ([`send_sync_request`]) are read from, and replied into, that buffer.
+//! The `*_with_user_buffer` variants use a caller-supplied, page-aligned buffer instead.
+//!
+//! ## References
+//! - [Switchbrew Wiki: SVC](https://switchbrew.org/wiki/SVC)
+//! - [Switchbrew Wiki: IPC Marshalling](https://switchbrew.org/wiki/IPC_Marshalling)
+
+pub mod client;
+pub mod light;
+pub mod server;
+
+pub use client::*;
+pub use server::*;
@@ -0,0 +1,278 @@
+//! Client-side IPC sessions.
+//!
+//! Provides the [`Session`] handle type and wrappers for connecting to named ports and sending
+//! synchronous and asynchronous requests.
+
+use core::ffi::{CStr, c_void};
+
+use crate::{
+    error::{KernelError as KError, ResultCodeError, ToRawResultCode},
+    event::ReadableEvent,
+    handle::OwnedHandle,
+    mem::is_page_aligned,
+    raw,
+    result::{
+        Error, ResultCode,
+        raw::{Result as RawResult, ResultCode as RawResultCode},
+    },
+};
+
+/// Size of the per-thread IPC message buffer located at the start of the TLS region.
+pub const MESSAGE_BUFFER_SIZE: usize = 0x100;
+
+define_handle_type! {
+    /// A handle to the client end of an IPC session.
+    pub struct Session
+}
+
+/// Connects to a kernel-registered named port (e.g. `"sm:"`).
+///
+/// This is a safe wrapper around [`raw::connect_to_named_port`]. The port name must be at most
+/// 11 characters long (12 bytes including the NUL terminator).
+///
+/// On success returns the client [`Session`] connected to the port.
+pub fn connect_to_named_port(name: &CStr) -> Result<OwnedHandle<Session>, ConnectToNamedPortError> {
+    let mut handle = raw::INVALID_HANDLE;
+    let rc = unsafe { raw::connect_to_named_port(&mut handle, name.as_ptr()) };
+
+    RawResult::from_raw(rc).map_err(|rc| ConnectToNamedPortError::from(Error::from(rc)))?;
+
+    // SAFETY: On success, the kernel returned a new handle that is not owned elsewhere.
+    Ok(unsafe { OwnedHandle::new(Session(handle)) })
+}
+
+/// Error type for [`connect_to_named_port`].
+#[derive(Debug, ResultCodeError)]
+pub enum ConnectToNamedPortError {
+    /// No port is registered under the given name —
+    /// `KernelError::NotFound` (raw code `0xF201`).
+    #[rc(desc = NotFound, msg = "Port not found")]
+    NotFound,
+    /// The port name is longer than 11 characters —
+    /// `KernelError::OutOfRange` (raw code `0xEE01`).
+    #[rc(desc = OutOfRange, msg = "Port name too long")]
+    NameTooLong,
+    /// The port has reached its maximum number of sessions —
+    /// `KernelError::OutOfSessions` (raw code `0xE01`).
+    #[rc(desc = OutOfSessions)]
+    OutOfSessions,
+    /// The kernel could not allocate the session object —
+    /// `KernelError::OutOfResource` (raw code `0xCE01`).
+    #[rc(desc = OutOfResource)]
+    OutOfResource,
+    /// The per-process session quota has been exhausted —
+    /// `KernelError::LimitReached` (raw code `0x10801`).
+    #[rc(desc = LimitReached, msg = "Session limit reached for process")]
+    LimitReached,
+    /// The process handle table contains no free slots —
+    /// `KernelError::OutOfHandles` (raw code `0xD201`).
+    #[rc(desc = OutOfHandles, msg = "Handle table full")]
+    OutOfHandles,
+    /// The server side of the port has been closed —
+    /// `KernelError::PortClosed` (raw code `0x10601`).
+    #[rc(desc = PortClosed)]
+    PortClosed,
+    /// Any unforeseen kernel error. Contains the original [`Error`] so callers
+    /// can inspect the raw result (`Error::to_raw`).
+    Unknown(Error),
+}
+
+/// Sends the request stored in the calling thread's TLS message buffer and waits for the reply.
+///
+/// This is a wrapper around [`raw::send_sync_request`]. The kernel reads the request from the
+/// first [`MESSAGE_BUFFER_SIZE`] bytes of the thread's TLS region and writes the reply back to the
+/// same place before returning. The TLS region can be obtained with
+/// `nx_sys_thread::tls_region::get_ptr` (see also `nx_sys_thread::tls_region::ipc_buffer_ptr`).
+///
+/// # Safety
+///
+/// The caller must ensure that the TLS message buffer contains a well-formed request. Any buffer
+/// descriptors or receive lists in the request must point to memory that stays valid, with the
+/// right access, for the whole call. The kernel reads from and writes to those addresses on the
+/// caller's behalf.
+pub unsafe fn send_sync_request(session: &Session) -> Result<(), SendSyncRequestError> {
+    let rc = unsafe { raw::send_sync_request(session.to_raw()) };
+    RawResult::from_raw(rc).map((), |rc| Error::from(rc).into())
+}
+
+/// Sends the request stored in `buffer` and waits for the reply.
+///
+/// This is a wrapper around [`raw::send_sync_request_with_user_buffer`]. It behaves like
+/// [`send_sync_request`], except that the message is read from and the reply written to `buffer`
+/// instead of the TLS message buffer.
+///
+/// The buffer's address and length must both be multiples of [`PAGE_SIZE`](crate::mem::PAGE_SIZE). This is checked before
+/// the SVC is issued.
+///
+/// # Safety
+///
+/// The caller must ensure that `buffer` contains a well-formed request. Any buffer descriptors or
+/// receive lists in it must point to memory that stays valid, with the right access, for the
+/// whole call.
+pub unsafe fn send_sync_request_with_user_buffer(
+    session: &Session,
+    buffer: &mut [u8],
+) -> Result<(), SendSyncRequestWithUserBufferError> {
+    check_user_buffer(buffer)?;
+
+    let rc = unsafe {
+        raw::send_sync_request_with_user_buffer(
+            buffer.as_mut_ptr() as *mut c_void,
+            buffer.len() as u64,
+            session.to_raw(),
+        )
+    };
+    RawResult::from_raw(rc).map((), map_user_buffer_error)
+}
+
+/// Sends the request stored in `buffer` without waiting for the reply.
+///
+/// This is a wrapper around [`raw::send_async_request_with_user_buffer`]. On success it returns a
+/// [`ReadableEvent`] that is signalled once the server has replied. The reply is then available
+/// in `buffer`. The caller owns the returned event handle and must close it.
+///
+/// The buffer's address and length must both be multiples of [`PAGE_SIZE`](crate::mem::PAGE_SIZE). This is checked before
+/// the SVC is issued.
+///
+/// # Safety
+///
+/// In addition to the requirements of [`send_sync_request_with_user_buffer`], `buffer` must not be
+/// accessed, moved or freed until the returned event has been signalled. The kernel writes the
+/// reply into it asynchronously.
+pub unsafe fn send_async_request_with_user_buffer(
+    session: &Session,
+    buffer: &mut [u8],
+) -> Result<ReadableEvent, SendAsyncRequestWithUserBufferError> {
+    check_user_buffer(buffer)?;
+
+    let mut handle = raw::INVALID_HANDLE;
+    let rc = unsafe {
+        raw::send_async_request_with_user_buffer(
+            &mut handle,
+            buffer.as_mut_ptr() as *mut c_void,
+            buffer.len() as u64,
+            session.to_raw(),
+        )
+    };
+
+    // SAFETY: On success the kernel returns a freshly created readable event handle.
+    let event = unsafe { ReadableEvent::from_raw(handle) };
+    RawResult::from_raw(rc).map(event, |rc| match rc.description() {
+        desc if KError::LimitReached == desc => SendAsyncRequestWithUserBufferError::LimitReached,
+        desc if KError::OutOfHandles == desc => SendAsyncRequestWithUserBufferError::OutOfHandles,
+        _ => SendAsyncRequestWithUserBufferError::Request(map_user_buffer_error(rc)),
+    })
+}
+
+/// Checks the page-alignment requirements shared by the `*_with_user_buffer` SVCs.
+fn check_user_buffer(buffer: &[u8]) -> Result<(), SendSyncRequestWithUserBufferError> {
+    if !is_page_aligned(buffer.as_ptr() as usize) {
+        return Err(SendSyncRequestWithUserBufferError::InvalidAddress);
+    }
+    if buffer.is_empty() || !is_page_aligned(buffer.len()) {
+        return Err(SendSyncRequestWithUserBufferError::InvalidSize);
+    }
+    Ok(())
+}
+
+fn map_user_buffer_error(rc: RawResultCode) -> SendSyncRequestWithUserBufferError {
+    match rc.description() {
+        desc if KError::InvalidAddress == desc => {
+            SendSyncRequestWithUserBufferError::InvalidAddress
+        }
+        desc if KError::InvalidSize == desc => SendSyncRequestWithUserBufferError::InvalidSize,
+        desc if KError::InvalidCurrentMemory == desc => {
+            SendSyncRequestWithUserBufferError::InvalidCurrentMemory
+        }
+        _ => SendSyncRequestWithUserBufferError::Request(Error::from(rc).into()),
+    }
+}
+
+/// Error type for [`send_sync_request`].
+#[derive(Debug, ResultCodeError)]
+pub enum SendSyncRequestError {
+    /// The supplied handle is not a valid client session handle —
+    /// `KernelError::InvalidHandle` (raw code `0xE401`).
+    #[rc(desc = InvalidHandle)]
+    InvalidHandle,
+    /// The server end of the session has been closed —
+    /// `KernelError::SessionClosed` (raw code `0xF601`).
+    #[rc(desc = SessionClosed)]
+    SessionClosed,
+    /// The kernel could not allocate the request object —
+    /// `KernelError::OutOfResource` (raw code `0xCE01`).
+    #[rc(desc = OutOfResource)]
+    OutOfResource,
+    /// The calling thread was asked to terminate while waiting for the reply —
+    /// `KernelError::TerminationRequested` (raw code `0x7601`).
+    #[rc(desc = TerminationRequested)]
+    TerminationRequested,
+    /// The message does not fit in the receiver's buffer —
+    /// `KernelError::MessageTooLarge` (raw code `0x20801`).
+    #[rc(desc = MessageTooLarge)]
+    MessageTooLarge,
+    /// The receive list of the message is invalid —
+    /// `KernelError::ReceiveListBroken` (raw code `0x20401`).
+    #[rc(desc = ReceiveListBroken)]
+    ReceiveListBroken,
+    /// Any unforeseen kernel error. Contains the original [`Error`] so callers
+    /// can inspect the raw result (`Error::to_raw`).
+    Unknown(Error),
+}
+
+/// Error type for [`send_sync_request_with_user_buffer`].
+#[derive(Debug, thiserror::Error)]
+pub enum SendSyncRequestWithUserBufferError {
+    /// The buffer address is not aligned to [`PAGE_SIZE`](crate::mem::PAGE_SIZE) —
+    /// `KernelError::InvalidAddress` (raw code `0xCC01`).
+    #[error("Invalid buffer address")]
+    InvalidAddress,
+    /// The buffer length is zero or not a multiple of [`PAGE_SIZE`](crate::mem::PAGE_SIZE) —
+    /// `KernelError::InvalidSize` (raw code `0xCA01`).
+    #[error("Invalid buffer size")]
+    InvalidSize,
+    /// The buffer is not in a memory state usable for IPC —
+    /// `KernelError::InvalidCurrentMemory` (raw code `0xD401`).
+    #[error("Invalid memory state")]
+    InvalidCurrentMemory,
+    /// The request itself failed.
+    #[error(transparent)]
+    Request(#[from] SendSyncRequestError),
+}
+
+impl ToRawResultCode for SendSyncRequestWithUserBufferError {
+    fn to_rc(self) -> ResultCode {
+        match self {
+            Self::InvalidAddress => KError::InvalidAddress.to_rc(),
+            Self::InvalidSize => KError::InvalidSize.to_rc(),
+            Self::InvalidCurrentMemory => KError::InvalidCurrentMemory.to_rc(),
+            Self::Request(err) => err.to_rc(),
+        }
+    }
+}
+
+/// Error type for [`send_async_request_with_user_buffer`].
+#[derive(Debug, thiserror::Error)]
+pub enum SendAsyncRequestWithUserBufferError {
+    /// The per-process event quota has been exhausted —
+    /// `KernelError::LimitReached` (raw code `0x10801`).
+    #[error("Event limit reached for process")]
+    LimitReached,
+    /// The process handle table contains no free slot for the reply event —
+    /// `KernelError::OutOfHandles` (raw code `0xD201`).
+    #[error("Handle table full")]
+    OutOfHandles,
+    /// The request itself, or the buffer it was sent from, was rejected.
+    #[error(transparent)]
+    Request(#[from] SendSyncRequestWithUserBufferError),
+}
+
+impl ToRawResultCode for SendAsyncRequestWithUserBufferError {
+    fn to_rc(self) -> ResultCode {
+        match self {
+            Self::LimitReached => KError::LimitReached.to_rc(),
+            Self::OutOfHandles => KError::OutOfHandles.to_rc(),
+            Self::Request(err) => err.to_rc(),
+        }
+    }
+}
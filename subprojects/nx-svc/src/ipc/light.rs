@@ -0,0 +1,275 @@
+//! Light IPC sessions.
+//!
+//! Light sessions skip the TLS message buffer entirely: a request and its reply are each seven
+//! 32-bit words passed in CPU registers (`w1`..`w7`). There are no buffer descriptors, handles or
+//! process IDs to translate, which makes light IPC the cheapest way for two processes to exchange
+//! small messages.
+//!
+//! Light handles are not interchangeable with regular ones, so this module has its own handle
+//! types. Light server sessions cannot be waited on; each one is served by a thread blocked in
+//! [`reply_and_receive`] (see [`serve`]).
+
+use core::ffi::CStr;
+
+use super::server::{AcceptSessionError, ConnectToPortError, CreatePortError, CreateSessionError};
+use crate::{
+    error::ResultCodeError,
+    handle::OwnedHandle,
+    raw,
+    result::{Error, raw::Result as RawResult},
+};
+
+/// Number of 32-bit words in a light IPC message.
+pub const MESSAGE_WORDS: usize = 7;
+
+/// A light IPC message.
+///
+/// The kernel copies the seven words verbatim between client and server; their meaning is up to
+/// the protocol spoken over the session.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(transparent)]
+pub struct Message(pub [u32; MESSAGE_WORDS]);
+
+impl Message {
+    /// Creates a message from its raw words.
+    #[inline]
+    pub const fn new(words: [u32; MESSAGE_WORDS]) -> Self {
+        Self(words)
+    }
+
+    /// Returns the raw words of the message.
+    #[inline]
+    pub const fn words(&self) -> &[u32; MESSAGE_WORDS] {
+        &self.0
+    }
+
+    /// Returns the raw words of the message for modification.
+    #[inline]
+    pub const fn words_mut(&mut self) -> &mut [u32; MESSAGE_WORDS] {
+        &mut self.0
+    }
+}
+
+impl From<[u32; MESSAGE_WORDS]> for Message {
+    fn from(words: [u32; MESSAGE_WORDS]) -> Self {
+        Self(words)
+    }
+}
+
+impl From<Message> for [u32; MESSAGE_WORDS] {
+    fn from(msg: Message) -> Self {
+        msg.0
+    }
+}
+
+define_handle_type! {
+    /// A handle to the client end of a light IPC session.
+    pub struct LightSession
+}
+
+define_handle_type! {
+    /// A handle to the server end of a light IPC session.
+    pub struct LightServerSession
+}
+
+define_waitable_handle_type! {
+    /// A handle to the server end of a light port.
+    ///
+    /// It is signalled when a client connection is pending. See [`accept_session`].
+    pub struct LightServerPort
+}
+
+define_handle_type! {
+    /// A handle to the client end of a light port.
+    pub struct LightClientPort
+}
+
+/// Creates a new light session pair.
+///
+/// This is a safe wrapper around [`raw::create_session`] with `is_light` set.
+///
+/// On success returns the `(server, client)` session handle pair.
+pub fn create_session()
+-> Result<(OwnedHandle<LightServerSession>, OwnedHandle<LightSession>), CreateSessionError> {
+    let mut server = raw::INVALID_HANDLE;
+    let mut client = raw::INVALID_HANDLE;
+    let rc = unsafe { raw::create_session(&mut server, &mut client, true, 0) };
+
+    RawResult::from_raw(rc).map_err(|rc| CreateSessionError::from(Error::from(rc)))?;
+
+    // SAFETY: On success, the kernel returned new handles that are not owned elsewhere.
+    Ok(unsafe {
+        (
+            OwnedHandle::new(LightServerSession(server)),
+            OwnedHandle::new(LightSession(client)),
+        )
+    })
+}
+
+/// Creates a new, unnamed light port.
+///
+/// This is a safe wrapper around [`raw::create_port`] with `is_light` set. See
+/// [`create_port`](super::create_port) for the meaning of the arguments.
+///
+/// On success returns the `(server, client)` port handle pair.
+pub fn create_port(
+    max_sessions: u32,
+    name: Option<&CStr>,
+) -> Result<(OwnedHandle<LightServerPort>, OwnedHandle<LightClientPort>), CreatePortError> {
+    let Ok(max_sessions) = i32::try_from(max_sessions) else {
+        return Err(CreatePortError::OutOfRange);
+    };
+
+    let mut server = raw::INVALID_HANDLE;
+    let mut client = raw::INVALID_HANDLE;
+    let rc = unsafe {
+        raw::create_port(
+            &mut server,
+            &mut client,
+            max_sessions,
+            true,
+            name.map_or(core::ptr::null(), CStr::as_ptr),
+        )
+    };
+
+    RawResult::from_raw(rc).map_err(|rc| CreatePortError::from(Error::from(rc)))?;
+
+    // SAFETY: On success, the kernel returned new handles that are not owned elsewhere.
+    Ok(unsafe {
+        (
+            OwnedHandle::new(LightServerPort(server)),
+            OwnedHandle::new(LightClientPort(client)),
+        )
+    })
+}
+
+/// Opens a new light session on a light port through its client end.
+///
+/// This is a safe wrapper around [`raw::connect_to_port`].
+pub fn connect_to_port(
+    port: &LightClientPort,
+) -> Result<OwnedHandle<LightSession>, ConnectToPortError> {
+    let mut handle = raw::INVALID_HANDLE;
+    let rc = unsafe { raw::connect_to_port(&mut handle, port.to_raw()) };
+    RawResult::from_raw(rc).map_err(|rc| ConnectToPortError::from(Error::from(rc)))?;
+
+    // SAFETY: On success, the kernel returned a new handle that is not owned elsewhere.
+    Ok(unsafe { OwnedHandle::new(LightSession(handle)) })
+}
+
+/// Accepts a pending connection on a light server port.
+///
+/// This is a safe wrapper around [`raw::accept_session`].
+pub fn accept_session(
+    port: &LightServerPort,
+) -> Result<OwnedHandle<LightServerSession>, AcceptSessionError> {
+    let mut handle = raw::INVALID_HANDLE;
+    let rc = unsafe { raw::accept_session(&mut handle, port.to_raw()) };
+    RawResult::from_raw(rc).map_err(|rc| AcceptSessionError::from(Error::from(rc)))?;
+
+    // SAFETY: On success, the kernel returned a new handle that is not owned elsewhere.
+    Ok(unsafe { OwnedHandle::new(LightServerSession(handle)) })
+}
+
+/// Sends `request` over a light session and waits for the reply.
+///
+/// This is a safe wrapper around [`raw::send_sync_request_light_with_args`]. The message is passed
+/// in registers, so no memory is shared with the server.
+pub fn send_sync_request(
+    session: &LightSession,
+    request: Message,
+) -> Result<Message, SendSyncRequestLightError> {
+    let mut msg = request;
+    let rc = unsafe {
+        raw::send_sync_request_light_with_args(session.to_raw(), msg.words_mut().as_mut_ptr())
+    };
+
+    RawResult::from_raw(rc).map(msg, |rc| Error::from(rc).into())
+}
+
+/// Error type for [`send_sync_request`].
+#[derive(Debug, ResultCodeError)]
+pub enum SendSyncRequestLightError {
+    /// The supplied handle is not a valid light session handle —
+    /// `KernelError::InvalidHandle` (raw code `0xE401`).
+    #[rc(desc = InvalidHandle)]
+    InvalidHandle,
+    /// The server end of the session has been closed —
+    /// `KernelError::SessionClosed` (raw code `0xF601`).
+    #[rc(desc = SessionClosed)]
+    SessionClosed,
+    /// The calling thread was asked to terminate while waiting for the reply —
+    /// `KernelError::TerminationRequested` (raw code `0x7601`).
+    #[rc(desc = TerminationRequested)]
+    TerminationRequested,
+    /// Any unforeseen kernel error. Contains the original [`Error`] so callers
+    /// can inspect the raw result (`Error::to_raw`).
+    Unknown(Error),
+}
+
+/// Replies to the pending request, then waits for the next one.
+///
+/// This is a safe wrapper around [`raw::reply_and_receive_light_with_args`]. `reply` is delivered
+/// to the client whose request was returned by the previous call. On the first call there is no
+/// pending request and `reply` is ignored.
+///
+/// On success returns the next request.
+pub fn reply_and_receive(
+    session: &LightServerSession,
+    reply: Message,
+) -> Result<Message, ReplyAndReceiveLightError> {
+    let mut msg = reply;
+    let rc = unsafe {
+        raw::reply_and_receive_light_with_args(session.to_raw(), msg.words_mut().as_mut_ptr())
+    };
+
+    RawResult::from_raw(rc).map(msg, |rc| Error::from(rc).into())
+}
+
+/// Error type for [`reply_and_receive`] and [`serve`].
+#[derive(Debug, ResultCodeError)]
+pub enum ReplyAndReceiveLightError {
+    /// The supplied handle is not a valid light server session handle —
+    /// `KernelError::InvalidHandle` (raw code `0xE401`).
+    #[rc(desc = InvalidHandle)]
+    InvalidHandle,
+    /// The client end of the session has been closed —
+    /// `KernelError::SessionClosed` (raw code `0xF601`).
+    #[rc(desc = SessionClosed)]
+    SessionClosed,
+    /// The wait was cancelled by another thread —
+    /// `KernelError::Cancelled` (raw code `0xEC01`).
+    #[rc(desc = Cancelled)]
+    Cancelled,
+    /// The calling thread was asked to terminate while waiting —
+    /// `KernelError::TerminationRequested` (raw code `0x7601`).
+    #[rc(desc = TerminationRequested)]
+    TerminationRequested,
+    /// Any unforeseen kernel error. Contains the original [`Error`] so callers
+    /// can inspect the raw result (`Error::to_raw`).
+    Unknown(Error),
+}
+
+/// Serves a light session until the client closes it.
+///
+/// Runs the [`reply_and_receive`] loop on the calling thread: every incoming request is passed to
+/// `handler`, and the message it returns is sent back as the reply.
+///
+/// Returns `Ok(())` once the client end has been closed, or the first other error reported by the
+/// kernel.
+pub fn serve<F>(
+    session: &LightServerSession,
+    mut handler: F,
+) -> Result<(), ReplyAndReceiveLightError>
+where
+    F: FnMut(&Message) -> Message,
+{
+    let mut reply = Message::default();
+    loop {
+        match reply_and_receive(session, reply) {
+            Ok(request) => reply = handler(&request),
+            Err(ReplyAndReceiveLightError::SessionClosed) => return Ok(()),
+            Err(err) => return Err(err),
+        }
+    }
+}
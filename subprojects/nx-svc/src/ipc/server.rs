@@ -0,0 +1,511 @@
+//! Server-side IPC ports and sessions.
+//!
+//! A port is the rendezvous point between a server and its clients. Clients connect to the
+//! [`ClientPort`] half, which queues a new session on the [`ServerPort`] half; the server then
+//! accepts it with [`accept_session`] and obtains a [`ServerSession`].
+//!
+//! Servers usually drive all of their ports and sessions from a single [`reply_and_receive`]
+//! loop.
+
+use core::ffi::{CStr, c_void};
+
+use super::client::Session;
+use crate::{
+    error::{KernelError as KError, ResultCodeError, ToRawResultCode},
+    handle::{OwnedHandle, Waitable},
+    mem::is_page_aligned,
+    raw::{self, Handle},
+    result::{
+        Error, ResultCode,
+        raw::{Result as RawResult, ResultCode as RawResultCode},
+    },
+    sync::MAX_WAIT_HANDLES,
+};
+
+define_waitable_handle_type! {
+    /// A handle to the server end of a port.
+    ///
+    /// It is signalled when a client connection is pending. See [`accept_session`].
+    pub struct ServerPort
+}
+
+define_handle_type! {
+    /// A handle to the client end of a port.
+    ///
+    /// Clients create new sessions from it with [`connect_to_port`].
+    pub struct ClientPort
+}
+
+define_waitable_handle_type! {
+    /// A handle to the server end of an IPC session.
+    ///
+    /// It is signalled when a request is pending or the client end has been closed.
+    pub struct ServerSession
+}
+
+/// Creates a new, unnamed port.
+///
+/// This is a safe wrapper around [`raw::create_port`]. `max_sessions` is the number of sessions
+/// that may be open on the port at the same time. `name` is only used by the kernel for debugging.
+///
+/// On success returns the `(server, client)` port handle pair.
+pub fn create_port(
+    max_sessions: u32,
+    name: Option<&CStr>,
+) -> Result<(OwnedHandle<ServerPort>, OwnedHandle<ClientPort>), CreatePortError> {
+    let Ok(max_sessions) = i32::try_from(max_sessions) else {
+        return Err(CreatePortError::OutOfRange);
+    };
+
+    let mut server = raw::INVALID_HANDLE;
+    let mut client = raw::INVALID_HANDLE;
+    let rc = unsafe {
+        raw::create_port(
+            &mut server,
+            &mut client,
+            max_sessions,
+            false,
+            name.map_or(core::ptr::null(), CStr::as_ptr),
+        )
+    };
+
+    RawResult::from_raw(rc).map_err(|rc| CreatePortError::from(Error::from(rc)))?;
+
+    // SAFETY: On success, the kernel returned new handles that are not owned elsewhere.
+    Ok(unsafe {
+        (
+            OwnedHandle::new(ServerPort(server)),
+            OwnedHandle::new(ClientPort(client)),
+        )
+    })
+}
+
+/// Error type for [`create_port`].
+#[derive(Debug, ResultCodeError)]
+pub enum CreatePortError {
+    /// `max_sessions` is zero or larger than `i32::MAX` —
+    /// `KernelError::OutOfRange` (raw code `0xEE01`).
+    #[rc(desc = OutOfRange, msg = "Invalid maximum session count")]
+    OutOfRange,
+    /// The kernel could not allocate the port object —
+    /// `KernelError::OutOfResource` (raw code `0xCE01`).
+    #[rc(desc = OutOfResource)]
+    OutOfResource,
+    /// The per-process port quota has been exhausted —
+    /// `KernelError::LimitReached` (raw code `0x10801`).
+    #[rc(desc = LimitReached, msg = "Port limit reached for process")]
+    LimitReached,
+    /// The process handle table contains no free slots for the two new handles —
+    /// `KernelError::OutOfHandles` (raw code `0xD201`).
+    #[rc(desc = OutOfHandles, msg = "Handle table full")]
+    OutOfHandles,
+    /// Any unforeseen kernel error. Contains the original [`Error`] so callers
+    /// can inspect the raw result (`Error::to_raw`).
+    Unknown(Error),
+}
+
+/// Registers a kernel named port and returns its server end.
+///
+/// This is a safe wrapper around [`raw::manage_named_port`]. Clients can then connect to the port
+/// with [`connect_to_named_port`](super::connect_to_named_port). The port name must be at most
+/// 11 characters long. `max_sessions` must not be zero; use [`unregister_named_port`] to remove a
+/// registration.
+pub fn manage_named_port(
+    name: &CStr,
+    max_sessions: u32,
+) -> Result<OwnedHandle<ServerPort>, ManageNamedPortError> {
+    let max_sessions = match i32::try_from(max_sessions) {
+        Ok(max_sessions) if max_sessions > 0 => max_sessions,
+        _ => return Err(ManageNamedPortError::OutOfRange),
+    };
+
+    let mut handle = raw::INVALID_HANDLE;
+    let rc = unsafe { raw::manage_named_port(&mut handle, name.as_ptr(), max_sessions) };
+    RawResult::from_raw(rc).map_err(|rc| ManageNamedPortError::from(Error::from(rc)))?;
+
+    // SAFETY: On success, the kernel returned a new handle that is not owned elsewhere.
+    Ok(unsafe { OwnedHandle::new(ServerPort(handle)) })
+}
+
+/// Removes the kernel named port registered under `name`.
+///
+/// This calls [`raw::manage_named_port`] with a maximum session count of zero. Sessions that are
+/// already open stay valid; only new connections by name are refused.
+pub fn unregister_named_port(name: &CStr) -> Result<(), ManageNamedPortError> {
+    let mut handle = raw::INVALID_HANDLE;
+    let rc = unsafe { raw::manage_named_port(&mut handle, name.as_ptr(), 0) };
+    RawResult::from_raw(rc).map((), |rc| Error::from(rc).into())
+}
+
+/// Error type for [`manage_named_port`] and [`unregister_named_port`].
+#[derive(Debug, ResultCodeError)]
+pub enum ManageNamedPortError {
+    /// The name is longer than 11 characters, or `max_sessions` is zero or larger than
+    /// `i32::MAX` — `KernelError::OutOfRange` (raw code `0xEE01`).
+    #[rc(desc = OutOfRange, msg = "Name or maximum session count out of range")]
+    OutOfRange,
+    /// A port is already registered under this name —
+    /// `KernelError::InvalidState` (raw code `0xFA01`).
+    #[rc(desc = InvalidState, msg = "Port name already registered")]
+    AlreadyRegistered,
+    /// No port is registered under this name —
+    /// `KernelError::NotFound` (raw code `0xF201`).
+    #[rc(desc = NotFound, msg = "Port not found")]
+    NotFound,
+    /// The kernel could not allocate the port object —
+    /// `KernelError::OutOfResource` (raw code `0xCE01`).
+    #[rc(desc = OutOfResource)]
+    OutOfResource,
+    /// The per-process port quota has been exhausted —
+    /// `KernelError::LimitReached` (raw code `0x10801`).
+    #[rc(desc = LimitReached, msg = "Port limit reached for process")]
+    LimitReached,
+    /// The process handle table contains no free slots —
+    /// `KernelError::OutOfHandles` (raw code `0xD201`).
+    #[rc(desc = OutOfHandles, msg = "Handle table full")]
+    OutOfHandles,
+    /// Any unforeseen kernel error. Contains the original [`Error`] so callers
+    /// can inspect the raw result (`Error::to_raw`).
+    Unknown(Error),
+}
+
+/// Opens a new session on a port through its client end.
+///
+/// This is a safe wrapper around [`raw::connect_to_port`].
+pub fn connect_to_port(port: &ClientPort) -> Result<OwnedHandle<Session>, ConnectToPortError> {
+    let mut handle = raw::INVALID_HANDLE;
+    let rc = unsafe { raw::connect_to_port(&mut handle, port.to_raw()) };
+
+    RawResult::from_raw(rc).map_err(|rc| ConnectToPortError::from(Error::from(rc)))?;
+
+    // SAFETY: On success, the kernel returned a new handle that is not owned elsewhere.
+    Ok(unsafe { OwnedHandle::from_raw(handle) })
+}
+
+/// Error type for [`connect_to_port`].
+#[derive(Debug, ResultCodeError)]
+pub enum ConnectToPortError {
+    /// The supplied handle is not a valid client port handle —
+    /// `KernelError::InvalidHandle` (raw code `0xE401`).
+    #[rc(desc = InvalidHandle)]
+    InvalidHandle,
+    /// The port has reached its maximum number of sessions —
+    /// `KernelError::OutOfSessions` (raw code `0xE01`).
+    #[rc(desc = OutOfSessions)]
+    OutOfSessions,
+    /// The kernel could not allocate the session object —
+    /// `KernelError::OutOfResource` (raw code `0xCE01`).
+    #[rc(desc = OutOfResource)]
+    OutOfResource,
+    /// The per-process session quota has been exhausted —
+    /// `KernelError::LimitReached` (raw code `0x10801`).
+    #[rc(desc = LimitReached, msg = "Session limit reached for process")]
+    LimitReached,
+    /// The process handle table contains no free slots —
+    /// `KernelError::OutOfHandles` (raw code `0xD201`).
+    #[rc(desc = OutOfHandles, msg = "Handle table full")]
+    OutOfHandles,
+    /// The server end of the port has been closed —
+    /// `KernelError::PortClosed` (raw code `0x10601`).
+    #[rc(desc = PortClosed)]
+    PortClosed,
+    /// Any unforeseen kernel error. Contains the original [`Error`] so callers
+    /// can inspect the raw result (`Error::to_raw`).
+    Unknown(Error),
+}
+
+/// Creates a new session that is not attached to any port.
+///
+/// This is a safe wrapper around [`raw::create_session`]. It is typically used to hand out
+/// sub-sessions (e.g. for CMIF "domain" or "clone" requests), where the client end is moved to
+/// the peer process inside a reply.
+///
+/// On success returns the `(server, client)` session handle pair.
+pub fn create_session()
+-> Result<(OwnedHandle<ServerSession>, OwnedHandle<Session>), CreateSessionError> {
+    let mut server = raw::INVALID_HANDLE;
+    let mut client = raw::INVALID_HANDLE;
+    let rc = unsafe { raw::create_session(&mut server, &mut client, false, 0) };
+
+    RawResult::from_raw(rc).map_err(|rc| CreateSessionError::from(Error::from(rc)))?;
+
+    // SAFETY: On success, the kernel returned new handles that are not owned elsewhere.
+    Ok(unsafe { (OwnedHandle::from_raw(server), OwnedHandle::from_raw(client)) })
+}
+
+/// Error type for [`create_session`].
+#[derive(Debug, ResultCodeError)]
+pub enum CreateSessionError {
+    /// The kernel could not allocate the session object —
+    /// `KernelError::OutOfResource` (raw code `0xCE01`).
+    #[rc(desc = OutOfResource)]
+    OutOfResource,
+    /// The per-process session quota has been exhausted —
+    /// `KernelError::LimitReached` (raw code `0x10801`).
+    #[rc(desc = LimitReached, msg = "Session limit reached for process")]
+    LimitReached,
+    /// The process handle table contains no free slots for the two new handles —
+    /// `KernelError::OutOfHandles` (raw code `0xD201`).
+    #[rc(desc = OutOfHandles, msg = "Handle table full")]
+    OutOfHandles,
+    /// Any unforeseen kernel error. Contains the original [`Error`] so callers
+    /// can inspect the raw result (`Error::to_raw`).
+    Unknown(Error),
+}
+
+/// Accepts a pending connection on a server port.
+///
+/// This is a safe wrapper around [`raw::accept_session`]. Call it once the port has been signalled
+/// (e.g. reported by [`reply_and_receive`]).
+pub fn accept_session(port: &ServerPort) -> Result<OwnedHandle<ServerSession>, AcceptSessionError> {
+    let mut handle = raw::INVALID_HANDLE;
+    let rc = unsafe { raw::accept_session(&mut handle, port.to_raw()) };
+
+    RawResult::from_raw(rc).map_err(|rc| AcceptSessionError::from(Error::from(rc)))?;
+
+    // SAFETY: On success, the kernel returned a new handle that is not owned elsewhere.
+    Ok(unsafe { OwnedHandle::new(ServerSession(handle)) })
+}
+
+/// Error type for [`accept_session`].
+#[derive(Debug, ResultCodeError)]
+pub enum AcceptSessionError {
+    /// The supplied handle is not a valid server port handle —
+    /// `KernelError::InvalidHandle` (raw code `0xE401`).
+    #[rc(desc = InvalidHandle)]
+    InvalidHandle,
+    /// No client is waiting to be accepted —
+    /// `KernelError::NotFound` (raw code `0xF201`).
+    #[rc(desc = NotFound)]
+    NoPendingSession,
+    /// The process handle table contains no free slots —
+    /// `KernelError::OutOfHandles` (raw code `0xD201`).
+    #[rc(desc = OutOfHandles, msg = "Handle table full")]
+    OutOfHandles,
+    /// Any unforeseen kernel error. Contains the original [`Error`] so callers
+    /// can inspect the raw result (`Error::to_raw`).
+    Unknown(Error),
+}
+
+/// Optionally replies to a session, then waits for the next incoming request or connection.
+///
+/// This is a wrapper around [`raw::reply_and_receive`], using the calling thread's TLS message
+/// buffer.
+///
+/// If `reply_target` is `Some`, the reply currently stored in the TLS message buffer is first
+/// sent to that session. The call then waits until one of `handles` is signalled, and returns its
+/// index. If the signalled handle is a [`ServerSession`], the received request has been copied to
+/// the TLS message buffer. If it is a [`ServerPort`], a connection is pending (see
+/// [`accept_session`]).
+///
+/// Ports and sessions can be mixed by passing `&dyn Waitable` items. At most [`MAX_WAIT_HANDLES`]
+/// handles are forwarded, and any extra ones are **silently ignored**, as with
+/// [`wait_synchronization_multiple`](crate::sync::wait_synchronization_multiple).
+///
+/// `timeout` is in nanoseconds (`u64::MAX` for an infinite wait). To only send a reply, pass no
+/// handles and a timeout of `0`. The call then returns [`ReplyAndReceiveError::TimedOut`] once the
+/// reply has been delivered.
+///
+/// # Safety
+///
+/// The caller must ensure that:
+/// 1. Every forwarded handle is a valid server port or server session handle owned by the current
+///    process.
+/// 2. The TLS message buffer contains a well-formed reply if `reply_target` is `Some`, or a blank
+///    message otherwise. Any buffer descriptors or receive lists in it must point to memory that
+///    stays valid for the whole call.
+pub unsafe fn reply_and_receive<'a, W, I>(
+    handles: I,
+    reply_target: Option<&ServerSession>,
+    timeout: u64,
+) -> Result<usize, ReplyAndReceiveError>
+where
+    W: Waitable + ?Sized + 'a,
+    I: IntoIterator<Item = &'a W>,
+{
+    let (raw_handles, count) = collect_raw_handles(handles);
+
+    let mut index = -1;
+    let rc = unsafe {
+        raw::reply_and_receive(
+            &mut index,
+            raw_handles.as_ptr(),
+            count as i32,
+            reply_target.map_or(raw::INVALID_HANDLE, ServerSession::to_raw),
+            timeout,
+        )
+    };
+    RawResult::from_raw(rc).map(index as usize, |rc| map_reply_and_receive_error(rc, index))
+}
+
+/// Like [`reply_and_receive`], but uses `buffer` instead of the TLS message buffer.
+///
+/// This is a wrapper around [`raw::reply_and_receive_with_user_buffer`]. The buffer's address and
+/// length must both be multiples of [`PAGE_SIZE`](crate::mem::PAGE_SIZE). This is checked before
+/// the SVC is issued.
+///
+/// # Safety
+///
+/// Same as [`reply_and_receive`], with `buffer` taking the place of the TLS message buffer.
+pub unsafe fn reply_and_receive_with_user_buffer<'a, W, I>(
+    buffer: &mut [u8],
+    handles: I,
+    reply_target: Option<&ServerSession>,
+    timeout: u64,
+) -> Result<usize, ReplyAndReceiveWithUserBufferError>
+where
+    W: Waitable + ?Sized + 'a,
+    I: IntoIterator<Item = &'a W>,
+{
+    if !is_page_aligned(buffer.as_ptr() as usize) {
+        return Err(ReplyAndReceiveWithUserBufferError::InvalidAddress);
+    }
+    if buffer.is_empty() || !is_page_aligned(buffer.len()) {
+        return Err(ReplyAndReceiveWithUserBufferError::InvalidSize);
+    }
+
+    let (raw_handles, count) = collect_raw_handles(handles);
+
+    let mut index = -1;
+    let rc = unsafe {
+        raw::reply_and_receive_with_user_buffer(
+            &mut index,
+            buffer.as_mut_ptr() as *mut c_void,
+            buffer.len() as u64,
+            raw_handles.as_ptr(),
+            count as i32,
+            reply_target.map_or(raw::INVALID_HANDLE, ServerSession::to_raw),
+            timeout,
+        )
+    };
+    RawResult::from_raw(rc).map(index as usize, |rc| match rc.description() {
+        desc if KError::InvalidAddress == desc => {
+            ReplyAndReceiveWithUserBufferError::InvalidAddress
+        }
+        desc if KError::InvalidSize == desc => ReplyAndReceiveWithUserBufferError::InvalidSize,
+        desc if KError::InvalidCurrentMemory == desc => {
+            ReplyAndReceiveWithUserBufferError::InvalidCurrentMemory
+        }
+        _ => ReplyAndReceiveWithUserBufferError::Receive(map_reply_and_receive_error(rc, index)),
+    })
+}
+
+/// Copies up to [`MAX_WAIT_HANDLES`] raw handles into a stack buffer.
+fn collect_raw_handles<'a, W, I>(handles: I) -> ([Handle; MAX_WAIT_HANDLES], usize)
+where
+    W: Waitable + ?Sized + 'a,
+    I: IntoIterator<Item = &'a W>,
+{
+    let mut raw_handles = [raw::INVALID_HANDLE; MAX_WAIT_HANDLES];
+    let mut count = 0usize;
+    for (slot, h) in raw_handles.iter_mut().zip(handles) {
+        *slot = h.raw_handle();
+        count += 1;
+    }
+    (raw_handles, count)
+}
+
+fn map_reply_and_receive_error(rc: RawResultCode, index: i32) -> ReplyAndReceiveError {
+    match rc.description() {
+        desc if KError::InvalidHandle == desc => ReplyAndReceiveError::InvalidHandle,
+        desc if KError::SessionClosed == desc => ReplyAndReceiveError::SessionClosed {
+            index: usize::try_from(index).ok(),
+        },
+        desc if KError::TimedOut == desc => ReplyAndReceiveError::TimedOut,
+        desc if KError::Cancelled == desc => ReplyAndReceiveError::Cancelled,
+        desc if KError::TerminationRequested == desc => ReplyAndReceiveError::TerminationRequested,
+        desc if KError::MessageTooLarge == desc => ReplyAndReceiveError::MessageTooLarge,
+        desc if KError::ReceiveListBroken == desc => ReplyAndReceiveError::ReceiveListBroken,
+        _ => ReplyAndReceiveError::Unknown(rc.into()),
+    }
+}
+
+/// Error type for [`reply_and_receive`].
+#[derive(Debug, thiserror::Error)]
+pub enum ReplyAndReceiveError {
+    /// One of the handles, or the reply target, is not a valid server port or session handle —
+    /// `KernelError::InvalidHandle` (raw code `0xE401`).
+    #[error("Invalid handle")]
+    InvalidHandle,
+    /// The client end of a session has been closed —
+    /// `KernelError::SessionClosed` (raw code `0xF601`).
+    ///
+    /// `index` is the position of the closed session in the handle list. It is `None` if the reply
+    /// could not be delivered because the reply target was closed; no request was received in
+    /// that case.
+    #[error("Session closed")]
+    SessionClosed {
+        /// Index of the closed session, or `None` for the reply target.
+        index: Option<usize>,
+    },
+    /// No handle was signalled before the timeout expired —
+    /// `KernelError::TimedOut` (raw code `0xEA01`).
+    #[error("Timed out")]
+    TimedOut,
+    /// The wait was cancelled by another thread —
+    /// `KernelError::Cancelled` (raw code `0xEC01`).
+    #[error("Cancelled")]
+    Cancelled,
+    /// The calling thread was asked to terminate while waiting —
+    /// `KernelError::TerminationRequested` (raw code `0x7601`).
+    #[error("Termination requested")]
+    TerminationRequested,
+    /// The message does not fit in the receiver's buffer —
+    /// `KernelError::MessageTooLarge` (raw code `0x20801`).
+    #[error("Message too large")]
+    MessageTooLarge,
+    /// The receive list of the message is invalid —
+    /// `KernelError::ReceiveListBroken` (raw code `0x20401`).
+    #[error("Receive list broken")]
+    ReceiveListBroken,
+    /// Any unforeseen kernel error. Contains the original [`Error`] so callers
+    /// can inspect the raw result (`Error::to_raw`).
+    #[error("Unknown error: {0}")]
+    Unknown(Error),
+}
+
+impl ToRawResultCode for ReplyAndReceiveError {
+    fn to_rc(self) -> ResultCode {
+        match self {
+            Self::InvalidHandle => KError::InvalidHandle.to_rc(),
+            Self::SessionClosed { .. } => KError::SessionClosed.to_rc(),
+            Self::TimedOut => KError::TimedOut.to_rc(),
+            Self::Cancelled => KError::Cancelled.to_rc(),
+            Self::TerminationRequested => KError::TerminationRequested.to_rc(),
+            Self::MessageTooLarge => KError::MessageTooLarge.to_rc(),
+            Self::ReceiveListBroken => KError::ReceiveListBroken.to_rc(),
+            Self::Unknown(err) => err.to_raw(),
+        }
+    }
+}
+
+/// Error type for [`reply_and_receive_with_user_buffer`].
+#[derive(Debug, thiserror::Error)]
+pub enum ReplyAndReceiveWithUserBufferError {
+    /// The buffer address is not page-aligned —
+    /// `KernelError::InvalidAddress` (raw code `0xCC01`).
+    #[error("Invalid buffer address")]
+    InvalidAddress,
+    /// The buffer length is zero or not a multiple of the page size —
+    /// `KernelError::InvalidSize` (raw code `0xCA01`).
+    #[error("Invalid buffer size")]
+    InvalidSize,
+    /// The buffer is not in a memory state usable for IPC —
+    /// `KernelError::InvalidCurrentMemory` (raw code `0xD401`).
+    #[error("Invalid memory state")]
+    InvalidCurrentMemory,
+    /// Replying or receiving failed.
+    #[error(transparent)]
+    Receive(#[from] ReplyAndReceiveError),
+}
+
+impl ToRawResultCode for ReplyAndReceiveWithUserBufferError {
+    fn to_rc(self) -> ResultCode {
+        match self {
+            Self::InvalidAddress => KError::InvalidAddress.to_rc(),
+            Self::InvalidSize => KError::InvalidSize.to_rc(),
+            Self::InvalidCurrentMemory => KError::InvalidCurrentMemory.to_rc(),
+            Self::Receive(err) => err.to_rc(),
+        }
+    }
+}
@@ -9,6 +9,9 @@
 
 #![no_std]
 
+// Lets the `nx-svc-derive` macros refer to this crate as `::nx_svc` from within it.
+extern crate self as nx_svc;
+
 /// #[panic_handler]
 ///
 /// Custom panic handler that calls svcBreak with Panic reason.
@@ -17,20 +20,30 @@
 /// See:
 ///  - <https://doc.rust-lang.org/nomicon/panic-handler.html>
 ///  - <https://docs.rust-embedded.org/book/start/panicking.html>
-mod panic_handler;
+pub mod panic_handler;
 
 #[macro_use]
-mod handle;
+pub mod handle;
 
+pub mod backtrace;
 pub mod code;
 pub mod debug;
+pub mod debugger;
 pub mod error;
+pub mod event;
+pub mod exception;
+pub mod ipc;
 pub mod mem;
 pub mod misc;
+pub mod process;
 pub mod raw;
+pub mod resource_limit;
 pub mod result;
+pub mod smc;
+pub mod symbolize;
 pub mod sync;
 pub mod thread;
+pub mod version;
 
 #[cfg(feature = "ffi")]
 mod ffi;
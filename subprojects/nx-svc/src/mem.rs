@@ -1,10 +1,15 @@
 //! Memory management system calls and utilities for the Horizon OS kernel.
 //!
 //! This module provides safe wrappers around memory-related system calls for querying
-//! memory properties and unmapping memory.
+//! memory properties and unmapping memory, as well as a walker over the process address space
+//! ([`regions`], [`MemoryMap`]).
 
+pub mod codemem;
 pub mod core;
+pub mod map;
 pub mod shmem;
 pub mod tmem;
 
 pub use core::*;
+
+pub use map::{MemoryMap, Regions, regions};
@@ -0,0 +1,165 @@
+//! Code memory helpers for Horizon OS kernel.
+//!
+//! Provides safe wrappers around the low-level SVCs involved in creating and
+//! mapping code memory kernel objects \[4.0.0+\], the kernel's mechanism for
+//! just-in-time (JIT) compilation.
+//!
+//! A code memory object takes ownership of a page-aligned region of the
+//! caller's heap. The region can then be mapped twice: once as the *owner*
+//! view (read/write, to emit code) and once as the *slave* view (read/execute,
+//! to run it).
+
+use core::{ffi::c_void, ptr::NonNull};
+
+use crate::{
+    error::ResultCodeError,
+    handle::OwnedHandle,
+    mem::core::MemoryPermission,
+    raw,
+    result::{Error, raw::Result as RawResult},
+};
+
+define_handle_type! {
+    /// A handle to a code memory kernel object.
+    pub struct Handle
+}
+
+/// Creates a code memory kernel object from an existing memory region.
+///
+/// `addr` **must** be page-aligned (4 KiB) and have at least `size` bytes
+/// allocated. The region becomes inaccessible to the caller until the code
+/// memory object is closed.
+pub fn create_code_memory(
+    addr: NonNull<c_void>,
+    size: usize,
+) -> Result<OwnedHandle<Handle>, CreateCodeMemoryError> {
+    let mut handle = raw::INVALID_HANDLE;
+    let rc = unsafe { raw::create_code_memory(&mut handle, addr.as_ptr(), size as u64) };
+
+    RawResult::from_raw(rc).map_err(|rc| CreateCodeMemoryError::from(Error::from(rc)))?;
+
+    // SAFETY: On success, the kernel returned a new handle that is not owned elsewhere.
+    Ok(unsafe { OwnedHandle::new(Handle(handle)) })
+}
+
+/// Maps the owner (writable) view of a code memory object.
+///
+/// `perm` must be [`MemoryPermission::R`] | [`MemoryPermission::W`].
+pub fn map_owner(
+    handle: Handle,
+    addr: NonNull<c_void>,
+    size: usize,
+    perm: MemoryPermission,
+) -> Result<(), ControlCodeMemoryError> {
+    control_code_memory(handle, raw::CodeMapOperation::MapOwner, addr, size, perm)
+}
+
+/// Maps the slave (executable) view of a code memory object.
+///
+/// `perm` must be [`MemoryPermission::R`], optionally with [`MemoryPermission::X`].
+pub fn map_slave(
+    handle: Handle,
+    addr: NonNull<c_void>,
+    size: usize,
+    perm: MemoryPermission,
+) -> Result<(), ControlCodeMemoryError> {
+    control_code_memory(handle, raw::CodeMapOperation::MapSlave, addr, size, perm)
+}
+
+/// Unmaps the owner view of a code memory object.
+pub fn unmap_owner(
+    handle: Handle,
+    addr: NonNull<c_void>,
+    size: usize,
+) -> Result<(), ControlCodeMemoryError> {
+    control_code_memory(
+        handle,
+        raw::CodeMapOperation::UnmapOwner,
+        addr,
+        size,
+        MemoryPermission::empty(),
+    )
+}
+
+/// Unmaps the slave view of a code memory object.
+pub fn unmap_slave(
+    handle: Handle,
+    addr: NonNull<c_void>,
+    size: usize,
+) -> Result<(), ControlCodeMemoryError> {
+    control_code_memory(
+        handle,
+        raw::CodeMapOperation::UnmapSlave,
+        addr,
+        size,
+        MemoryPermission::empty(),
+    )
+}
+
+fn control_code_memory(
+    handle: Handle,
+    op: raw::CodeMapOperation,
+    addr: NonNull<c_void>,
+    size: usize,
+    perm: MemoryPermission,
+) -> Result<(), ControlCodeMemoryError> {
+    let rc = unsafe {
+        raw::control_code_memory(
+            handle.to_raw(),
+            op,
+            addr.as_ptr(),
+            size as u64,
+            perm.bits() as u64,
+        )
+    };
+    RawResult::from_raw(rc).map((), |rc| Error::from(rc).into())
+}
+
+/// Closes a code memory handle.
+pub fn close_handle(handle: Handle) -> Result<(), CloseHandleError> {
+    let rc = unsafe { raw::close_handle(handle.to_raw()) };
+    RawResult::from_raw(rc).map((), |rc| Error::from(rc).into())
+}
+
+#[derive(Debug, ResultCodeError)]
+pub enum CreateCodeMemoryError {
+    #[rc(desc = InvalidSize)]
+    InvalidSize,
+    #[rc(desc = InvalidAddress)]
+    InvalidAddress,
+    #[rc(desc = InvalidCurrentMemory, msg = "Invalid memory state")]
+    InvalidMemState,
+    #[rc(desc = OutOfResource)]
+    OutOfResource,
+    #[rc(desc = LimitReached)]
+    LimitReached,
+    #[rc(desc = OutOfHandles, msg = "Handle table full")]
+    OutOfHandles,
+    Unknown(Error),
+}
+
+#[derive(Debug, ResultCodeError)]
+pub enum ControlCodeMemoryError {
+    #[rc(desc = InvalidHandle)]
+    InvalidHandle,
+    #[rc(desc = InvalidAddress)]
+    InvalidAddress,
+    #[rc(desc = InvalidSize)]
+    InvalidSize,
+    #[rc(desc = InvalidCurrentMemory, msg = "Invalid memory state")]
+    InvalidCurrentMemory,
+    #[rc(desc = InvalidMemoryRegion)]
+    InvalidMemoryRegion,
+    #[rc(desc = InvalidNewMemoryPermission)]
+    InvalidPermission,
+    #[rc(desc = InvalidEnumValue)]
+    InvalidOperation,
+    Unknown(Error),
+}
+
+#[derive(Debug, ResultCodeError)]
+pub enum CloseHandleError {
+    #[rc(desc = InvalidHandle)]
+    InvalidHandle,
+    Unknown(Error),
+}
@@ -3,7 +3,7 @@ use core::{ffi::c_void, ptr, ptr::NonNull};
 use bitflags::bitflags;
 
 use crate::{
-    error::{KernelError as KError, ResultCode, ToRawResultCode},
+    error::ResultCodeError,
     raw,
     result::{Error, raw::Result as RawResult},
 };
@@ -11,6 +11,17 @@ use crate::{
 /// Page information
 pub type PageInfo = u32;
 
+/// Size of a memory page (4 KiB).
+///
+/// Most memory-related SVCs require addresses and sizes to be multiples of this value.
+pub const PAGE_SIZE: usize = 0x1000;
+
+/// Returns `true` if `value` is a multiple of [`PAGE_SIZE`].
+#[inline]
+pub(crate) const fn is_page_aligned(value: usize) -> bool {
+    value & (PAGE_SIZE - 1) == 0
+}
+
 /// Sets the process heap to a given size.
 ///
 /// It can extend and shrink the heap.
@@ -20,23 +31,11 @@ pub type PageInfo = u32;
 pub fn set_heap_size(size: usize) -> Result<*mut c_void, SetHeapSizeError> {
     let mut addr = ptr::null_mut();
     let rc = unsafe { raw::set_heap_size(&mut addr, size) };
-    RawResult::from_raw(rc).map(addr, |rc| match rc.description() {
-        desc if KError::InvalidSize == desc => SetHeapSizeError::InvalidSize,
-        desc if KError::OutOfResource == desc => SetHeapSizeError::OutOfResource,
-        desc if KError::OutOfMemory == desc => SetHeapSizeError::OutOfMemory,
-        desc if KError::InvalidCurrentMemory == desc => SetHeapSizeError::InvalidCurrentMemory,
-        desc if KError::InvalidNewMemoryPermission == desc => {
-            SetHeapSizeError::InvalidNewMemoryPermission
-        }
-        desc if KError::InvalidMemoryRegion == desc => SetHeapSizeError::InvalidMemoryRegion,
-        desc if KError::InvalidState == desc => SetHeapSizeError::InvalidState,
-        desc if KError::LimitReached == desc => SetHeapSizeError::LimitReached,
-        _ => SetHeapSizeError::Unknown(rc.into()),
-    })
+    RawResult::from_raw(rc).map(addr, |rc| Error::from(rc).into())
 }
 
 /// Error type for set_heap_size operations.
-#[derive(Debug, thiserror::Error)]
+#[derive(Debug, ResultCodeError)]
 pub enum SetHeapSizeError {
     /// The size parameter is invalid.
     ///
@@ -44,7 +43,7 @@ pub enum SetHeapSizeError {
     /// - The size is not aligned to 4KB
     /// - The size is 0
     /// - The size would cause an overflow
-    #[error("Invalid size")]
+    #[rc(desc = InvalidSize)]
     InvalidSize,
 
     /// System resources are exhausted.
@@ -52,7 +51,7 @@ pub enum SetHeapSizeError {
     /// This occurs when:
     /// - The system has no more physical memory available
     /// - The system has no more virtual memory available
-    #[error("Out of resource")]
+    #[rc(desc = OutOfResource)]
     OutOfResource,
 
     /// Not enough memory available.
@@ -60,7 +59,7 @@ pub enum SetHeapSizeError {
     /// This occurs when:
     /// - The process has reached its memory limit
     /// - The system cannot allocate the requested amount of memory
-    #[error("Out of memory")]
+    #[rc(desc = OutOfMemory)]
     OutOfMemory,
 
     /// Current memory state is invalid.
@@ -69,7 +68,7 @@ pub enum SetHeapSizeError {
     /// - The memory region is not in the correct state for heap operations
     /// - The memory region is not properly mapped
     /// - The memory region has incorrect permissions
-    #[error("Invalid memory state")]
+    #[rc(desc = InvalidCurrentMemory, msg = "Invalid memory state")]
     InvalidCurrentMemory,
 
     /// Memory permissions are invalid.
@@ -77,7 +76,7 @@ pub enum SetHeapSizeError {
     /// This occurs when:
     /// - The requested permissions are not allowed for heap memory
     /// - The permissions would conflict with existing memory attributes
-    #[error("Invalid memory permission")]
+    #[rc(desc = InvalidNewMemoryPermission, msg = "Invalid memory permission")]
     InvalidNewMemoryPermission,
 
     /// Memory region is invalid.
@@ -86,7 +85,7 @@ pub enum SetHeapSizeError {
     /// - The requested size exceeds the maximum heap size
     /// - The requested size exceeds the available heap region
     /// - The operation would exceed the process's memory limit
-    #[error("Invalid memory region")]
+    #[rc(desc = InvalidMemoryRegion)]
     InvalidMemoryRegion,
 
     /// Operation is invalid for current state.
@@ -94,7 +93,7 @@ pub enum SetHeapSizeError {
     /// This occurs when:
     /// - The heap is in an invalid state for the requested operation
     /// - The operation cannot be performed in the current context
-    #[error("Invalid state")]
+    #[rc(desc = InvalidState)]
     InvalidState,
 
     /// Resource limit reached.
@@ -102,32 +101,13 @@ pub enum SetHeapSizeError {
     /// This occurs when:
     /// - The process has reached its resource limit
     /// - The system cannot allocate more resources
-    #[error("Resource limit reached")]
+    #[rc(desc = LimitReached, msg = "Resource limit reached")]
     LimitReached,
 
     /// An unknown error occurred
-    #[error("Unknown error: {0}")]
     Unknown(Error),
 }
 
-impl ToRawResultCode for SetHeapSizeError {
-    fn to_rc(self) -> ResultCode {
-        match self {
-            SetHeapSizeError::InvalidSize => KError::InvalidSize.to_rc(),
-            SetHeapSizeError::OutOfResource => KError::OutOfResource.to_rc(),
-            SetHeapSizeError::OutOfMemory => KError::OutOfMemory.to_rc(),
-            SetHeapSizeError::InvalidCurrentMemory => KError::InvalidCurrentMemory.to_rc(),
-            SetHeapSizeError::InvalidNewMemoryPermission => {
-                KError::InvalidNewMemoryPermission.to_rc()
-            }
-            SetHeapSizeError::InvalidMemoryRegion => KError::InvalidMemoryRegion.to_rc(),
-            SetHeapSizeError::InvalidState => KError::InvalidState.to_rc(),
-            SetHeapSizeError::LimitReached => KError::LimitReached.to_rc(),
-            SetHeapSizeError::Unknown(err) => err.to_raw(),
-        }
-    }
-}
-
 /// Queries information about a memory address.
 ///
 /// This function is used to get detailed information about a memory address,
@@ -144,22 +124,17 @@ pub fn query_memory(addr: usize) -> Result<(MemoryInfo, PageInfo), QueryMemoryEr
     let mut page_info = Default::default();
 
     let rc = unsafe { raw::query_memory(&mut mem_info, &mut page_info, addr) };
-    RawResult::from_raw(rc).map((mem_info.into(), page_info), |rc| match rc.description() {
-        desc if KError::InvalidHandle == desc => QueryMemoryError::InvalidHandle,
-        desc if KError::InvalidAddress == desc => QueryMemoryError::InvalidAddress,
-        desc if KError::InvalidCurrentMemory == desc => QueryMemoryError::InvalidCurrentMemory,
-        _ => QueryMemoryError::Unknown(rc.into()),
-    })
+    RawResult::from_raw(rc).map((mem_info.into(), page_info), |rc| Error::from(rc).into())
 }
 
 /// Error type for query_memory operations.
-#[derive(Debug, thiserror::Error)]
+#[derive(Debug, ResultCodeError)]
 pub enum QueryMemoryError {
     /// The process handle is invalid or not found.
     ///
     /// This occurs when trying to query memory from a process that doesn't exist
     /// or when the handle table lookup fails.
-    #[error("Invalid handle")]
+    #[rc(desc = InvalidHandle)]
     InvalidHandle,
 
     /// The address is invalid or not properly aligned.
@@ -168,7 +143,7 @@ pub enum QueryMemoryError {
     /// - The address is not aligned to 4KB
     /// - The address is outside the process's address space
     /// - The address would cause an overflow when used in calculations
-    #[error("Invalid address")]
+    #[rc(desc = InvalidAddress)]
     InvalidAddress,
 
     /// The memory state is invalid for the operation.
@@ -177,25 +152,13 @@ pub enum QueryMemoryError {
     /// - The memory region is not in a valid state for querying
     /// - The memory region is not mapped
     /// - The memory region is not accessible to the current process
-    #[error("Invalid memory state")]
+    #[rc(desc = InvalidCurrentMemory, msg = "Invalid memory state")]
     InvalidCurrentMemory,
 
     /// An unknown error occurred
-    #[error("Unknown error: {0}")]
     Unknown(Error),
 }
 
-impl ToRawResultCode for QueryMemoryError {
-    fn to_rc(self) -> ResultCode {
-        match self {
-            Self::InvalidHandle => KError::InvalidHandle.to_rc(),
-            Self::InvalidAddress => KError::InvalidAddress.to_rc(),
-            Self::InvalidCurrentMemory => KError::InvalidCurrentMemory.to_rc(),
-            Self::Unknown(err) => err.to_raw(),
-        }
-    }
-}
-
 /// Maps a memory range.
 ///
 /// This function is used to map a memory range.
@@ -213,23 +176,17 @@ pub fn map_memory(
     size: usize,
 ) -> Result<(), MapMemoryError> {
     let rc = unsafe { raw::map_memory(dst.as_ptr(), src.as_ptr(), size) };
-    RawResult::from_raw(rc).map((), |rc| match rc.description() {
-        desc if KError::InvalidAddress == desc => MapMemoryError::InvalidAddress,
-        desc if KError::InvalidSize == desc => MapMemoryError::InvalidSize,
-        desc if KError::InvalidCurrentMemory == desc => MapMemoryError::InvalidCurrentMemory,
-        desc if KError::InvalidMemoryRegion == desc => MapMemoryError::InvalidMemoryRegion,
-        _ => MapMemoryError::Unknown(rc.into()),
-    })
+    RawResult::from_raw(rc).map((), |rc| Error::from(rc).into())
 }
 
 /// Error type for map_memory operations.
-#[derive(Debug, thiserror::Error)]
+#[derive(Debug, ResultCodeError)]
 pub enum MapMemoryError {
     /// The memory address is invalid or not properly aligned.
     ///
     /// This occurs when either the source or destination address is not aligned to 4KB,
     /// or when the address range would cause an overflow.
-    #[error("Invalid address")]
+    #[rc(desc = InvalidAddress)]
     InvalidAddress,
 
     /// The size parameter is invalid.
@@ -237,7 +194,7 @@ pub enum MapMemoryError {
     /// This occurs when:
     /// - The size is 0
     /// - The size is not aligned to 4KB
-    #[error("Invalid size")]
+    #[rc(desc = InvalidSize)]
     InvalidSize,
 
     /// The memory state is invalid for the operation.
@@ -246,7 +203,7 @@ pub enum MapMemoryError {
     /// - The source address range is not within the process's address space
     /// - The address range would cause an overflow (address + size <= address)
     /// - The memory region is not in a valid state for mapping
-    #[error("Invalid memory state")]
+    #[rc(desc = InvalidCurrentMemory, msg = "Invalid memory state")]
     InvalidCurrentMemory,
 
     /// The memory range is invalid for the operation.
@@ -254,26 +211,13 @@ pub enum MapMemoryError {
     /// This occurs when:
     /// - The destination is outside the stack/alias region
     /// - The mapping violates region constraints enforced by the kernel
-    #[error("Invalid memory range")]
+    #[rc(desc = InvalidMemoryRegion, msg = "Invalid memory range")]
     InvalidMemoryRegion,
 
     /// An unknown error occurred
-    #[error("Unknown error: {0}")]
     Unknown(Error),
 }
 
-impl ToRawResultCode for MapMemoryError {
-    fn to_rc(self) -> ResultCode {
-        match self {
-            MapMemoryError::InvalidAddress => KError::InvalidAddress.to_rc(),
-            MapMemoryError::InvalidSize => KError::InvalidSize.to_rc(),
-            MapMemoryError::InvalidCurrentMemory => KError::InvalidCurrentMemory.to_rc(),
-            MapMemoryError::InvalidMemoryRegion => KError::InvalidMemoryRegion.to_rc(),
-            MapMemoryError::Unknown(err) => err.to_raw(),
-        }
-    }
-}
-
 /// Unmaps a memory range.
 ///
 /// This function is used to unmap a previously mapped memory range.
@@ -291,30 +235,24 @@ pub fn unmap_memory(
     size: usize,
 ) -> Result<(), UnmapMemoryError> {
     let rc = unsafe { raw::unmap_memory(dst.as_ptr(), src.as_ptr(), size) };
-    RawResult::from_raw(rc).map((), |rc| match rc.description() {
-        desc if KError::InvalidHandle == desc => UnmapMemoryError::InvalidHandle,
-        desc if KError::InvalidAddress == desc => UnmapMemoryError::InvalidAddress,
-        desc if KError::InvalidCurrentMemory == desc => UnmapMemoryError::InvalidCurrentMemory,
-        desc if KError::InvalidMemoryRegion == desc => UnmapMemoryError::InvalidMemoryRegion,
-        _ => UnmapMemoryError::Unknown(rc.into()),
-    })
+    RawResult::from_raw(rc).map((), |rc| Error::from(rc).into())
 }
 
 /// Error type for unmap_memory operations.
-#[derive(Debug, thiserror::Error)]
+#[derive(Debug, ResultCodeError)]
 pub enum UnmapMemoryError {
     /// The process handle is invalid or not found.
     ///
     /// This occurs when trying to unmap memory from a process that doesn't exist
     /// or when the handle table lookup fails.
-    #[error("Invalid handle")]
+    #[rc(desc = InvalidHandle)]
     InvalidHandle,
 
     /// The memory address is invalid or not properly aligned.
     ///
     /// This occurs when either the source or destination address is not aligned to 4KB,
     /// or when the address range would cause an overflow.
-    #[error("Invalid address")]
+    #[rc(desc = InvalidAddress)]
     InvalidAddress,
 
     /// The memory state is invalid for the operation.
@@ -323,7 +261,7 @@ pub enum UnmapMemoryError {
     /// - The source address range is not within the process's address space
     /// - The address range would cause an overflow (address + size <= address)
     /// - The memory region is not in a valid state for unmapping
-    #[error("Invalid memory state")]
+    #[rc(desc = InvalidCurrentMemory, msg = "Invalid memory state")]
     InvalidCurrentMemory,
 
     /// The memory range is invalid for the operation.
@@ -332,24 +270,144 @@ pub enum UnmapMemoryError {
     /// - The destination is outside the stack region
     /// - The destination is inside the heap region
     /// - The destination is inside the alias region
-    #[error("Invalid memory range")]
+    #[rc(desc = InvalidMemoryRegion, msg = "Invalid memory range")]
     InvalidMemoryRegion,
 
     /// An unknown error occurred
-    #[error("Unknown error: {0}")]
     Unknown(Error),
 }
 
-impl ToRawResultCode for UnmapMemoryError {
-    fn to_rc(self) -> ResultCode {
-        match self {
-            UnmapMemoryError::InvalidHandle => KError::InvalidHandle.to_rc(),
-            UnmapMemoryError::InvalidAddress => KError::InvalidAddress.to_rc(),
-            UnmapMemoryError::InvalidCurrentMemory => KError::InvalidCurrentMemory.to_rc(),
-            UnmapMemoryError::InvalidMemoryRegion => KError::InvalidMemoryRegion.to_rc(),
-            UnmapMemoryError::Unknown(err) => err.to_raw(),
-        }
+/// Sets the memory permissions of a memory range.
+///
+/// This is a safe wrapper around [`raw::set_memory_permission`]. The kernel only accepts this on
+/// memory the process owns (e.g. heap or static data), and only allows the permissions
+/// `---`, `R--` and `RW-`.
+///
+/// # Arguments
+///
+/// * `addr` - The start address of the range; must be aligned to [`PAGE_SIZE`]
+/// * `size` - The size of the range; must be a non-zero multiple of [`PAGE_SIZE`]
+/// * `perm` - The new permissions
+///
+/// Returns `Ok(())` if the permissions were changed, or a [`SetMemoryPermissionError`] on failure.
+pub fn set_memory_permission(
+    addr: NonNull<c_void>,
+    size: usize,
+    perm: MemoryPermission,
+) -> Result<(), SetMemoryPermissionError> {
+    if !is_page_aligned(addr.as_ptr() as usize) {
+        return Err(SetMemoryPermissionError::InvalidAddress);
+    }
+    if size == 0 || !is_page_aligned(size) {
+        return Err(SetMemoryPermissionError::InvalidSize);
+    }
+
+    let rc = unsafe { raw::set_memory_permission(addr.as_ptr(), size, perm.bits()) };
+    RawResult::from_raw(rc).map((), |rc| Error::from(rc).into())
+}
+
+/// Error type for set_memory_permission operations.
+#[derive(Debug, ResultCodeError)]
+pub enum SetMemoryPermissionError {
+    /// The memory address is invalid or not properly aligned.
+    ///
+    /// This occurs when the address is not aligned to 4KB.
+    #[rc(desc = InvalidAddress)]
+    InvalidAddress,
+
+    /// The size parameter is invalid.
+    ///
+    /// This occurs when:
+    /// - The size is 0
+    /// - The size is not aligned to 4KB
+    #[rc(desc = InvalidSize)]
+    InvalidSize,
+
+    /// The memory state is invalid for the operation.
+    ///
+    /// This occurs when:
+    /// - The address range is not within the process's address space
+    /// - The memory region does not allow its permissions to be changed
+    #[rc(desc = InvalidCurrentMemory, msg = "Invalid memory state")]
+    InvalidCurrentMemory,
+
+    /// The requested permissions are not allowed.
+    ///
+    /// This occurs when the permissions are not one of `---`, `R--` or `RW-`.
+    #[rc(desc = InvalidNewMemoryPermission, msg = "Invalid memory permission")]
+    InvalidNewMemoryPermission,
+
+    /// An unknown error occurred
+    Unknown(Error),
+}
+
+/// Sets the memory attributes of a memory range.
+///
+/// This is a safe wrapper around [`raw::set_memory_attribute`]. Only the attributes selected by
+/// `mask` are changed, and are set to their value in `attr`. The kernel only allows changing
+/// [`MemoryAttribute::UNCACHED`] (and [`MemoryAttribute::PERMISSION_LOCKED`] on newer firmware).
+///
+/// # Arguments
+///
+/// * `addr` - The start address of the range; must be aligned to [`PAGE_SIZE`]
+/// * `size` - The size of the range; must be a non-zero multiple of [`PAGE_SIZE`]
+/// * `mask` - The attributes to change
+/// * `attr` - The new values of the attributes in `mask`
+///
+/// Returns `Ok(())` if the attributes were changed, or a [`SetMemoryAttributeError`] on failure.
+pub fn set_memory_attribute(
+    addr: NonNull<c_void>,
+    size: usize,
+    mask: MemoryAttribute,
+    attr: MemoryAttribute,
+) -> Result<(), SetMemoryAttributeError> {
+    if !is_page_aligned(addr.as_ptr() as usize) {
+        return Err(SetMemoryAttributeError::InvalidAddress);
     }
+    if size == 0 || !is_page_aligned(size) {
+        return Err(SetMemoryAttributeError::InvalidSize);
+    }
+
+    let rc =
+        unsafe { raw::set_memory_attribute(addr.as_ptr(), size, mask.0.bits(), attr.0.bits()) };
+    RawResult::from_raw(rc).map((), |rc| Error::from(rc).into())
+}
+
+/// Error type for set_memory_attribute operations.
+#[derive(Debug, ResultCodeError)]
+pub enum SetMemoryAttributeError {
+    /// The memory address is invalid or not properly aligned.
+    ///
+    /// This occurs when the address is not aligned to 4KB.
+    #[rc(desc = InvalidAddress)]
+    InvalidAddress,
+
+    /// The size parameter is invalid.
+    ///
+    /// This occurs when:
+    /// - The size is 0
+    /// - The size is not aligned to 4KB
+    #[rc(desc = InvalidSize)]
+    InvalidSize,
+
+    /// The memory state is invalid for the operation.
+    ///
+    /// This occurs when:
+    /// - The address range is not within the process's address space
+    /// - The memory region does not allow its attributes to be changed
+    #[rc(desc = InvalidCurrentMemory, msg = "Invalid memory state")]
+    InvalidCurrentMemory,
+
+    /// The mask and attribute combination is not allowed.
+    ///
+    /// This occurs when:
+    /// - The mask selects attributes that cannot be changed
+    /// - The attributes contain bits outside the mask
+    #[rc(desc = InvalidCombination, msg = "Invalid attribute combination")]
+    InvalidCombination,
+
+    /// An unknown error occurred
+    Unknown(Error),
 }
 
 /// Information about a memory region.
@@ -622,6 +680,21 @@ impl From<MemoryType> for raw::MemoryType {
 pub struct MemoryAttribute(raw::MemoryAttribute);
 
 impl MemoryAttribute {
+    /// No attributes set
+    pub const NONE: MemoryAttribute = MemoryAttribute(raw::MemoryAttribute::empty());
+
+    /// The uncached attribute
+    pub const UNCACHED: MemoryAttribute = MemoryAttribute(raw::MemoryAttribute::IS_UNCACHED);
+
+    /// The permission locked attribute
+    pub const PERMISSION_LOCKED: MemoryAttribute =
+        MemoryAttribute(raw::MemoryAttribute::IS_PERMISSION_LOCKED);
+
+    /// Returns the union of the attributes in `self` and `other`
+    pub const fn union(self, other: MemoryAttribute) -> MemoryAttribute {
+        MemoryAttribute(self.0.union(other.0))
+    }
+
     /// Returns whether this memory region is uncached
     pub fn is_uncached(&self) -> bool {
         self.0.contains(raw::MemoryAttribute::IS_UNCACHED)
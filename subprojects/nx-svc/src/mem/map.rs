@@ -0,0 +1,116 @@
+//! Address-space walking.
+//!
+//! [`regions`] walks the current process's address space with [`query_memory`], starting at
+//! address 0, and yields one [`MemoryInfo`] per contiguous region. [`MemoryMap`] formats the
+//! mapped regions as a `/proc/self/maps`-style table.
+
+use core::{fmt, iter::FusedIterator};
+
+use super::core::{MemoryInfo, MemoryState, MemoryType, query_memory};
+
+/// Returns an iterator over every memory region of the current process.
+///
+/// The regions are yielded in ascending address order and cover the whole address space,
+/// including [`MemoryType::Unmapped`] gaps. The walk stops at the end of the address space, or
+/// at the first [`query_memory`] failure.
+pub fn regions() -> Regions {
+    Regions::starting_at(0)
+}
+
+/// Iterator over the memory regions of the current process.
+///
+/// Created by [`regions`].
+#[derive(Debug, Clone)]
+pub struct Regions {
+    next: Option<usize>,
+}
+
+impl Regions {
+    /// Creates an iterator starting at the region that contains `addr`.
+    pub fn starting_at(addr: usize) -> Self {
+        Self { next: Some(addr) }
+    }
+
+    /// Only yields regions of the given type.
+    pub fn of_type(self, typ: MemoryType) -> impl Iterator<Item = MemoryInfo> {
+        self.filter(move |info| info.typ == typ)
+    }
+
+    /// Only yields regions whose state satisfies `pred`.
+    ///
+    /// For example, `regions().with_state(MemoryState::can_ipc)` yields the regions that can be
+    /// used as IPC buffers.
+    pub fn with_state<F>(self, mut pred: F) -> impl Iterator<Item = MemoryInfo>
+    where
+        F: FnMut(&MemoryState) -> bool,
+    {
+        self.filter(move |info| pred(&info.state))
+    }
+
+    /// Skips [`MemoryType::Unmapped`] regions.
+    pub fn mapped(self) -> impl Iterator<Item = MemoryInfo> {
+        self.filter(|info| info.typ != MemoryType::Unmapped)
+    }
+}
+
+impl Iterator for Regions {
+    type Item = MemoryInfo;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let addr = self.next.take()?;
+        let (info, _) = query_memory(addr).ok()?;
+
+        // The last region ends at the top of the address space, where `addr + size` wraps to 0.
+        self.next = info.addr.checked_add(info.size).filter(|&end| end > addr);
+
+        Some(info)
+    }
+}
+
+impl FusedIterator for Regions {}
+
+/// `/proc/self/maps`-style view of the current process's address space.
+///
+/// Formatting it (e.g. with `{}`) walks the address space and writes one line per mapped region:
+///
+/// ```text
+/// 0000000008000000-0000000008006000 r-x ---- CodeStatic
+/// 0000000008006000-0000000008007000 r-- ---- CodeMutable
+/// ```
+///
+/// The columns are the start and end addresses, the permissions, the attribute flags
+/// (`B`orrowed, `I`PC-mapped, `D`evice-mapped, `U`ncached) and the memory type. The address space
+/// is queried again each time the value is formatted.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MemoryMap;
+
+impl fmt::Display for MemoryMap {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for info in regions().mapped() {
+            writeln!(f, "{info}")?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for MemoryInfo {
+    /// Formats the region as a single `/proc/self/maps`-style line (see [`MemoryMap`]).
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let flag = |set: bool, c: char| if set { c } else { '-' };
+
+        write!(
+            f,
+            "{:016x}-{:016x} {}{}{} {}{}{}{} {:?}",
+            self.addr,
+            self.addr.wrapping_add(self.size),
+            flag(self.perm.is_readable(), 'r'),
+            flag(self.perm.is_writable(), 'w'),
+            flag(self.perm.is_executable(), 'x'),
+            flag(self.attr.is_borrowed(), 'B'),
+            flag(self.attr.is_ipc_mapped(), 'I'),
+            flag(self.attr.is_device_mapped(), 'D'),
+            flag(self.attr.is_uncached(), 'U'),
+            self.typ,
+        )
+    }
+}
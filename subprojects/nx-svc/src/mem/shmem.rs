@@ -8,9 +8,10 @@ use core::{ffi::c_void, ptr::NonNull};
 use bitflags::bitflags;
 
 use crate::{
-    error::{KernelError as KError, ToRawResultCode},
+    error::ResultCodeError,
+    handle::OwnedHandle,
     raw,
-    result::{Error, ResultCode, raw::Result as RawResult},
+    result::{Error, raw::Result as RawResult},
 };
 
 define_handle_type! {
@@ -25,39 +26,27 @@ pub fn create_shared_memory(
     size: usize,
     local_perm: LocalShmemPermission,
     remote_perm: RemoteShmemPermission,
-) -> Result<Handle, CreateSharedMemoryError> {
+) -> Result<OwnedHandle<Handle>, CreateSharedMemoryError> {
     let mut handle = raw::INVALID_HANDLE;
     let rc = unsafe {
         raw::create_shared_memory(&mut handle, size, local_perm.bits(), remote_perm.bits())
     };
 
-    RawResult::from_raw(rc).map(Handle(handle), |rc| match rc.description() {
-        desc if KError::OutOfMemory == desc => CreateSharedMemoryError::OutOfMemory,
-        desc if KError::LimitReached == desc => CreateSharedMemoryError::LimitReached,
-        _ => CreateSharedMemoryError::Unknown(rc.into()),
-    })
+    RawResult::from_raw(rc).map_err(|rc| CreateSharedMemoryError::from(Error::from(rc)))?;
+
+    // SAFETY: On success, the kernel returned a new handle that is not owned elsewhere.
+    Ok(unsafe { OwnedHandle::new(Handle(handle)) })
 }
 
-#[derive(Debug, thiserror::Error)]
+#[derive(Debug, ResultCodeError)]
 pub enum CreateSharedMemoryError {
-    #[error("Out of memory")]
+    #[rc(desc = OutOfMemory)]
     OutOfMemory,
-    #[error("Limit reached")]
+    #[rc(desc = LimitReached)]
     LimitReached,
-    #[error("Unknown error: {0}")]
     Unknown(Error),
 }
 
-impl ToRawResultCode for CreateSharedMemoryError {
-    fn to_rc(self) -> ResultCode {
-        match self {
-            Self::OutOfMemory => KError::OutOfMemory.to_rc(),
-            Self::LimitReached => KError::LimitReached.to_rc(),
-            Self::Unknown(err) => err.to_raw(),
-        }
-    }
-}
-
 /// Maps a shared memory object into the current process.
 pub fn map_shared_memory(
     handle: Handle,
@@ -66,59 +55,30 @@ pub fn map_shared_memory(
     perm: MemoryPermission,
 ) -> Result<(), MapSharedMemoryError> {
     let rc = unsafe { raw::map_shared_memory(handle.0, addr.as_ptr(), size, perm.bits()) };
-    RawResult::from_raw(rc).map((), |rc| match rc.description() {
-        desc if KError::InvalidHandle == desc => MapSharedMemoryError::InvalidHandle,
-        desc if KError::InvalidAddress == desc => MapSharedMemoryError::InvalidAddress,
-        desc if KError::InvalidCurrentMemory == desc => MapSharedMemoryError::InvalidCurrentMemory,
-        desc if KError::InvalidMemoryRegion == desc => MapSharedMemoryError::InvalidMemoryRegion,
-        desc if KError::InvalidSize == desc => MapSharedMemoryError::InvalidSize,
-        desc if KError::InvalidNewMemoryPermission == desc => {
-            MapSharedMemoryError::InvalidPermission
-        }
-        desc if KError::OutOfResource == desc => MapSharedMemoryError::OutOfResource,
-        desc if KError::OutOfMemory == desc => MapSharedMemoryError::OutOfMemory,
-        _ => MapSharedMemoryError::Unknown(rc.into()),
-    })
+    RawResult::from_raw(rc).map((), |rc| Error::from(rc).into())
 }
 
-#[derive(Debug, thiserror::Error)]
+#[derive(Debug, ResultCodeError)]
 pub enum MapSharedMemoryError {
-    #[error("Invalid handle")]
+    #[rc(desc = InvalidHandle)]
     InvalidHandle,
-    #[error("Invalid address")]
+    #[rc(desc = InvalidAddress)]
     InvalidAddress,
-    #[error("Invalid memory state")]
+    #[rc(desc = InvalidCurrentMemory, msg = "Invalid memory state")]
     InvalidCurrentMemory,
-    #[error("Invalid memory region")]
+    #[rc(desc = InvalidMemoryRegion)]
     InvalidMemoryRegion,
-    #[error("Invalid size")]
+    #[rc(desc = InvalidSize)]
     InvalidSize,
-    #[error("Invalid permission")]
+    #[rc(desc = InvalidNewMemoryPermission)]
     InvalidPermission,
-    #[error("Out of resource")]
+    #[rc(desc = OutOfResource)]
     OutOfResource,
-    #[error("Out of memory")]
+    #[rc(desc = OutOfMemory)]
     OutOfMemory,
-    #[error("Unknown error: {0}")]
     Unknown(Error),
 }
 
-impl ToRawResultCode for MapSharedMemoryError {
-    fn to_rc(self) -> ResultCode {
-        match self {
-            Self::InvalidHandle => KError::InvalidHandle.to_rc(),
-            Self::InvalidAddress => KError::InvalidAddress.to_rc(),
-            Self::InvalidCurrentMemory => KError::InvalidCurrentMemory.to_rc(),
-            Self::InvalidMemoryRegion => KError::InvalidMemoryRegion.to_rc(),
-            Self::InvalidSize => KError::InvalidSize.to_rc(),
-            Self::InvalidPermission => KError::InvalidNewMemoryPermission.to_rc(),
-            Self::OutOfResource => KError::OutOfResource.to_rc(),
-            Self::OutOfMemory => KError::OutOfMemory.to_rc(),
-            Self::Unknown(err) => err.to_raw(),
-        }
-    }
-}
-
 /// Unmaps a previously mapped shared memory kernel object.
 pub fn unmap_shared_memory(
     handle: Handle,
@@ -126,69 +86,35 @@ pub fn unmap_shared_memory(
     size: usize,
 ) -> Result<(), UnmapSharedMemoryError> {
     let rc = unsafe { raw::unmap_shared_memory(handle.0, addr.as_ptr(), size) };
-    RawResult::from_raw(rc).map((), |rc| match rc.description() {
-        desc if KError::InvalidCurrentMemory == desc => {
-            UnmapSharedMemoryError::InvalidCurrentMemory
-        }
-        desc if KError::InvalidSize == desc => UnmapSharedMemoryError::InvalidSize,
-        desc if KError::InvalidMemoryRegion == desc => UnmapSharedMemoryError::InvalidMemoryRange,
-        desc if KError::OutOfResource == desc => UnmapSharedMemoryError::OutOfResource,
-        _ => UnmapSharedMemoryError::Unknown(rc.into()),
-    })
+    RawResult::from_raw(rc).map((), |rc| Error::from(rc).into())
 }
 
-#[derive(Debug, thiserror::Error)]
+#[derive(Debug, ResultCodeError)]
 pub enum UnmapSharedMemoryError {
-    #[error("Invalid memory state")]
+    #[rc(desc = InvalidCurrentMemory, msg = "Invalid memory state")]
     InvalidCurrentMemory,
-    #[error("Invalid size")]
+    #[rc(desc = InvalidSize)]
     InvalidSize,
-    #[error("Invalid memory range")]
+    #[rc(desc = InvalidMemoryRegion)]
     InvalidMemoryRange,
-    #[error("Out of resource")]
+    #[rc(desc = OutOfResource)]
     OutOfResource,
-    #[error("Unknown error: {0}")]
     Unknown(Error),
 }
 
-impl ToRawResultCode for UnmapSharedMemoryError {
-    fn to_rc(self) -> ResultCode {
-        match self {
-            Self::InvalidCurrentMemory => KError::InvalidCurrentMemory.to_rc(),
-            Self::InvalidSize => KError::InvalidSize.to_rc(),
-            Self::InvalidMemoryRange => KError::InvalidMemoryRegion.to_rc(),
-            Self::OutOfResource => KError::OutOfResource.to_rc(),
-            Self::Unknown(err) => err.to_raw(),
-        }
-    }
-}
-
 /// Closes a shared memory kernel object handle.
 pub fn close_handle(handle: Handle) -> Result<(), CloseHandleError> {
     let rc = unsafe { raw::close_handle(handle.0) };
-    RawResult::from_raw(rc).map((), |rc| match rc.description() {
-        desc if KError::InvalidHandle == desc => CloseHandleError::InvalidHandle,
-        _ => CloseHandleError::Unknown(rc.into()),
-    })
+    RawResult::from_raw(rc).map((), |rc| Error::from(rc).into())
 }
 
-#[derive(Debug, thiserror::Error)]
+#[derive(Debug, ResultCodeError)]
 pub enum CloseHandleError {
-    #[error("Invalid handle")]
+    #[rc(desc = InvalidHandle)]
     InvalidHandle,
-    #[error("Unknown error: {0}")]
     Unknown(Error),
 }
 
-impl ToRawResultCode for CloseHandleError {
-    fn to_rc(self) -> ResultCode {
-        match self {
-            Self::InvalidHandle => KError::InvalidHandle.to_rc(),
-            Self::Unknown(err) => err.to_raw(),
-        }
-    }
-}
-
 bitflags! {
     /// Local shared memory permissions
     #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -8,9 +8,10 @@ use core::{ffi::c_void, ptr::NonNull};
 use bitflags::bitflags;
 
 use crate::{
-    error::{KernelError as KError, ToRawResultCode},
+    error::ResultCodeError,
+    handle::OwnedHandle,
     raw,
-    result::{Error, ResultCode, raw::Result as RawResult},
+    result::{Error, raw::Result as RawResult},
 };
 
 define_handle_type! {
@@ -27,20 +28,14 @@ pub fn create_transfer_memory(
     addr: NonNull<c_void>,
     size: usize,
     perm: MemoryPermission,
-) -> Result<Handle, CreateTransferMemoryError> {
+) -> Result<OwnedHandle<Handle>, CreateTransferMemoryError> {
     let mut handle = raw::INVALID_HANDLE;
     let rc = unsafe { raw::create_transfer_memory(&mut handle, addr.as_ptr(), size, perm.bits()) };
 
-    RawResult::from_raw(rc).map(Handle(handle), |rc| match rc.description() {
-        desc if KError::InvalidSize == desc => CreateTransferMemoryError::InvalidSize,
-        desc if KError::InvalidAddress == desc => CreateTransferMemoryError::InvalidAddress,
-        desc if KError::InvalidNewMemoryPermission == desc => {
-            CreateTransferMemoryError::InvalidPermission
-        }
-        desc if KError::InvalidCurrentMemory == desc => CreateTransferMemoryError::InvalidMemState,
-        desc if KError::LimitReached == desc => CreateTransferMemoryError::LimitReached,
-        _ => CreateTransferMemoryError::Unknown(rc.into()),
-    })
+    RawResult::from_raw(rc).map_err(|rc| CreateTransferMemoryError::from(Error::from(rc)))?;
+
+    // SAFETY: On success, the kernel returned a new handle that is not owned elsewhere.
+    Ok(unsafe { OwnedHandle::new(Handle(handle)) })
 }
 
 /// Maps a transfer memory object into the current process.
@@ -51,19 +46,7 @@ pub fn map_transfer_memory(
     perm: MemoryPermission,
 ) -> Result<(), MapTransferMemoryError> {
     let rc = unsafe { raw::map_transfer_memory(handle.to_raw(), addr.as_ptr(), size, perm.bits()) };
-    RawResult::from_raw(rc).map((), |rc| match rc.description() {
-        desc if KError::InvalidHandle == desc => MapTransferMemoryError::InvalidHandle,
-        desc if KError::InvalidAddress == desc => MapTransferMemoryError::InvalidAddress,
-        desc if KError::InvalidCurrentMemory == desc => {
-            MapTransferMemoryError::InvalidCurrentMemory
-        }
-        desc if KError::InvalidSize == desc => MapTransferMemoryError::InvalidSize,
-        desc if KError::InvalidMemoryRegion == desc => MapTransferMemoryError::InvalidMemoryRegion,
-        desc if KError::InvalidNewMemoryPermission == desc => {
-            MapTransferMemoryError::InvalidPermission
-        }
-        _ => MapTransferMemoryError::Unknown(rc.into()),
-    })
+    RawResult::from_raw(rc).map((), |rc| Error::from(rc).into())
 }
 
 /// Unmaps a previously mapped transfer memory object.
@@ -73,27 +56,13 @@ pub fn unmap_transfer_memory(
     size: usize,
 ) -> Result<(), UnmapTransferMemoryError> {
     let rc = unsafe { raw::unmap_transfer_memory(handle.to_raw(), addr.as_ptr(), size) };
-    RawResult::from_raw(rc).map((), |rc| match rc.description() {
-        desc if KError::InvalidHandle == desc => UnmapTransferMemoryError::InvalidHandle,
-        desc if KError::InvalidAddress == desc => UnmapTransferMemoryError::InvalidAddress,
-        desc if KError::InvalidCurrentMemory == desc => {
-            UnmapTransferMemoryError::InvalidCurrentMemory
-        }
-        desc if KError::InvalidSize == desc => UnmapTransferMemoryError::InvalidSize,
-        desc if KError::InvalidMemoryRegion == desc => {
-            UnmapTransferMemoryError::InvalidMemoryRegion
-        }
-        _ => UnmapTransferMemoryError::Unknown(rc.into()),
-    })
+    RawResult::from_raw(rc).map((), |rc| Error::from(rc).into())
 }
 
 /// Closes a transfer memory handle.
 pub fn close_handle(handle: Handle) -> Result<(), CloseHandleError> {
     let rc = unsafe { raw::close_handle(handle.to_raw()) };
-    RawResult::from_raw(rc).map((), |rc| match rc.description() {
-        desc if KError::InvalidHandle == desc => CloseHandleError::InvalidHandle,
-        _ => CloseHandleError::Unknown(rc.into()),
-    })
+    RawResult::from_raw(rc).map((), |rc| Error::from(rc).into())
 }
 
 bitflags! {
@@ -121,109 +90,56 @@ bitflags! {
     }
 }
 
-#[derive(Debug, thiserror::Error)]
+#[derive(Debug, ResultCodeError)]
 pub enum CreateTransferMemoryError {
-    #[error("Invalid size")]
+    #[rc(desc = InvalidSize)]
     InvalidSize,
-    #[error("Invalid address")]
+    #[rc(desc = InvalidAddress)]
     InvalidAddress,
-    #[error("Invalid permission")]
+    #[rc(desc = InvalidNewMemoryPermission)]
     InvalidPermission,
-    #[error("Invalid memory state")]
+    #[rc(desc = InvalidCurrentMemory, msg = "Invalid memory state")]
     InvalidMemState,
-    #[error("Limit reached")]
+    #[rc(desc = LimitReached)]
     LimitReached,
-    #[error("Unknown error: {0}")]
     Unknown(Error),
 }
 
-impl ToRawResultCode for CreateTransferMemoryError {
-    fn to_rc(self) -> ResultCode {
-        match self {
-            Self::InvalidSize => KError::InvalidSize.to_rc(),
-            Self::InvalidAddress => KError::InvalidAddress.to_rc(),
-            Self::InvalidPermission => KError::InvalidNewMemoryPermission.to_rc(),
-            Self::InvalidMemState => KError::InvalidCurrentMemory.to_rc(),
-            Self::LimitReached => KError::LimitReached.to_rc(),
-            Self::Unknown(err) => err.to_raw(),
-        }
-    }
-}
-
-#[derive(Debug, thiserror::Error)]
+#[derive(Debug, ResultCodeError)]
 pub enum MapTransferMemoryError {
-    #[error("Invalid handle")]
+    #[rc(desc = InvalidHandle)]
     InvalidHandle,
-    #[error("Invalid address")]
+    #[rc(desc = InvalidAddress)]
     InvalidAddress,
-    #[error("Invalid size")]
+    #[rc(desc = InvalidSize)]
     InvalidSize,
-    #[error("Invalid memory state")]
+    #[rc(desc = InvalidCurrentMemory, msg = "Invalid memory state")]
     InvalidCurrentMemory,
-    #[error("Invalid memory region")]
+    #[rc(desc = InvalidMemoryRegion)]
     InvalidMemoryRegion,
-    #[error("Invalid permission")]
+    #[rc(desc = InvalidNewMemoryPermission)]
     InvalidPermission,
-    #[error("Unknown error: {0}")]
     Unknown(Error),
 }
 
-impl ToRawResultCode for MapTransferMemoryError {
-    fn to_rc(self) -> ResultCode {
-        match self {
-            Self::InvalidHandle => KError::InvalidHandle.to_rc(),
-            Self::InvalidAddress => KError::InvalidAddress.to_rc(),
-            Self::InvalidSize => KError::InvalidSize.to_rc(),
-            Self::InvalidCurrentMemory => KError::InvalidCurrentMemory.to_rc(),
-            Self::InvalidMemoryRegion => KError::InvalidMemoryRegion.to_rc(),
-            Self::InvalidPermission => KError::InvalidNewMemoryPermission.to_rc(),
-            Self::Unknown(err) => err.to_raw(),
-        }
-    }
-}
-
-#[derive(Debug, thiserror::Error)]
+#[derive(Debug, ResultCodeError)]
 pub enum UnmapTransferMemoryError {
-    #[error("Invalid handle")]
+    #[rc(desc = InvalidHandle)]
     InvalidHandle,
-    #[error("Invalid address")]
+    #[rc(desc = InvalidAddress)]
     InvalidAddress,
-    #[error("Invalid size")]
+    #[rc(desc = InvalidSize)]
     InvalidSize,
-    #[error("Invalid memory state")]
+    #[rc(desc = InvalidCurrentMemory, msg = "Invalid memory state")]
     InvalidCurrentMemory,
-    #[error("Invalid memory region")]
+    #[rc(desc = InvalidMemoryRegion)]
     InvalidMemoryRegion,
-    #[error("Unknown error: {0}")]
     Unknown(Error),
 }
 
-impl ToRawResultCode for UnmapTransferMemoryError {
-    fn to_rc(self) -> ResultCode {
-        match self {
-            Self::InvalidHandle => KError::InvalidHandle.to_rc(),
-            Self::InvalidAddress => KError::InvalidAddress.to_rc(),
-            Self::InvalidSize => KError::InvalidSize.to_rc(),
-            Self::InvalidCurrentMemory => KError::InvalidCurrentMemory.to_rc(),
-            Self::InvalidMemoryRegion => KError::InvalidMemoryRegion.to_rc(),
-            Self::Unknown(err) => err.to_raw(),
-        }
-    }
-}
-
-#[derive(Debug, thiserror::Error)]
+#[derive(Debug, ResultCodeError)]
 pub enum CloseHandleError {
-    #[error("Invalid handle")]
+    #[rc(desc = InvalidHandle)]
     InvalidHandle,
-    #[error("Unknown error: {0}")]
     Unknown(Error),
 }
-
-impl ToRawResultCode for CloseHandleError {
-    fn to_rc(self) -> ResultCode {
-        match self {
-            Self::InvalidHandle => KError::InvalidHandle.to_rc(),
-            Self::Unknown(err) => err.to_raw(),
-        }
-    }
-}
@@ -6,12 +6,15 @@
 //!
 //! The main functionality is centered around the [`get_info`] system call, which provides
 //! a type-safe way to query various system properties and kernel object information.
+//! Privileged, system-wide information is available through [`get_system_info`].
 
 pub use super::raw::{CUR_PROCESS_HANDLE, Handle, INVALID_HANDLE};
 use super::{
-    error::{KernelError, Module, ResultCode, ToRawResultCode},
+    error::{KernelError, ResultCode, ResultCodeError, ToRawResultCode},
+    process::ProcessId,
     raw,
     result::{Error, Result, raw::Result as RawResult},
+    version::{self, Version},
 };
 
 /// Retrieves random entropy.
@@ -148,16 +151,14 @@ pub fn get_info(info_type: InfoType, handle: Handle) -> Result<u64, GetInfoError
         } else if desc == KernelError::InvalidAddress {
             GetInfoError::InvalidAddress
         } else if desc == KernelError::InvalidEnumValue {
-            // Check if it's an info type or ID error based on the error code
-            if rc.module() == Module::Kernel {
-                if desc == KernelError::InvalidEnumValue {
-                    GetInfoError::InvalidInfoType
-                } else {
-                    GetInfoError::InvalidInfoId
-                }
+            // Tell apart info types this firmware lacks from plain invalid ones
+            if info_type.is_available_on(version::current()) {
+                GetInfoError::InvalidInfoType
             } else {
-                GetInfoError::Unknown(Error::from(rc))
+                GetInfoError::UnsupportedOnThisFirmware
             }
+        } else if desc == KernelError::InvalidCombination {
+            GetInfoError::InvalidInfoId
         } else {
             GetInfoError::Unknown(Error::from(rc))
         }
@@ -232,6 +233,58 @@ pub enum InfoType {
 }
 
 impl InfoType {
+    /// Returns the first firmware version supporting this info type.
+    pub const fn min_version(&self) -> Version {
+        match *self {
+            InfoType::CoreMask
+            | InfoType::PriorityMask
+            | InfoType::AliasRegionAddress
+            | InfoType::AliasRegionSize
+            | InfoType::HeapRegionAddress
+            | InfoType::HeapRegionSize
+            | InfoType::TotalMemorySize
+            | InfoType::UsedMemorySize
+            | InfoType::DebuggerAttached
+            | InfoType::ResourceLimit
+            | InfoType::ThreadTickCountDeprecated => Version::new(1, 0, 0),
+            InfoType::IdleTickCount { .. }
+            | InfoType::RandomEntropy { .. }
+            | InfoType::AslrRegionAddress
+            | InfoType::AslrRegionSize
+            | InfoType::StackRegionAddress
+            | InfoType::StackRegionSize => Version::new(2, 0, 0),
+            InfoType::SystemResourceSizeTotal
+            | InfoType::SystemResourceSizeUsed
+            | InfoType::ProgramId => Version::new(3, 0, 0),
+            InfoType::InitialProcessIdRange { .. } => Version::new(4, 0, 0),
+            InfoType::UserExceptionContextAddress => Version::new(5, 0, 0),
+            InfoType::TotalNonSystemMemorySize | InfoType::UsedNonSystemMemorySize => {
+                Version::new(6, 0, 0)
+            }
+            InfoType::IsApplication => Version::new(9, 0, 0),
+            InfoType::FreeThreadCount => Version::new(11, 0, 0),
+            InfoType::ThreadTickCount { .. } => Version::new(13, 0, 0),
+            InfoType::IsSvcPermitted => Version::new(14, 0, 0),
+            InfoType::IoRegionHint => Version::new(16, 0, 0),
+            InfoType::AliasRegionExtraSize => Version::new(18, 0, 0),
+            InfoType::TransferMemoryHint => Version::new(19, 0, 0),
+        }
+    }
+
+    /// Returns the last firmware version supporting this info type, if it has been removed since.
+    pub const fn max_version(&self) -> Option<Version> {
+        match *self {
+            InfoType::InitialProcessIdRange { .. } => Some(Version::new(4, 1, 0)),
+            InfoType::ThreadTickCountDeprecated => Some(Version::new(12, 1, 0)),
+            _ => None,
+        }
+    }
+
+    /// Returns `true` if this info type is supported on firmware `version`.
+    pub fn is_available_on(&self, version: Version) -> bool {
+        version >= self.min_version() && self.max_version().is_none_or(|max| version <= max)
+    }
+
     /// Returns the (id0, id1) pair for [`__nx_svc_get_info`].
     pub fn to_ids(&self) -> (u32, u64) {
         match *self {
@@ -288,9 +341,12 @@ pub enum GetInfoError {
     /// The info type is invalid.
     #[error("Invalid info type")]
     InvalidInfoType,
-    /// The info ID is invalid.
+    /// The info sub-ID is invalid.
     #[error("Invalid info ID")]
     InvalidInfoId,
+    /// The info type is not available on the running firmware (see [`InfoType::min_version`]).
+    #[error("Info type unsupported on this firmware")]
+    UnsupportedOnThisFirmware,
     /// An unknown error occurred.
     ///
     /// This variant is used when the error code is not recognized.
@@ -306,8 +362,146 @@ impl ToRawResultCode for GetInfoError {
             GetInfoError::InvalidMemState => KernelError::InvalidAddress.to_rc(),
             GetInfoError::InvalidAddress => KernelError::InvalidAddress.to_rc(),
             GetInfoError::InvalidInfoType => KernelError::InvalidEnumValue.to_rc(),
-            GetInfoError::InvalidInfoId => KernelError::InvalidEnumValue.to_rc(),
+            GetInfoError::InvalidInfoId => KernelError::InvalidCombination.to_rc(),
+            GetInfoError::UnsupportedOnThisFirmware => KernelError::InvalidEnumValue.to_rc(),
             GetInfoError::Unknown(err) => err.to_raw(),
         }
     }
 }
+
+/// Retrieves the total size of the given physical memory pool.
+///
+/// This is a convenience wrapper around [`get_system_info`] for the
+/// [`SystemInfoType::TotalPhysicalMemorySize`] info type.
+pub fn get_total_physical_memory_size(
+    pool: PhysicalMemoryPool,
+) -> Result<usize, GetSystemInfoError> {
+    get_system_info(SystemInfoType::TotalPhysicalMemorySize { pool }).map(|size| size as usize)
+}
+
+/// Retrieves the amount of memory currently used in the given physical memory pool.
+///
+/// This is a convenience wrapper around [`get_system_info`] for the
+/// [`SystemInfoType::UsedPhysicalMemorySize`] info type.
+pub fn get_used_physical_memory_size(
+    pool: PhysicalMemoryPool,
+) -> Result<usize, GetSystemInfoError> {
+    get_system_info(SystemInfoType::UsedPhysicalMemorySize { pool }).map(|size| size as usize)
+}
+
+/// Retrieves the range of process IDs assigned to the initial (kernel-launched) processes.
+///
+/// Returns the inclusive `(min, max)` bounds of the range. This is a convenience wrapper around
+/// [`get_system_info`] for the [`SystemInfoType::InitialProcessIdRange`] info type.
+pub fn get_initial_process_id_range() -> Result<(ProcessId, ProcessId), GetSystemInfoError> {
+    let min = get_system_info(SystemInfoType::InitialProcessIdRange {
+        bound: ProcessIdRangeBound::Min,
+    })?;
+    let max = get_system_info(SystemInfoType::InitialProcessIdRange {
+        bound: ProcessIdRangeBound::Max,
+    })?;
+    Ok((ProcessId(min), ProcessId(max)))
+}
+
+/// Retrieves privileged information about the system.
+///
+/// This is a safe wrapper around [`raw::get_system_info`]. Unlike [`get_info`], the supported
+/// info types are not tied to a kernel object, so the call is always issued with
+/// [`INVALID_HANDLE`].
+///
+/// The calling process must be permitted to use `svcGetSystemInfo` (`0x6F`), which is usually
+/// only the case for system modules.
+pub fn get_system_info(info_type: SystemInfoType) -> Result<u64, GetSystemInfoError> {
+    let (id0, id1) = info_type.to_ids();
+    let mut out = 0u64;
+
+    let rc = unsafe { raw::get_system_info(&mut out, id0, INVALID_HANDLE, id1) };
+    RawResult::from_raw(rc).map(out, |rc| Error::from(rc).into())
+}
+
+/// Info type for [`get_system_info`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SystemInfoType {
+    /// Total size of a physical memory pool.
+    TotalPhysicalMemorySize { pool: PhysicalMemoryPool },
+    /// Amount of memory currently used in a physical memory pool.
+    UsedPhysicalMemorySize { pool: PhysicalMemoryPool },
+    /// [5.0.0+] Bounds of the process ID range assigned to the initial processes.
+    InitialProcessIdRange { bound: ProcessIdRangeBound },
+}
+
+impl SystemInfoType {
+    /// Returns the (id0, id1) pair for [`raw::get_system_info`].
+    pub fn to_ids(&self) -> (u64, u64) {
+        match *self {
+            SystemInfoType::TotalPhysicalMemorySize { pool } => (0, pool as u64),
+            SystemInfoType::UsedPhysicalMemorySize { pool } => (1, pool as u64),
+            SystemInfoType::InitialProcessIdRange { bound } => (2, bound as u64),
+        }
+    }
+}
+
+/// Physical memory pool, as partitioned by the kernel.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(u64)]
+pub enum PhysicalMemoryPool {
+    /// Memory reserved for the running application.
+    Application = 0,
+    /// Memory reserved for applets.
+    Applet = 1,
+    /// Memory reserved for system modules.
+    System = 2,
+    /// Memory reserved for system modules, not protected from DMA by the memory controller.
+    SystemUnsafe = 3,
+}
+
+/// Bound of the initial process ID range, see [`SystemInfoType::InitialProcessIdRange`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(u64)]
+pub enum ProcessIdRangeBound {
+    /// Lowest initial process ID.
+    Min = 0,
+    /// Highest initial process ID.
+    Max = 1,
+}
+
+/// Error type for [`get_system_info`].
+#[derive(Debug, ResultCodeError)]
+pub enum GetSystemInfoError {
+    /// The info type is not known to the kernel —
+    /// `KernelError::InvalidEnumValue` (raw code `0xF001`).
+    #[rc(desc = InvalidEnumValue, msg = "Invalid info type")]
+    InvalidInfoType,
+    /// The info sub-ID (memory pool or range bound) is out of range —
+    /// `KernelError::InvalidCombination` (raw code `0xE801`).
+    #[rc(desc = InvalidCombination, msg = "Invalid info ID")]
+    InvalidInfoId,
+    /// Any unforeseen kernel error. Contains the original [`Error`] so callers
+    /// can inspect the raw result (`Error::to_raw`).
+    Unknown(Error),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn info_type_availability() {
+        let v = Version::new;
+
+        assert!(InfoType::CoreMask.is_available_on(v(1, 0, 0)));
+        assert!(!InfoType::ProgramId.is_available_on(v(2, 3, 0)));
+        assert!(InfoType::ProgramId.is_available_on(v(3, 0, 0)));
+        assert!(!InfoType::FreeThreadCount.is_available_on(v(10, 2, 0)));
+        assert!(InfoType::FreeThreadCount.is_available_on(v(11, 0, 0)));
+
+        // Removed info types
+        let range = InfoType::InitialProcessIdRange { which: 0 };
+        assert!(!range.is_available_on(v(3, 0, 2)));
+        assert!(range.is_available_on(v(4, 0, 0)));
+        assert!(range.is_available_on(v(4, 1, 0)));
+        assert!(!range.is_available_on(v(5, 0, 0)));
+        assert!(InfoType::ThreadTickCountDeprecated.is_available_on(v(12, 1, 0)));
+        assert!(!InfoType::ThreadTickCountDeprecated.is_available_on(v(13, 0, 0)));
+    }
+}
@@ -4,16 +4,48 @@
 //! system call with a Panic reason, allowing for better debugging and error
 //! reporting in homebrew applications.
 //!
-//! The panic handler formats messages using Rust's standard "panicked at" format
-//! and passes them to `svcBreak` via a 512-byte static buffer, following the same
-//! approach as libnx's `fatalThrow` and `diagAbortWithResult` functions.
+//! The panic handler formats messages using Rust's standard "panicked at" format,
+//! prefixed with the panicking thread's ID and handle, and passes them to `svcBreak`
+//! via a 2 KiB static buffer, following the same approach as libnx's `fatalThrow`
+//! and `diagAbortWithResult` functions. Messages that do not fit are truncated, and
+//! marked as such with a trailing `...`.
+//!
+//! The message is followed by a [`Backtrace`] of the panicking thread, listing the
+//! return addresses of the active calls along with their offsets into the loaded
+//! module.
+//!
+//! ## Panic hook
+//!
+//! Applications can register a [`PanicHook`] with [`set_panic_hook`] to run custom
+//! code (e.g. logging, flushing files or showing an error screen) before breaking.
+//!
+//! ## Nested panics
+//!
+//! Only the first panic runs the hook and formats its message. Any panic raised while
+//! it is being processed, either by the hook itself or concurrently by another thread,
+//! breaks immediately with a fixed message.
 
-use core::{fmt::Write as _, panic::PanicInfo};
+use core::{
+    fmt::Write as _,
+    panic::PanicInfo,
+    ptr,
+    sync::atomic::{AtomicBool, AtomicPtr, Ordering},
+};
 
-use crate::debug::{self, BreakReason};
+use crate::{
+    backtrace::Backtrace,
+    debug::{self, BreakReason},
+    thread::{self, Handle},
+};
 
-/// Maximum size for panic message buffer
-const PANIC_MSG_BUF_SIZE: usize = 512;
+/// Maximum size for panic message buffer, large enough to fit a full backtrace
+const PANIC_MSG_BUF_SIZE: usize = 2048;
+
+/// Marker appended to truncated panic messages
+const TRUNCATION_MARKER: &[u8] = b"...";
+
+/// Message passed to svcBreak on a nested panic
+const NESTED_PANIC_MSG: &str = "panicked while processing panic";
 
 /// Static buffer for storing panic messages
 ///
@@ -22,22 +54,67 @@ const PANIC_MSG_BUF_SIZE: usize = 512;
 /// remains valid for the duration of the break event.
 static mut PANIC_MSG_BUF: [u8; PANIC_MSG_BUF_SIZE] = [0; PANIC_MSG_BUF_SIZE];
 
+/// Set once a panic is being processed, see [Nested panics](self#nested-panics).
+static PANICKING: AtomicBool = AtomicBool::new(false);
+
+/// The registered panic hook, or null if none.
+static PANIC_HOOK: AtomicPtr<()> = AtomicPtr::new(ptr::null_mut());
+
+/// A panic hook, see [`set_panic_hook`].
+pub type PanicHook = fn(&PanicInfo<'_>);
+
+/// Registers a panic hook, returning the previous one.
+///
+/// The hook runs on the panicking thread, before the panic message is formatted and
+/// `svcBreak` is called. A panic raised by the hook breaks immediately.
+pub fn set_panic_hook(hook: PanicHook) -> Option<PanicHook> {
+    swap_panic_hook(hook as *mut ())
+}
+
+/// Unregisters the panic hook, returning it.
+pub fn take_panic_hook() -> Option<PanicHook> {
+    swap_panic_hook(ptr::null_mut())
+}
+
+fn swap_panic_hook(hook: *mut ()) -> Option<PanicHook> {
+    let prev = PANIC_HOOK.swap(hook, Ordering::AcqRel);
+    // SAFETY: Only `PanicHook` pointers (or null) are stored in `PANIC_HOOK`.
+    (!prev.is_null()).then(|| unsafe { core::mem::transmute::<*mut (), PanicHook>(prev) })
+}
+
 /// Custom panic handler that calls the Switch debug break system call.
 ///
 /// When a panic occurs, this handler will:
-/// 1. Format the panic message using Rust's standard "panicked at" format
-/// 2. Call `svcBreak` with `BreakReason::Panic`
-/// 3. Pass the formatted message buffer address and size to svcBreak
+/// 1. Break immediately if another panic is already being processed
+/// 2. Run the registered [`PanicHook`], if any
+/// 3. Format the panic message using Rust's standard "panicked at" format,
+///    prefixed with the panicking thread's ID and handle, and followed by
+///    a backtrace
+/// 4. Call `svcBreak` with `BreakReason::Panic`, passing the formatted
+///    message buffer address and size
 ///
 /// This follows the same approach as libnx's fatalThrow and diagAbortWithResult,
 /// and uses Rust's standard panic message format for consistency.
-#[panic_handler]
+#[cfg_attr(not(test), panic_handler)]
 pub fn panic_handler(info: &PanicInfo) -> ! {
-    // Format the panic message using Rust's standard Display implementation
-    // This gives us the standard "panicked at '<message>', <file>:<line>:<column>" format
+    if PANICKING.swap(true, Ordering::AcqRel) {
+        debug::break_event(
+            BreakReason::Panic,
+            NESTED_PANIC_MSG.as_ptr() as usize,
+            NESTED_PANIC_MSG.len(),
+        );
+    }
+
+    let hook = PANIC_HOOK.load(Ordering::Acquire);
+    if !hook.is_null() {
+        // SAFETY: Only `PanicHook` pointers are stored in `PANIC_HOOK`.
+        let hook = unsafe { core::mem::transmute::<*mut (), PanicHook>(hook) };
+        hook(info);
+    }
 
     // SAFETY: Taking a raw pointer to static mut and creating a slice from it is safe.
-    // The pointer is valid, properly aligned, and we have exclusive access during panic.
+    // The pointer is valid, properly aligned, and we have exclusive access: the
+    // `PANICKING` guard lets a single panic reach this point.
     let (buf_slice, buf_ptr) = unsafe {
         let raw_ptr = &raw mut PANIC_MSG_BUF;
         let slice = core::slice::from_raw_parts_mut(raw_ptr as *mut u8, PANIC_MSG_BUF_SIZE);
@@ -47,10 +124,26 @@ pub fn panic_handler(info: &PanicInfo) -> ! {
     // Create a cursor to write into the buffer
     let mut cursor = Cursor::new(buf_slice);
 
+    // Identify the panicking thread
+    let _ = match thread::get_id(Handle::current_thread()) {
+        Ok(id) => write!(cursor, "thread {}", id.get()),
+        Err(_) => write!(cursor, "thread <unknown>"),
+    };
+    let _ = match current_thread_handle() {
+        Some(handle) => write!(cursor, " (handle {handle:#010x}) "),
+        None => write!(cursor, " "),
+    };
+
     // Write the panic info using Rust's standard Display format
     // This automatically handles the "panicked at" formatting
     let _ = write!(cursor, "{}", info);
 
+    // Append the backtrace; its innermost frames are the panic handler and the
+    // `core::panicking` machinery, followed by the panic location
+    let backtrace = Backtrace::capture();
+    let _ = write!(cursor, "\nbacktrace:\n{backtrace}");
+    cursor.mark_truncation();
+
     let written = cursor.position();
     let (msg_ptr, msg_len) = (buf_ptr as usize, written);
 
@@ -60,6 +153,30 @@ pub fn panic_handler(info: &PanicInfo) -> ! {
     debug::break_event(BreakReason::Panic, msg_ptr, msg_len);
 }
 
+/// Magic value identifying libnx's `ThreadVars` structure (`"!TV$"`).
+const THREAD_VARS_MAGIC: u32 = 0x2154_5624;
+
+/// Offset of the `ThreadVars` structure in the TLS block.
+const THREAD_VARS_OFFSET: usize = 0x1E0;
+
+/// Returns the current thread's kernel handle, as stored by libnx in the `ThreadVars`
+/// structure at the end of the TLS block, or `None` if the structure is not set up.
+///
+/// `nx-svc` cannot depend on `nx-sys-thread` (which owns the TLS layout), so the
+/// tiny subset needed here is replicated, like `nx-sys-sync` does.
+fn current_thread_handle() -> Option<u32> {
+    let tls: *const u8;
+    // SAFETY: Reading `tpidrro_el0` has no side effects.
+    unsafe { core::arch::asm!("mrs {}, tpidrro_el0", out(reg) tls, options(nomem, nostack)) };
+
+    // SAFETY: The kernel maps a 0x200-byte TLS block at `tpidrro_el0` for every thread.
+    let (magic, handle) = unsafe {
+        let tv = tls.add(THREAD_VARS_OFFSET).cast::<u32>();
+        (ptr::read_volatile(tv), ptr::read_volatile(tv.add(1)))
+    };
+    (magic == THREAD_VARS_MAGIC).then_some(handle)
+}
+
 /// A cursor implementation for writing to a byte buffer in no_std environments.
 ///
 /// Wraps a mutable byte slice and tracks the current write position.
@@ -67,18 +184,31 @@ pub fn panic_handler(info: &PanicInfo) -> ! {
 struct Cursor<'a> {
     buf: &'a mut [u8],
     pos: usize,
+    truncated: bool,
 }
 
 impl<'a> Cursor<'a> {
     /// Creates a new cursor wrapping the provided buffer.
     fn new(buf: &'a mut [u8]) -> Self {
-        Self { buf, pos: 0 }
+        Self {
+            buf,
+            pos: 0,
+            truncated: false,
+        }
     }
 
     /// Returns the current write position in the buffer.
     fn position(&self) -> usize {
         self.pos
     }
+
+    /// Replaces the end of the buffer with [`TRUNCATION_MARKER`] if any write was truncated.
+    fn mark_truncation(&mut self) {
+        if self.truncated && self.pos >= TRUNCATION_MARKER.len() {
+            self.buf[self.pos - TRUNCATION_MARKER.len()..self.pos]
+                .copy_from_slice(TRUNCATION_MARKER);
+        }
+    }
 }
 
 impl<'a> core::fmt::Write for Cursor<'a> {
@@ -91,6 +221,9 @@ impl<'a> core::fmt::Write for Cursor<'a> {
             self.buf[self.pos..self.pos + to_write].copy_from_slice(&bytes[..to_write]);
             self.pos += to_write;
         }
+        if to_write < bytes.len() {
+            self.truncated = true;
+        }
 
         Ok(())
     }
@@ -0,0 +1,558 @@
+//! Process management for Horizon OS (Nintendo Switch)
+//!
+//! Safe wrappers around the kernel's process-related SVCs: enumerating processes and threads,
+//! querying a process's ID and state, pausing, resuming and terminating processes, and creating
+//! and starting new ones.
+//!
+//! Most of these SVCs are privileged: they require the corresponding kernel capability in the
+//! calling process's NPDM.
+//!
+//! ## References
+//! - [Switchbrew Wiki: SVC](https://switchbrew.org/wiki/SVC)
+
+use crate::{
+    debugger::DebugHandle,
+    error::ResultCodeError,
+    handle::OwnedHandle,
+    raw,
+    result::{Error, raw::Result as RawResult},
+    thread::{Priority, ThreadId},
+};
+
+define_waitable_handle_type! {
+    /// A handle to a process kernel object.
+    ///
+    /// It is signalled whenever the process changes state (see [`get_state`]).
+    pub struct Process
+}
+
+impl Process {
+    /// Creates a new [`Process`] for the current process.
+    pub fn current() -> Self {
+        Self(raw::CUR_PROCESS_HANDLE)
+    }
+
+    /// Returns `true` if the handle is the current process.
+    pub fn is_current(&self) -> bool {
+        self.0 == raw::CUR_PROCESS_HANDLE
+    }
+}
+
+/// A kernel process ID.
+///
+/// Process IDs are unique system-wide and are never reused while the system is running.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[repr(transparent)]
+pub struct ProcessId(pub u64);
+
+impl ProcessId {
+    /// Returns the raw process ID.
+    pub const fn get(self) -> u64 {
+        self.0
+    }
+}
+
+impl From<u64> for ProcessId {
+    fn from(id: u64) -> Self {
+        Self(id)
+    }
+}
+
+impl From<ProcessId> for u64 {
+    fn from(id: ProcessId) -> Self {
+        id.0
+    }
+}
+
+/// Maximum number of entries the kernel accepts in a process or thread list buffer.
+const MAX_LIST_ENTRIES: usize = 0x0FFF_FFFF;
+
+/// Lists the IDs of all processes running on the system.
+///
+/// This is a safe wrapper around [`raw::get_process_list`]. At most `buf.len()` IDs are written;
+/// returns the filled prefix of `buf`. If the returned slice is as long as `buf`, the list may
+/// have been truncated.
+pub fn get_process_list(buf: &mut [ProcessId]) -> Result<&[ProcessId], GetListError> {
+    let max = buf.len().min(MAX_LIST_ENTRIES);
+    let mut count = 0i32;
+    let rc = unsafe { raw::get_process_list(&mut count, buf.as_mut_ptr() as *mut u64, max as u32) };
+    RawResult::from_raw(rc).map((), |rc| GetListError::from(Error::from(rc)))?;
+
+    Ok(&buf[..(count as usize).min(max)])
+}
+
+/// The threads to list with [`get_thread_list`].
+#[derive(Debug, Clone, Copy)]
+pub enum ThreadListScope<'a> {
+    /// Every thread on the system.
+    System,
+    /// The threads of a process.
+    Process(&'a Process),
+    /// The threads of a process being debugged.
+    Debug(&'a DebugHandle),
+}
+
+impl ThreadListScope<'_> {
+    fn to_raw(self) -> raw::Handle {
+        match self {
+            Self::System => raw::INVALID_HANDLE,
+            Self::Process(process) => process.to_raw(),
+            Self::Debug(debug) => debug.to_raw(),
+        }
+    }
+}
+
+/// Lists the IDs of the threads in `scope`.
+///
+/// This is a safe wrapper around [`raw::get_thread_list`]. At most `buf.len()` IDs are written;
+/// returns the filled prefix of `buf`. If the returned slice is as long as `buf`, the list may
+/// have been truncated.
+pub fn get_thread_list<'b>(
+    scope: ThreadListScope<'_>,
+    buf: &'b mut [ThreadId],
+) -> Result<&'b [ThreadId], GetListError> {
+    let max = buf.len().min(MAX_LIST_ENTRIES);
+    let mut count = 0i32;
+    let rc = unsafe {
+        raw::get_thread_list(
+            &mut count,
+            buf.as_mut_ptr() as *mut u64,
+            max as u32,
+            scope.to_raw(),
+        )
+    };
+    RawResult::from_raw(rc).map((), |rc| GetListError::from(Error::from(rc)))?;
+
+    Ok(&buf[..(count as usize).min(max)])
+}
+
+/// Error type for [`get_process_list`] and [`get_thread_list`].
+#[derive(Debug, ResultCodeError)]
+pub enum GetListError {
+    /// The scope handle is neither a process nor a debug handle —
+    /// `KernelError::InvalidHandle` (raw code `0xE401`).
+    #[rc(desc = InvalidHandle)]
+    InvalidHandle,
+    /// The buffer size is out of the range accepted by the kernel —
+    /// `KernelError::OutOfRange` (raw code `0xEE01`).
+    #[rc(desc = OutOfRange)]
+    OutOfRange,
+    /// Any unforeseen kernel error. Contains the original [`Error`] so callers
+    /// can inspect the raw result (`Error::to_raw`).
+    Unknown(Error),
+}
+
+/// Gets the ID of a process.
+///
+/// This is a safe wrapper around [`raw::get_process_id`].
+pub fn get_id(process: &Process) -> Result<ProcessId, GetProcessIdError> {
+    let mut id = 0u64;
+    let rc = unsafe { raw::get_process_id(&mut id, process.to_raw()) };
+    RawResult::from_raw(rc).map(ProcessId(id), |rc| Error::from(rc).into())
+}
+
+/// Error type for [`get_id`].
+#[derive(Debug, ResultCodeError)]
+pub enum GetProcessIdError {
+    /// The supplied handle is not a valid process handle —
+    /// `KernelError::InvalidHandle` (raw code `0xE401`).
+    #[rc(desc = InvalidHandle)]
+    InvalidHandle,
+    /// Any unforeseen kernel error. Contains the original [`Error`] so callers
+    /// can inspect the raw result (`Error::to_raw`).
+    Unknown(Error),
+}
+
+/// Lifecycle state of a process, as reported by [`get_state`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProcessState {
+    /// Created but not started yet.
+    Created,
+    /// Created, not started yet, with a debugger attached.
+    CreatedAttached,
+    /// Running.
+    Started,
+    /// Stopped by an unhandled exception.
+    Crashed,
+    /// Running with a debugger attached.
+    StartedAttached,
+    /// Exiting.
+    Exiting,
+    /// Exited.
+    Exited,
+    /// Stopped by the attached debugger.
+    DebugSuspended,
+    /// A state unknown to this crate.
+    Unknown(i64),
+}
+
+impl From<i64> for ProcessState {
+    fn from(value: i64) -> Self {
+        match value {
+            0 => Self::Created,
+            1 => Self::CreatedAttached,
+            2 => Self::Started,
+            3 => Self::Crashed,
+            4 => Self::StartedAttached,
+            5 => Self::Exiting,
+            6 => Self::Exited,
+            7 => Self::DebugSuspended,
+            other => Self::Unknown(other),
+        }
+    }
+}
+
+/// Gets the lifecycle state of a process.
+///
+/// This is a safe wrapper around [`raw::get_process_info`] with
+/// [`ProcessInfoType::ProcessState`](raw::ProcessInfoType::ProcessState).
+pub fn get_state(process: &Process) -> Result<ProcessState, GetProcessInfoError> {
+    let mut state = 0i64;
+    let rc = unsafe {
+        raw::get_process_info(
+            &mut state,
+            process.to_raw(),
+            raw::ProcessInfoType::ProcessState,
+        )
+    };
+    RawResult::from_raw(rc).map(state.into(), |rc| Error::from(rc).into())
+}
+
+/// Error type for [`get_state`].
+#[derive(Debug, ResultCodeError)]
+pub enum GetProcessInfoError {
+    /// The supplied handle is not a valid process handle —
+    /// `KernelError::InvalidHandle` (raw code `0xE401`).
+    #[rc(desc = InvalidHandle)]
+    InvalidHandle,
+    /// Any unforeseen kernel error. Contains the original [`Error`] so callers
+    /// can inspect the raw result (`Error::to_raw`).
+    Unknown(Error),
+}
+
+/// Pauses all threads of a process.
+///
+/// Under the hood this invokes [`raw::set_process_activity`] with
+/// [`ProcessActivity::Paused`](raw::ProcessActivity::Paused).
+pub fn pause(process: &Process) -> Result<(), SetProcessActivityError> {
+    let rc = unsafe { raw::set_process_activity(process.to_raw(), raw::ProcessActivity::Paused) };
+    RawResult::from_raw(rc).map((), |rc| Error::from(rc).into())
+}
+
+/// Resumes a process paused with [`pause`].
+///
+/// Under the hood this invokes [`raw::set_process_activity`] with
+/// [`ProcessActivity::Runnable`](raw::ProcessActivity::Runnable).
+pub fn resume(process: &Process) -> Result<(), SetProcessActivityError> {
+    let rc = unsafe { raw::set_process_activity(process.to_raw(), raw::ProcessActivity::Runnable) };
+    RawResult::from_raw(rc).map((), |rc| Error::from(rc).into())
+}
+
+/// Error type for [`pause`] and [`resume`].
+#[derive(Debug, ResultCodeError)]
+pub enum SetProcessActivityError {
+    /// The supplied handle is not a valid process handle, or is the current process —
+    /// `KernelError::InvalidHandle` (raw code `0xE401`).
+    #[rc(desc = InvalidHandle)]
+    InvalidHandle,
+    /// The process is already paused (or already runnable), or is not running —
+    /// `KernelError::InvalidState` (raw code `0xFA01`).
+    #[rc(desc = InvalidState)]
+    InvalidState,
+    /// Any unforeseen kernel error. Contains the original [`Error`] so callers
+    /// can inspect the raw result (`Error::to_raw`).
+    Unknown(Error),
+}
+
+/// Terminates a process.
+///
+/// This is a safe wrapper around [`raw::terminate_process`]. The handle stays valid; wait on it
+/// until [`get_state`] reports [`ProcessState::Exited`], then close it.
+pub fn terminate(process: &Process) -> Result<(), TerminateProcessError> {
+    let rc = unsafe { raw::terminate_process(process.to_raw()) };
+    RawResult::from_raw(rc).map((), |rc| Error::from(rc).into())
+}
+
+/// Error type for [`terminate`].
+#[derive(Debug, ResultCodeError)]
+pub enum TerminateProcessError {
+    /// The supplied handle is not a valid process handle —
+    /// `KernelError::InvalidHandle` (raw code `0xE401`).
+    #[rc(desc = InvalidHandle)]
+    InvalidHandle,
+    /// The process is already exiting —
+    /// `KernelError::InvalidState` (raw code `0xFA01`).
+    #[rc(desc = InvalidState)]
+    InvalidState,
+    /// Any unforeseen kernel error. Contains the original [`Error`] so callers
+    /// can inspect the raw result (`Error::to_raw`).
+    Unknown(Error),
+}
+
+bitflags::bitflags! {
+    /// Process creation flags, see [`CreateProcessParameter::flags`].
+    ///
+    /// Besides the single-bit flags below, the flags word holds two multi-bit fields, the
+    /// [`AddressSpace`] (bits 1–3) and the [`PoolPartition`] (bits 7–10), accessed through
+    /// [`address_space`](Self::address_space) and [`pool_partition`](Self::pool_partition).
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+    #[repr(transparent)]
+    pub struct CreateProcessFlags: u32 {
+        /// The process runs in AArch64 mode.
+        const IS_64BIT = 1 << 0;
+        /// The process can be debugged.
+        const ENABLE_DEBUG = 1 << 4;
+        /// Randomize the address space layout.
+        const ENABLE_ASLR = 1 << 5;
+        /// The process is an application.
+        const IS_APPLICATION = 1 << 6;
+        /// Optimize memory allocation \[7.0.0+\]; only valid for applications.
+        const OPTIMIZE_MEMORY_ALLOCATION = 1 << 11;
+        /// Do not merge device address space mappings \[11.0.0+\].
+        const DISABLE_DEVICE_ADDRESS_SPACE_MERGE = 1 << 12;
+    }
+}
+
+impl CreateProcessFlags {
+    const ADDRESS_SPACE_SHIFT: u32 = 1;
+    const ADDRESS_SPACE_MASK: u32 = 0x7 << Self::ADDRESS_SPACE_SHIFT;
+    const POOL_PARTITION_SHIFT: u32 = 7;
+    const POOL_PARTITION_MASK: u32 = 0xF << Self::POOL_PARTITION_SHIFT;
+
+    /// Returns the address space type, or `None` if the field holds an unknown value.
+    pub fn address_space(&self) -> Option<AddressSpace> {
+        match (self.bits() & Self::ADDRESS_SPACE_MASK) >> Self::ADDRESS_SPACE_SHIFT {
+            0 => Some(AddressSpace::Bits32),
+            1 => Some(AddressSpace::Bits64Deprecated),
+            2 => Some(AddressSpace::Bits32WithoutAlias),
+            3 => Some(AddressSpace::Bits64),
+            _ => None,
+        }
+    }
+
+    /// Returns the flags with the address space field set to `address_space`.
+    pub fn with_address_space(self, address_space: AddressSpace) -> Self {
+        let field = (address_space as u32) << Self::ADDRESS_SPACE_SHIFT;
+        Self::from_bits_retain((self.bits() & !Self::ADDRESS_SPACE_MASK) | field)
+    }
+
+    /// Returns the memory pool partition, or `None` if the field holds an unknown value.
+    pub fn pool_partition(&self) -> Option<PoolPartition> {
+        match (self.bits() & Self::POOL_PARTITION_MASK) >> Self::POOL_PARTITION_SHIFT {
+            0 => Some(PoolPartition::Application),
+            1 => Some(PoolPartition::Applet),
+            2 => Some(PoolPartition::System),
+            3 => Some(PoolPartition::SystemNonSecure),
+            _ => None,
+        }
+    }
+
+    /// Returns the flags with the pool partition field set to `pool`.
+    pub fn with_pool_partition(self, pool: PoolPartition) -> Self {
+        let field = (pool as u32) << Self::POOL_PARTITION_SHIFT;
+        Self::from_bits_retain((self.bits() & !Self::POOL_PARTITION_MASK) | field)
+    }
+}
+
+/// Address space layout of a new process, see [`CreateProcessFlags::address_space`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u32)]
+pub enum AddressSpace {
+    /// 32-bit address space.
+    Bits32 = 0,
+    /// 36-bit address space, the \[1.0.0\] 64-bit layout.
+    Bits64Deprecated = 1,
+    /// 32-bit address space without the alias region.
+    Bits32WithoutAlias = 2,
+    /// 39-bit address space.
+    Bits64 = 3,
+}
+
+/// Memory pool partition a new process allocates from, see
+/// [`CreateProcessFlags::pool_partition`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u32)]
+pub enum PoolPartition {
+    /// Application pool.
+    Application = 0,
+    /// Applet pool.
+    Applet = 1,
+    /// System pool.
+    System = 2,
+    /// Non-secure system pool.
+    SystemNonSecure = 3,
+}
+
+/// Parameters of a new process, passed to [`create`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(C)]
+pub struct CreateProcessParameter {
+    /// Process name, NUL-padded.
+    pub name: [u8; 12],
+    /// Process category/version.
+    pub version: u32,
+    /// Program ID (title ID).
+    pub program_id: u64,
+    /// Address at which the code region is mapped.
+    pub code_address: u64,
+    /// Size of the code region, in pages.
+    pub code_num_pages: i32,
+    /// Creation flags.
+    pub flags: CreateProcessFlags,
+    /// Resource limit of the process, or [`raw::INVALID_HANDLE`] for the system default.
+    pub resource_limit: raw::Handle,
+    /// Size of the system resource (secure memory), in pages.
+    pub system_resource_num_pages: i32,
+}
+
+const _: () = assert!(size_of::<CreateProcessParameter>() == 0x30);
+
+/// Creates a new process in the [`ProcessState::Created`] state.
+///
+/// This is a safe wrapper around [`raw::create_process`]. `capabilities` holds the process's
+/// kernel capability descriptors, as found in the NPDM. The process's code must then be loaded
+/// into its address space before it is started with [`start`].
+pub fn create(
+    params: &CreateProcessParameter,
+    capabilities: &[u32],
+) -> Result<OwnedHandle<Process>, CreateProcessError> {
+    let mut handle = raw::INVALID_HANDLE;
+    let rc = unsafe {
+        raw::create_process(
+            &mut handle,
+            params as *const CreateProcessParameter as *const u8,
+            capabilities.as_ptr(),
+            capabilities.len() as u64,
+        )
+    };
+    RawResult::from_raw(rc).map_err(|rc| CreateProcessError::from(Error::from(rc)))?;
+
+    // SAFETY: On success, the kernel returned a new handle that is not owned elsewhere.
+    Ok(unsafe { OwnedHandle::new(Process(handle)) })
+}
+
+/// Error type for [`create`].
+#[derive(Debug, ResultCodeError)]
+pub enum CreateProcessError {
+    /// The code address is not page-aligned —
+    /// `KernelError::InvalidAddress` (raw code `0xCC01`).
+    #[rc(desc = InvalidAddress)]
+    InvalidAddress,
+    /// The code or system resource size is invalid —
+    /// `KernelError::InvalidSize` (raw code `0xCA01`).
+    #[rc(desc = InvalidSize)]
+    InvalidSize,
+    /// The creation flags contain an unknown address space or pool value —
+    /// `KernelError::InvalidEnumValue` (raw code `0xF001`).
+    #[rc(desc = InvalidEnumValue)]
+    InvalidFlags,
+    /// The resource limit handle is invalid —
+    /// `KernelError::InvalidHandle` (raw code `0xE401`).
+    #[rc(desc = InvalidHandle)]
+    InvalidHandle,
+    /// The code region does not fit the requested address space —
+    /// `KernelError::InvalidMemoryRegion` (raw code `0xDC01`).
+    #[rc(desc = InvalidMemoryRegion)]
+    InvalidMemoryRegion,
+    /// A kernel capability descriptor is malformed —
+    /// `KernelError::NotImplemented` (raw code `0x4201`).
+    #[rc(desc = NotImplemented)]
+    InvalidCapabilities,
+    /// Not enough memory to create the process —
+    /// `KernelError::OutOfMemory` (raw code `0xD001`).
+    #[rc(desc = OutOfMemory)]
+    OutOfMemory,
+    /// The kernel could not allocate the process object —
+    /// `KernelError::OutOfResource` (raw code `0xCE01`).
+    #[rc(desc = OutOfResource)]
+    OutOfResource,
+    /// The resource limit has been reached —
+    /// `KernelError::LimitReached` (raw code `0x10801`).
+    #[rc(desc = LimitReached)]
+    LimitReached,
+    /// The process handle table contains no free slots —
+    /// `KernelError::OutOfHandles` (raw code `0xD201`).
+    #[rc(desc = OutOfHandles, msg = "Handle table full")]
+    OutOfHandles,
+    /// Any unforeseen kernel error. Contains the original [`Error`] so callers
+    /// can inspect the raw result (`Error::to_raw`).
+    Unknown(Error),
+}
+
+/// Starts a process created with [`create`].
+///
+/// This is a safe wrapper around [`raw::start_process`]. The main thread is created with the
+/// given `priority` on core `core_id`, with a stack of `stack_size` bytes.
+pub fn start(
+    process: &Process,
+    priority: Priority,
+    core_id: u8,
+    stack_size: u32,
+) -> Result<(), StartProcessError> {
+    let rc = unsafe {
+        raw::start_process(
+            process.to_raw(),
+            priority.get() as i32,
+            core_id as i32,
+            stack_size,
+        )
+    };
+    RawResult::from_raw(rc).map((), |rc| Error::from(rc).into())
+}
+
+/// Error type for [`start`].
+#[derive(Debug, ResultCodeError)]
+pub enum StartProcessError {
+    /// The supplied handle is not a valid process handle —
+    /// `KernelError::InvalidHandle` (raw code `0xE401`).
+    #[rc(desc = InvalidHandle)]
+    InvalidHandle,
+    /// The priority is not allowed by the process's capabilities —
+    /// `KernelError::InvalidPriority` (raw code `0xE001`).
+    #[rc(desc = InvalidPriority)]
+    InvalidPriority,
+    /// The core is not allowed by the process's capabilities —
+    /// `KernelError::InvalidCoreId` (raw code `0xE201`).
+    #[rc(desc = InvalidCoreId)]
+    InvalidCoreId,
+    /// The process has already been started —
+    /// `KernelError::InvalidState` (raw code `0xFA01`).
+    #[rc(desc = InvalidState)]
+    InvalidState,
+    /// Not enough memory for the main thread's stack —
+    /// `KernelError::OutOfMemory` (raw code `0xD001`).
+    #[rc(desc = OutOfMemory)]
+    OutOfMemory,
+    /// The kernel could not allocate the main thread —
+    /// `KernelError::OutOfResource` (raw code `0xCE01`).
+    #[rc(desc = OutOfResource)]
+    OutOfResource,
+    /// The process's thread quota has been exhausted —
+    /// `KernelError::LimitReached` (raw code `0x10801`).
+    #[rc(desc = LimitReached)]
+    LimitReached,
+    /// Any unforeseen kernel error. Contains the original [`Error`] so callers
+    /// can inspect the raw result (`Error::to_raw`).
+    Unknown(Error),
+}
+
+/// Closes a process handle.
+///
+/// This is a safe wrapper around [`raw::close_handle`]. Closing the handle does not terminate
+/// the process.
+pub fn close_handle(process: Process) -> Result<(), CloseHandleError> {
+    let rc = unsafe { raw::close_handle(process.to_raw()) };
+    RawResult::from_raw(rc).map((), |rc| Error::from(rc).into())
+}
+
+/// Error type for [`close_handle`].
+#[derive(Debug, ResultCodeError)]
+pub enum CloseHandleError {
+    /// The supplied handle is not a valid process handle —
+    /// `KernelError::InvalidHandle` (raw code `0xE401`).
+    #[rc(desc = InvalidHandle)]
+    InvalidHandle,
+    /// Any unforeseen kernel error. Contains the original [`Error`] so callers
+    /// can inspect the raw result (`Error::to_raw`).
+    Unknown(Error),
+}
@@ -1223,6 +1223,46 @@ pub unsafe extern "C" fn send_sync_request_light(session: Handle) -> ResultCode
     );
 }
 
+/// Sends a light IPC synchronization request to a session, passing the message in registers.
+///
+/// Light IPC messages are seven 32-bit words carried in `w1`..`w7`. This variant loads the
+/// request from `args` before the call and stores the reply back into it afterwards, which
+/// [`send_sync_request_light`] leaves to the caller.
+///
+/// Syscall code: [SEND_SYNC_REQUEST_LIGHT](crate::code::SEND_SYNC_REQUEST_LIGHT) (`0x20`).
+///
+/// | Arg | Name | Description |
+/// | --- | --- | --- |
+/// | IN | _session_ | Light session handle. |
+/// | IN/OUT | _args_ | Pointer to the 7-word message; overwritten with the reply. |
+///
+/// # Safety
+///
+/// `args` must be valid for reads and writes of seven `u32` values.
+///
+/// Ref: <https://switchbrew.org/wiki/SVC#SendSyncRequestLight>
+#[unsafe(naked)]
+pub unsafe extern "C" fn send_sync_request_light_with_args(
+    session: Handle,
+    args: *mut u32,
+) -> ResultCode {
+    core::arch::naked_asm!(
+        "str x1, [sp, #-16]!",    // Store x1 (args pointer) on stack
+        "ldp w2, w3, [x1, #4]",   // Load message words 1 and 2
+        "ldp w4, w5, [x1, #12]",  // Load message words 3 and 4
+        "ldp w6, w7, [x1, #20]",  // Load message words 5 and 6
+        "ldr w1, [x1]",           // Load message word 0 (overwrites the pointer)
+        "svc {code}",             // Issue the SVC call with immediate value 0x20
+        "ldr x8, [sp], #16",      // Load args pointer from stack
+        "str w1, [x8]",           // Store reply word 0
+        "stp w2, w3, [x8, #4]",   // Store reply words 1 and 2
+        "stp w4, w5, [x8, #12]",  // Store reply words 3 and 4
+        "stp w6, w7, [x8, #20]",  // Store reply words 5 and 6
+        "ret",
+        code = const SEND_SYNC_REQUEST_LIGHT,
+    );
+}
+
 /// Sends an IPC synchronization request to a session.
 ///
 /// `Result svcSendSyncRequest(Handle session);`
@@ -2075,6 +2115,46 @@ pub unsafe extern "C" fn reply_and_receive_light(handle: Handle) -> ResultCode {
     );
 }
 
+/// Performs light IPC input/output, passing the message in registers.
+///
+/// Replies to the pending request (if any) with the seven words in `args`, then waits for the
+/// next request on the light session and stores it into `args`. See
+/// [`send_sync_request_light_with_args`] for the register layout.
+///
+/// Syscall code: [REPLY_AND_RECEIVE_LIGHT](crate::code::REPLY_AND_RECEIVE_LIGHT) (`0x42`).
+///
+/// | Arg | Name | Description |
+/// | --- | --- | --- |
+/// | IN | _handle_ | Light server session handle |
+/// | IN/OUT | _args_ | Pointer to the 7-word reply; overwritten with the next request. |
+///
+/// # Safety
+///
+/// `args` must be valid for reads and writes of seven `u32` values.
+///
+/// Ref: <https://switchbrew.org/wiki/SVC#ReplyAndReceiveLight>
+#[unsafe(naked)]
+pub unsafe extern "C" fn reply_and_receive_light_with_args(
+    handle: Handle,
+    args: *mut u32,
+) -> ResultCode {
+    core::arch::naked_asm!(
+        "str x1, [sp, #-16]!",    // Store x1 (args pointer) on stack
+        "ldp w2, w3, [x1, #4]",   // Load message words 1 and 2
+        "ldp w4, w5, [x1, #12]",  // Load message words 3 and 4
+        "ldp w6, w7, [x1, #20]",  // Load message words 5 and 6
+        "ldr w1, [x1]",           // Load message word 0 (overwrites the pointer)
+        "svc {code}",             // Issue the SVC call with immediate value 0x42
+        "ldr x8, [sp], #16",      // Load args pointer from stack
+        "str w1, [x8]",           // Store request word 0
+        "stp w2, w3, [x8, #4]",   // Store request words 1 and 2
+        "stp w4, w5, [x8, #12]",  // Store request words 3 and 4
+        "stp w6, w7, [x8, #20]",  // Store request words 5 and 6
+        "ret",
+        code = const REPLY_AND_RECEIVE_LIGHT,
+    );
+}
+
 /// Performs IPC input/output.
 ///
 /// If ReplyTargetSessionHandle is not zero, a reply from the TLS will be sent to that session. Then
@@ -0,0 +1,308 @@
+//! Resource limits for Horizon OS (Nintendo Switch)
+//!
+//! Every process is bound to a resource limit object that caps the amount of memory it can map
+//! and the number of threads, events, transfer memories and sessions it can create. This module
+//! wraps the resource-limit SVCs; [`snapshot`] reads the limit, current and peak values of every
+//! [`Resource`] in one call, which is handy for warning before a quota runs out:
+//!
+//! ```ignore
+//! let reslimit = ResourceLimit::current_process()?;
+//! let usage = resource_limit::snapshot(&reslimit)?;
+//! if usage.threads.remaining() < 2 {
+//!     // ...
+//! }
+//! ```
+//!
+//! ## References
+//! - [Switchbrew Wiki: SVC](https://switchbrew.org/wiki/SVC)
+
+use crate::{
+    error::ResultCodeError,
+    handle::OwnedHandle,
+    misc::{self, GetInfoError, InfoType},
+    raw,
+    result::{Error, raw::Result as RawResult},
+    version,
+};
+
+define_handle_type! {
+    /// A handle to a resource limit kernel object.
+    pub struct ResourceLimit
+}
+
+impl ResourceLimit {
+    /// Gets the resource limit of the current process.
+    ///
+    /// This queries [`InfoType::ResourceLimit`]. The kernel creates a new handle on every call,
+    /// which should be closed with [`close_handle`]. Processes without a resource limit get an
+    /// invalid handle (see [`is_valid`](Self::is_valid)).
+    pub fn current_process() -> Result<Self, GetInfoError> {
+        let handle = misc::get_info(InfoType::ResourceLimit, raw::CUR_PROCESS_HANDLE)?;
+        Ok(Self(handle as raw::Handle))
+    }
+}
+
+/// A resource capped by a [`ResourceLimit`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Resource {
+    /// Mapped memory, in bytes.
+    Memory,
+    /// Threads.
+    Threads,
+    /// Events.
+    Events,
+    /// Transfer memories.
+    TransferMemories,
+    /// IPC sessions.
+    Sessions,
+}
+
+impl Resource {
+    /// Every limitable resource, in kernel order.
+    pub const ALL: [Resource; 5] = [
+        Resource::Memory,
+        Resource::Threads,
+        Resource::Events,
+        Resource::TransferMemories,
+        Resource::Sessions,
+    ];
+
+    fn to_raw(self) -> raw::LimitableResource {
+        match self {
+            Self::Memory => raw::LimitableResource::Memory,
+            Self::Threads => raw::LimitableResource::Threads,
+            Self::Events => raw::LimitableResource::Events,
+            Self::TransferMemories => raw::LimitableResource::TransferMemories,
+            Self::Sessions => raw::LimitableResource::Sessions,
+        }
+    }
+}
+
+/// Gets the maximum value of a resource.
+///
+/// This is a safe wrapper around [`raw::get_resource_limit_limit_value`].
+pub fn get_limit_value(
+    reslimit: &ResourceLimit,
+    resource: Resource,
+) -> Result<i64, GetResourceLimitValueError> {
+    let mut value = 0i64;
+    let rc = unsafe {
+        raw::get_resource_limit_limit_value(&mut value, reslimit.to_raw(), resource.to_raw())
+    };
+    RawResult::from_raw(rc).map(value, |rc| Error::from(rc).into())
+}
+
+/// Gets the current value of a resource.
+///
+/// This is a safe wrapper around [`raw::get_resource_limit_current_value`].
+pub fn get_current_value(
+    reslimit: &ResourceLimit,
+    resource: Resource,
+) -> Result<i64, GetResourceLimitValueError> {
+    let mut value = 0i64;
+    let rc = unsafe {
+        raw::get_resource_limit_current_value(&mut value, reslimit.to_raw(), resource.to_raw())
+    };
+    RawResult::from_raw(rc).map(value, |rc| Error::from(rc).into())
+}
+
+/// Gets the highest value a resource has reached. \[11.0.0+\]
+///
+/// This is a safe wrapper around [`raw::get_resource_limit_peak_value`].
+pub fn get_peak_value(
+    reslimit: &ResourceLimit,
+    resource: Resource,
+) -> Result<i64, GetResourceLimitValueError> {
+    let mut value = 0i64;
+    let rc = unsafe {
+        raw::get_resource_limit_peak_value(&mut value, reslimit.to_raw(), resource.to_raw())
+    };
+    RawResult::from_raw(rc).map(value, |rc| Error::from(rc).into())
+}
+
+/// Error type for [`get_limit_value`], [`get_current_value`], [`get_peak_value`] and
+/// [`snapshot`].
+#[derive(Debug, ResultCodeError)]
+pub enum GetResourceLimitValueError {
+    /// The supplied handle is not a valid resource limit handle —
+    /// `KernelError::InvalidHandle` (raw code `0xE401`).
+    #[rc(desc = InvalidHandle)]
+    InvalidHandle,
+    /// The kernel does not know the resource —
+    /// `KernelError::InvalidEnumValue` (raw code `0xF001`).
+    #[rc(desc = InvalidEnumValue)]
+    InvalidResource,
+    /// Any unforeseen kernel error. Contains the original [`Error`] so callers
+    /// can inspect the raw result (`Error::to_raw`).
+    Unknown(Error),
+}
+
+/// Limit, current and peak values of a single resource.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ResourceUsage {
+    /// Maximum value.
+    pub limit: i64,
+    /// Current value.
+    pub current: i64,
+    /// Highest value reached so far, or `None` before 11.0.0, where it is not available.
+    pub peak: Option<i64>,
+}
+
+impl ResourceUsage {
+    /// Returns how much of the resource is still available.
+    pub const fn remaining(&self) -> i64 {
+        self.limit.saturating_sub(self.current)
+    }
+}
+
+/// Usage of every resource of a [`ResourceLimit`], as returned by [`snapshot`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ResourceLimitSnapshot {
+    /// Mapped memory, in bytes.
+    pub memory: ResourceUsage,
+    /// Threads.
+    pub threads: ResourceUsage,
+    /// Events.
+    pub events: ResourceUsage,
+    /// Transfer memories.
+    pub transfer_memories: ResourceUsage,
+    /// IPC sessions.
+    pub sessions: ResourceUsage,
+}
+
+impl ResourceLimitSnapshot {
+    /// Returns the usage of `resource`.
+    pub const fn get(&self, resource: Resource) -> &ResourceUsage {
+        match resource {
+            Resource::Memory => &self.memory,
+            Resource::Threads => &self.threads,
+            Resource::Events => &self.events,
+            Resource::TransferMemories => &self.transfer_memories,
+            Resource::Sessions => &self.sessions,
+        }
+    }
+
+    /// Returns an iterator over the usage of every resource, in [`Resource::ALL`] order.
+    pub fn iter(&self) -> impl Iterator<Item = (Resource, &ResourceUsage)> {
+        Resource::ALL.into_iter().map(|res| (res, self.get(res)))
+    }
+
+    fn get_mut(&mut self, resource: Resource) -> &mut ResourceUsage {
+        match resource {
+            Resource::Memory => &mut self.memory,
+            Resource::Threads => &mut self.threads,
+            Resource::Events => &mut self.events,
+            Resource::TransferMemories => &mut self.transfer_memories,
+            Resource::Sessions => &mut self.sessions,
+        }
+    }
+}
+
+/// Reads the limit, current and peak values of every resource.
+///
+/// Peak values are only read on 11.0.0+, as `svcGetResourceLimitPeakValue` does not exist on
+/// earlier firmware; there, [`ResourceUsage::peak`] is `None`.
+///
+/// The values are read one SVC at a time, so they are not an atomic snapshot: other threads may
+/// allocate or release resources in between.
+pub fn snapshot(
+    reslimit: &ResourceLimit,
+) -> Result<ResourceLimitSnapshot, GetResourceLimitValueError> {
+    let has_peak = version::is_at_least(11);
+
+    let mut snapshot = ResourceLimitSnapshot::default();
+    for resource in Resource::ALL {
+        *snapshot.get_mut(resource) = ResourceUsage {
+            limit: get_limit_value(reslimit, resource)?,
+            current: get_current_value(reslimit, resource)?,
+            peak: if has_peak {
+                Some(get_peak_value(reslimit, resource)?)
+            } else {
+                None
+            },
+        };
+    }
+    Ok(snapshot)
+}
+
+/// Creates a new resource limit, with every limit set to zero.
+///
+/// This is a safe wrapper around [`raw::create_resource_limit`].
+pub fn create() -> Result<OwnedHandle<ResourceLimit>, CreateResourceLimitError> {
+    let mut handle = raw::INVALID_HANDLE;
+    let rc = unsafe { raw::create_resource_limit(&mut handle) };
+    RawResult::from_raw(rc).map_err(|rc| CreateResourceLimitError::from(Error::from(rc)))?;
+
+    // SAFETY: On success, the kernel returned a new handle that is not owned elsewhere.
+    Ok(unsafe { OwnedHandle::new(ResourceLimit(handle)) })
+}
+
+/// Error type for [`create`].
+#[derive(Debug, ResultCodeError)]
+pub enum CreateResourceLimitError {
+    /// The kernel could not allocate the resource limit object —
+    /// `KernelError::OutOfResource` (raw code `0xCE01`).
+    #[rc(desc = OutOfResource)]
+    OutOfResource,
+    /// The process handle table contains no free slots —
+    /// `KernelError::OutOfHandles` (raw code `0xD201`).
+    #[rc(desc = OutOfHandles, msg = "Handle table full")]
+    OutOfHandles,
+    /// Any unforeseen kernel error. Contains the original [`Error`] so callers
+    /// can inspect the raw result (`Error::to_raw`).
+    Unknown(Error),
+}
+
+/// Sets the maximum value of a resource.
+///
+/// This is a safe wrapper around [`raw::set_resource_limit_limit_value`]. The new limit must not
+/// be lower than the resource's current value.
+pub fn set_limit_value(
+    reslimit: &ResourceLimit,
+    resource: Resource,
+    value: u64,
+) -> Result<(), SetResourceLimitValueError> {
+    let rc =
+        unsafe { raw::set_resource_limit_limit_value(reslimit.to_raw(), resource.to_raw(), value) };
+    RawResult::from_raw(rc).map((), |rc| Error::from(rc).into())
+}
+
+/// Error type for [`set_limit_value`].
+#[derive(Debug, ResultCodeError)]
+pub enum SetResourceLimitValueError {
+    /// The supplied handle is not a valid resource limit handle —
+    /// `KernelError::InvalidHandle` (raw code `0xE401`).
+    #[rc(desc = InvalidHandle)]
+    InvalidHandle,
+    /// The kernel does not know the resource —
+    /// `KernelError::InvalidEnumValue` (raw code `0xF001`).
+    #[rc(desc = InvalidEnumValue)]
+    InvalidResource,
+    /// The new limit is lower than the resource's current value —
+    /// `KernelError::InvalidState` (raw code `0xFA01`).
+    #[rc(desc = InvalidState, msg = "Limit below current value")]
+    BelowCurrentValue,
+    /// Any unforeseen kernel error. Contains the original [`Error`] so callers
+    /// can inspect the raw result (`Error::to_raw`).
+    Unknown(Error),
+}
+
+/// Closes a resource limit handle.
+///
+/// This is a safe wrapper around [`raw::close_handle`].
+pub fn close_handle(reslimit: ResourceLimit) -> Result<(), CloseHandleError> {
+    let rc = unsafe { raw::close_handle(reslimit.to_raw()) };
+    RawResult::from_raw(rc).map((), |rc| Error::from(rc).into())
+}
+
+/// Error type for [`close_handle`].
+#[derive(Debug, ResultCodeError)]
+pub enum CloseHandleError {
+    /// The supplied handle is not a valid resource limit handle —
+    /// `KernelError::InvalidHandle` (raw code `0xE401`).
+    #[rc(desc = InvalidHandle)]
+    InvalidHandle,
+    /// Any unforeseen kernel error. Contains the original [`Error`] so callers
+    /// can inspect the raw result (`Error::to_raw`).
+    Unknown(Error),
+}
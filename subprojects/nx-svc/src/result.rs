@@ -21,7 +21,7 @@
 //! - [Switchbrew Wiki: SVC](https://switchbrew.org/wiki/SVC)
 //! - [Switchbrew Wiki: Error Codes](https://switchbrew.org/wiki/Error_codes)
 
-use crate::error::{Module, ToRawResultCode};
+use crate::error::{Module, ToRawResultCode, description_name};
 
 /// Type alias for Result with [`Error`] as the error type.
 ///
@@ -58,11 +58,23 @@ pub type ResultCode = u32;
 ///
 /// # assert_eq!(format!("{}", err), "2001-0404");
 /// ```
+///
+/// Descriptions listed in the [catalogue](crate::error::description_name) are followed by the
+/// module and description names, e.g. `2002-0001 (FS: PathNotFound)`.
 #[derive(Copy, Clone, Eq, PartialEq)]
 #[repr(transparent)]
 pub struct Error(raw::ResultCode);
 
 impl Error {
+    /// Creates an [`Error`] from a raw result code
+    ///
+    /// `value` must be a failure code; passing the success code (`0`) panics in debug builds.
+    #[inline]
+    pub const fn from_raw(value: ResultCode) -> Self {
+        debug_assert!(value != 0, "the success result code is not an error");
+        Self(raw::ResultCode::from_raw(value))
+    }
+
     /// Returns the module that caused the error
     #[inline]
     pub const fn module(&self) -> Module {
@@ -80,6 +92,21 @@ impl Error {
     pub const fn to_raw(self) -> ResultCode {
         self.0.to_raw()
     }
+
+    /// Returns the name of the module that caused the error (e.g. `"FS"`), if it is a known one
+    pub const fn module_name(&self) -> Option<&'static str> {
+        match Module::from_raw(self.0.module_value()) {
+            Some(module) => Some(module.name()),
+            None => None,
+        }
+    }
+
+    /// Returns the symbolic name of the description (e.g. `"PathNotFound"`), if it is listed
+    /// in the [description catalogue](crate::error::description_name)
+    pub fn description_str(&self) -> Option<&'static str> {
+        let module = Module::from_raw(self.0.module_value())?;
+        description_name(module, self.0.description())
+    }
 }
 
 impl ToRawResultCode for Error {
@@ -90,22 +117,31 @@ impl ToRawResultCode for Error {
 }
 
 impl core::fmt::Display for Error {
-    /// Formats the error code as a `2XXX-YYYY` string.
+    /// Formats the error code as a `2XXX-YYYY` string, followed by the module and description
+    /// names when the description is listed in the catalogue.
     ///
     /// ```rust
     /// use nx_svc::rc::{Error, ErrorModule};
     ///
-    /// let err = Error::from_parts(ErrorModule::Kernel, 500);
+    /// let err = Error::from_parts(ErrorModule::FS, 1);
+    /// assert_eq!(format!("{}", err), "2002-0001 (FS: PathNotFound)");
     ///
+    /// let err = Error::from_parts(ErrorModule::Kernel, 500);
     /// assert_eq!(format!("{}", err), "2001-0500");
     /// ```
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         write!(
             f,
             "{:04}-{:04}",
-            2000 + self.0.module() as u32,
+            2000 + self.0.module_value(),
             self.0.description()
-        )
+        )?;
+
+        if let (Some(module), Some(desc)) = (self.module_name(), self.description_str()) {
+            write!(f, " ({module}: {desc})")?;
+        }
+
+        Ok(())
     }
 }
 
@@ -194,6 +230,12 @@ pub(crate) mod raw {
             self.0 == SUCCESS
         }
 
+        /// Returns the raw module number
+        #[inline]
+        pub const fn module_value(&self) -> u32 {
+            self.0 & MODULE_MASK
+        }
+
         /// Returns the module that caused the error
         #[inline]
         pub const fn module(&self) -> Module {
@@ -266,3 +308,41 @@ pub(crate) mod raw {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    extern crate alloc;
+
+    use alloc::format;
+
+    use super::*;
+
+    fn error(module: Module, description: u32) -> Error {
+        Error::from(raw::ResultCode::from_parts(module, description))
+    }
+
+    #[test]
+    fn names() {
+        let err = error(Module::FS, 1);
+        assert_eq!(err.module_name(), Some("FS"));
+        assert_eq!(err.description_str(), Some("PathNotFound"));
+
+        let err = error(Module::Kernel, 500);
+        assert_eq!(err.module_name(), Some("Kernel"));
+        assert_eq!(err.description_str(), None);
+    }
+
+    #[test]
+    fn display() {
+        assert_eq!(
+            format!("{}", error(Module::FS, 1)),
+            "2002-0001 (FS: PathNotFound)"
+        );
+        assert_eq!(
+            format!("{}", error(Module::Kernel, 114)),
+            "2001-0114 (Kernel: InvalidHandle)"
+        );
+        // Unlisted descriptions only show the code
+        assert_eq!(format!("{}", error(Module::Kernel, 500)), "2001-0500");
+    }
+}
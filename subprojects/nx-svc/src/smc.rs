@@ -0,0 +1,556 @@
+//! Secure monitor calls
+//!
+//! The secure monitor (TrustZone, EL3) exposes a set of user-facing functions to privileged
+//! processes through `svcCallSecureMonitor`. Arguments and results are exchanged through the
+//! `X0`–`X7` registers ([`SecmonArgs`]): `X0` holds the function ID on entry and the
+//! [`SmcError`] result on return.
+//!
+//! This module models the calls as typed requests ([`GetConfig`], [`GenerateRandomBytes`],
+//! [`GetResult`]) that pack their arguments and decode the response, and issues them with
+//! [`call`].
+//!
+//! ## References
+//! - [Switchbrew Wiki: SMC](https://switchbrew.org/wiki/SMC)
+//! - [Atmosphère: `secmon_smc_handler.cpp`](https://github.com/Atmosphere-NX/Atmosphere/blob/master/exosphere/program/source/smc/secmon_smc_handler.cpp)
+
+use crate::{
+    error::{Module, ResultCodeError},
+    raw::{self, SecmonArgs},
+    result::{Error, raw::ResultCode},
+};
+
+/// Issues a secure monitor call.
+///
+/// <div class="warning">
+/// This is a privileged syscall: the calling process must be permitted to use
+/// `svcCallSecureMonitor` (`0x7F`).
+/// </div>
+///
+/// This is a safe wrapper around [`raw::call_secure_monitor`].
+pub fn call<R: Request>(request: &R) -> Result<R::Output, SmcError> {
+    let mut args = request.to_args();
+    unsafe { raw::call_secure_monitor(&mut args) };
+    request.decode(&args)
+}
+
+/// A typed secure monitor call.
+///
+/// This trait is sealed: only the requests defined in this module can be issued with [`call`].
+pub trait Request: _priv::Sealed {
+    /// Decoded result of the call.
+    type Output;
+
+    /// Packs the request into the argument registers.
+    fn to_args(&self) -> SecmonArgs;
+
+    /// Decodes the result registers returned by the secure monitor.
+    fn decode(&self, args: &SecmonArgs) -> Result<Self::Output, SmcError>;
+}
+
+/// User-facing secure monitor function IDs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u32)]
+pub enum FunctionId {
+    /// Sets a configuration item.
+    SetConfig = 0xC300_0401,
+    /// Gets a configuration item, see [`GetConfig`].
+    GetConfig = 0xC300_0002,
+    /// Gets the result of an asynchronous operation, see [`GetResult`].
+    GetResult = 0xC300_0003,
+    /// Gets the result and output data of an asynchronous operation.
+    GetResultData = 0xC300_0404,
+    /// Starts an asynchronous modular exponentiation.
+    ModularExponentiate = 0xC300_0E05,
+    /// Generates random bytes, see [`GenerateRandomBytes`].
+    GenerateRandomBytes = 0xC300_0006,
+    /// Generates an AES key-encryption key.
+    GenerateAesKek = 0xC300_0007,
+    /// Loads an AES key into a keyslot.
+    LoadAesKey = 0xC300_0008,
+    /// Starts an asynchronous AES operation.
+    ComputeAes = 0xC300_0009,
+    /// Generates a specific AES key.
+    GenerateSpecificAesKey = 0xC300_000A,
+    /// Computes an AES-CMAC.
+    ComputeCmac = 0xC300_040B,
+}
+
+/// Gets a configuration item from the secure monitor.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GetConfig {
+    item: ConfigItem,
+}
+
+impl GetConfig {
+    /// Creates a request for the given configuration item.
+    pub const fn new(item: ConfigItem) -> Self {
+        Self { item }
+    }
+
+    /// Returns the requested configuration item.
+    pub const fn item(&self) -> ConfigItem {
+        self.item
+    }
+}
+
+impl Request for GetConfig {
+    type Output = ConfigValue;
+
+    fn to_args(&self) -> SecmonArgs {
+        pack(FunctionId::GetConfig, &[self.item as u64])
+    }
+
+    fn decode(&self, args: &SecmonArgs) -> Result<ConfigValue, SmcError> {
+        let [x0, x1, x2, x3, x4, ..] = args.x;
+        check(x0)?;
+        Ok(ConfigValue([x1, x2, x3, x4]))
+    }
+}
+
+impl _priv::Sealed for GetConfig {}
+
+/// Configuration items readable with [`GetConfig`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u32)]
+pub enum ConfigItem {
+    /// Whether program (NCA/ACID) signature verification is disabled.
+    DisableProgramVerification = 1,
+    /// DRAM chip identifier.
+    DramId = 2,
+    /// Interrupt number of the security engine.
+    SecurityEngineInterruptNumber = 3,
+    /// Fuse version.
+    FuseVersion = 4,
+    /// Hardware type, see [`ConfigValue::as_hardware_type`].
+    HardwareType = 5,
+    /// Hardware state (retail or development unit), see [`ConfigValue::as_hardware_state`].
+    HardwareState = 6,
+    /// Whether the console booted into recovery mode.
+    IsRecoveryBoot = 7,
+    /// Device ID.
+    DeviceId = 8,
+    /// Boot reason.
+    BootReason = 9,
+    /// Memory size and arrangement, see [`ConfigValue::as_memory_mode`].
+    MemoryMode = 10,
+    /// Whether development functions are enabled.
+    IsDevelopmentFunctionEnabled = 11,
+    /// Kernel configuration flags.
+    KernelConfiguration = 12,
+    /// Whether the charger Hi-Z mode is enabled.
+    IsChargerHiZModeEnabled = 13,
+    /// Retail interactive display (kiosk) state.
+    RetailInteractiveDisplayState = 14,
+    /// Power regulator type.
+    RegulatorType = 15,
+    /// Device unique key generation.
+    DeviceUniqueKeyGeneration = 16,
+    /// SHA-256 hash of the package2 (kernel and built-in system modules), see
+    /// [`ConfigValue::to_bytes`].
+    Package2Hash = 17,
+
+    /// Atmosphère extension: Exosphère API version.
+    ExosphereApiVersion = 65000,
+    /// Atmosphère extension: whether a reboot has been requested.
+    ExosphereNeedsReboot = 65001,
+    /// Atmosphère extension: whether a shutdown has been requested.
+    ExosphereNeedsShutdown = 65002,
+    /// Atmosphère extension: Git commit hash of the running Exosphère.
+    ExosphereGitCommitHash = 65003,
+    /// Atmosphère extension: whether the console has the RCM bug patched.
+    ExosphereHasRcmBugPatch = 65004,
+    /// Atmosphère extension: whether PRODINFO should be blanked.
+    ExosphereBlankProdInfo = 65005,
+    /// Atmosphère extension: whether writes to the calibration partition are allowed.
+    ExosphereAllowCalWrites = 65006,
+}
+
+/// Value of a configuration item, as returned in `X1`–`X4`.
+///
+/// Most items are a single integer (see [`as_u64`](Self::as_u64)); [`ConfigItem::Package2Hash`]
+/// spans all four registers (see [`to_bytes`](Self::to_bytes)).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ConfigValue([u64; 4]);
+
+impl ConfigValue {
+    /// Returns the raw `X1`–`X4` registers.
+    pub const fn raw(&self) -> [u64; 4] {
+        self.0
+    }
+
+    /// Returns the value as an integer.
+    pub const fn as_u64(&self) -> u64 {
+        self.0[0]
+    }
+
+    /// Returns the value as a boolean.
+    pub const fn as_bool(&self) -> bool {
+        self.0[0] != 0
+    }
+
+    /// Returns the value as little-endian bytes.
+    pub fn to_bytes(&self) -> [u8; 32] {
+        let mut bytes = [0; 32];
+        for (chunk, reg) in bytes.chunks_exact_mut(8).zip(self.0) {
+            chunk.copy_from_slice(&reg.to_le_bytes());
+        }
+        bytes
+    }
+
+    /// Decodes a [`ConfigItem::HardwareType`] value.
+    pub const fn as_hardware_type(&self) -> Option<HardwareType> {
+        Some(match self.as_u64() {
+            0 => HardwareType::Icosa,
+            1 => HardwareType::Copper,
+            2 => HardwareType::Hoag,
+            3 => HardwareType::Iowa,
+            4 => HardwareType::Calcio,
+            5 => HardwareType::Aula,
+            _ => return None,
+        })
+    }
+
+    /// Decodes a [`ConfigItem::HardwareState`] value.
+    pub const fn as_hardware_state(&self) -> Option<HardwareState> {
+        Some(match self.as_u64() {
+            0 => HardwareState::Development,
+            1 => HardwareState::Production,
+            _ => return None,
+        })
+    }
+
+    /// Decodes a [`ConfigItem::MemoryMode`] value.
+    ///
+    /// The memory mode packs the DRAM size in bits 4–7 and the memory arrangement in bits 0–3.
+    pub const fn as_memory_mode(&self) -> Option<MemoryMode> {
+        let raw = self.as_u64();
+        let size = match (raw >> 4) & 0xF {
+            0 => MemorySize::Size4GiB,
+            1 => MemorySize::Size6GiB,
+            2 => MemorySize::Size8GiB,
+            _ => return None,
+        };
+        let arrange = match raw & 0xF {
+            1 => MemoryArrange::Normal,
+            2 => MemoryArrange::AppletDev,
+            3 => MemoryArrange::SystemDev,
+            _ => return None,
+        };
+        Some(MemoryMode { size, arrange })
+    }
+}
+
+/// Console hardware type.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HardwareType {
+    /// Original Nintendo Switch (Erista).
+    Icosa,
+    /// Unreleased Erista revision.
+    Copper,
+    /// Nintendo Switch Lite.
+    Hoag,
+    /// Nintendo Switch (Mariko).
+    Iowa,
+    /// Unreleased Mariko revision.
+    Calcio,
+    /// Nintendo Switch OLED model.
+    Aula,
+}
+
+/// Console hardware state.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HardwareState {
+    /// Development unit.
+    Development,
+    /// Retail unit.
+    Production,
+}
+
+/// Memory configuration of the console, see [`ConfigValue::as_memory_mode`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MemoryMode {
+    /// Installed DRAM size.
+    pub size: MemorySize,
+    /// Arrangement of the memory pools.
+    pub arrange: MemoryArrange,
+}
+
+/// Installed DRAM size.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MemorySize {
+    /// 4 GiB (retail units).
+    Size4GiB,
+    /// 6 GiB.
+    Size6GiB,
+    /// 8 GiB.
+    Size8GiB,
+}
+
+/// Arrangement of the physical memory pools.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MemoryArrange {
+    /// Retail arrangement.
+    Normal,
+    /// Larger applet pool, for applet development.
+    AppletDev,
+    /// Larger system pool, for system development.
+    SystemDev,
+}
+
+/// Generates random bytes with the secure monitor's CSPRNG.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GenerateRandomBytes {
+    size: usize,
+}
+
+impl GenerateRandomBytes {
+    /// Maximum number of bytes generated per call (the size of `X1`–`X7`).
+    pub const MAX_SIZE: usize = 0x38;
+
+    /// Creates a request for `size` random bytes.
+    ///
+    /// Returns an error if `size` exceeds [`MAX_SIZE`](Self::MAX_SIZE).
+    pub const fn new(size: usize) -> Result<Self, InvalidRandomSizeError> {
+        if size > Self::MAX_SIZE {
+            return Err(InvalidRandomSizeError { size });
+        }
+        Ok(Self { size })
+    }
+}
+
+impl Request for GenerateRandomBytes {
+    type Output = RandomBytes;
+
+    fn to_args(&self) -> SecmonArgs {
+        pack(FunctionId::GenerateRandomBytes, &[self.size as u64])
+    }
+
+    fn decode(&self, args: &SecmonArgs) -> Result<RandomBytes, SmcError> {
+        let regs = args.x;
+        check(regs[0])?;
+
+        let mut bytes = [0; GenerateRandomBytes::MAX_SIZE];
+        for (chunk, reg) in bytes.chunks_exact_mut(8).zip(&regs[1..]) {
+            chunk.copy_from_slice(&reg.to_le_bytes());
+        }
+        Ok(RandomBytes {
+            bytes,
+            len: self.size,
+        })
+    }
+}
+
+impl _priv::Sealed for GenerateRandomBytes {}
+
+/// Random bytes returned by [`GenerateRandomBytes`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RandomBytes {
+    bytes: [u8; GenerateRandomBytes::MAX_SIZE],
+    len: usize,
+}
+
+impl RandomBytes {
+    /// Returns the generated bytes.
+    pub fn as_slice(&self) -> &[u8] {
+        &self.bytes[..self.len]
+    }
+}
+
+/// Error returned by [`GenerateRandomBytes::new`] when the size is too large.
+#[derive(Debug, thiserror::Error)]
+#[error(
+    "Invalid random bytes size: {size} (max {})",
+    GenerateRandomBytes::MAX_SIZE
+)]
+pub struct InvalidRandomSizeError {
+    /// The requested size.
+    pub size: usize,
+}
+
+/// Gets the result of an asynchronous secure monitor operation.
+///
+/// Asynchronous operations (e.g. [`FunctionId::ComputeAes`]) return a key identifying the
+/// operation, whose completion is signalled through the secure monitor's interrupt event.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GetResult {
+    key: u64,
+}
+
+impl GetResult {
+    /// Creates a request for the result of the operation identified by `key`.
+    pub const fn new(key: u64) -> Self {
+        Self { key }
+    }
+}
+
+impl Request for GetResult {
+    /// The result of the asynchronous operation. The outer result of [`call`] reports whether the
+    /// `GetResult` call itself succeeded.
+    type Output = Result<(), SmcError>;
+
+    fn to_args(&self) -> SecmonArgs {
+        pack(FunctionId::GetResult, &[self.key])
+    }
+
+    fn decode(&self, args: &SecmonArgs) -> Result<Result<(), SmcError>, SmcError> {
+        let [x0, x1, ..] = args.x;
+        check(x0)?;
+        Ok(check(x1))
+    }
+}
+
+impl _priv::Sealed for GetResult {}
+
+/// Result of a secure monitor call.
+///
+/// The secure monitor reports its own result values in `X0`, which Horizon's `spl` service maps
+/// onto the `SPL` module with the same description.
+#[derive(Debug, ResultCodeError)]
+#[rc(module = SPL)]
+pub enum SmcError {
+    /// The function is not implemented —
+    /// `SPL` `SecureMonitorNotImplemented` (raw code `0x021A`).
+    #[rc(desc = 1)]
+    NotImplemented,
+    /// An argument is invalid —
+    /// `SPL` `SecureMonitorInvalidArgument` (raw code `0x041A`).
+    #[rc(desc = 2)]
+    InvalidArgument,
+    /// The secure monitor is busy with another operation —
+    /// `SPL` `SecureMonitorBusy` (raw code `0x061A`).
+    #[rc(desc = 3)]
+    Busy,
+    /// No asynchronous operation is pending —
+    /// `SPL` `SecureMonitorNoAsyncOperation` (raw code `0x081A`).
+    #[rc(desc = 4)]
+    NoAsyncOperation,
+    /// The asynchronous operation key is invalid —
+    /// `SPL` `SecureMonitorInvalidAsyncOperation` (raw code `0x0A1A`).
+    #[rc(desc = 5)]
+    InvalidAsyncOperation,
+    /// The caller is not permitted to use the function —
+    /// `SPL` `SecureMonitorNotPermitted` (raw code `0x0C1A`).
+    #[rc(desc = 6)]
+    NotPermitted,
+    /// The secure monitor is not initialized —
+    /// `SPL` `SecureMonitorNotInitialized` (raw code `0x0E1A`).
+    #[rc(desc = 7)]
+    NotInitialized,
+    /// Any unforeseen secure monitor result. Contains the corresponding [`Error`] so callers
+    /// can inspect the raw result (`Error::to_raw`).
+    Unknown(Error),
+}
+
+/// Packs a function ID and its parameters into the argument registers.
+fn pack(function: FunctionId, params: &[u64]) -> SecmonArgs {
+    let mut x = [0; 8];
+    x[0] = function as u64;
+    x[1..=params.len()].copy_from_slice(params);
+    SecmonArgs { x }
+}
+
+/// Checks a secure monitor result value.
+fn check(result: u64) -> Result<(), SmcError> {
+    match result {
+        0 => Ok(()),
+        res => Err(Error::from(ResultCode::from_parts(Module::SPL, res as u32)).into()),
+    }
+}
+
+mod _priv {
+    /// Sealed trait to prevent external implementations.
+    pub trait Sealed {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::ToRawResultCode;
+
+    fn args(x: [u64; 8]) -> SecmonArgs {
+        SecmonArgs { x }
+    }
+
+    #[test]
+    fn get_config_packing() {
+        let regs = GetConfig::new(ConfigItem::HardwareType).to_args().x;
+        assert_eq!(regs, [0xC300_0002, 5, 0, 0, 0, 0, 0, 0]);
+
+        let regs = GetConfig::new(ConfigItem::ExosphereApiVersion).to_args().x;
+        assert_eq!(regs[..2], [0xC300_0002, 65000]);
+    }
+
+    #[test]
+    fn get_config_decoding() {
+        let req = GetConfig::new(ConfigItem::HardwareType);
+
+        let value = req.decode(&args([0, 4, 0, 0, 0, 0, 0, 0])).unwrap();
+        assert_eq!(value.as_u64(), 4);
+        assert_eq!(value.as_hardware_type(), Some(HardwareType::Calcio));
+        assert_eq!(value.as_hardware_state(), None);
+
+        let err = req.decode(&args([2, 0, 0, 0, 0, 0, 0, 0])).unwrap_err();
+        assert!(matches!(err, SmcError::InvalidArgument));
+    }
+
+    #[test]
+    fn config_value_decoding() {
+        let value = ConfigValue([0x21, 0, 0, 0]);
+        assert_eq!(
+            value.as_memory_mode(),
+            Some(MemoryMode {
+                size: MemorySize::Size8GiB,
+                arrange: MemoryArrange::Normal,
+            })
+        );
+        assert_eq!(ConfigValue([0x30, 0, 0, 0]).as_memory_mode(), None);
+        assert_eq!(
+            ConfigValue([1, 0, 0, 0]).as_hardware_state(),
+            Some(HardwareState::Production)
+        );
+
+        let hash = ConfigValue([0x0706_0504_0302_0100, 0, 0, 0xFF << 56]).to_bytes();
+        assert_eq!(hash[..8], [0, 1, 2, 3, 4, 5, 6, 7]);
+        assert_eq!(hash[31], 0xFF);
+    }
+
+    #[test]
+    fn generate_random_bytes() {
+        assert!(GenerateRandomBytes::new(GenerateRandomBytes::MAX_SIZE + 1).is_err());
+
+        let req = GenerateRandomBytes::new(10).unwrap();
+        let regs = req.to_args().x;
+        assert_eq!(regs, [0xC300_0006, 10, 0, 0, 0, 0, 0, 0]);
+
+        let regs = [0, 0x0807_0605_0403_0201, 0x0A09, 0, 0, 0, 0, 0xFF];
+        let bytes = req.decode(&args(regs)).unwrap();
+        assert_eq!(bytes.as_slice(), [1, 2, 3, 4, 5, 6, 7, 8, 9, 10]);
+    }
+
+    #[test]
+    fn get_result() {
+        let req = GetResult::new(0xDEAD_BEEF);
+        let regs = req.to_args().x;
+        assert_eq!(regs, [0xC300_0003, 0xDEAD_BEEF, 0, 0, 0, 0, 0, 0]);
+
+        assert!(matches!(req.decode(&args([0; 8])), Ok(Ok(()))));
+        assert!(matches!(
+            req.decode(&args([0, 3, 0, 0, 0, 0, 0, 0])),
+            Ok(Err(SmcError::Busy))
+        ));
+        assert!(matches!(
+            req.decode(&args([5, 0, 0, 0, 0, 0, 0, 0])),
+            Err(SmcError::InvalidAsyncOperation)
+        ));
+    }
+
+    #[test]
+    fn smc_error_codes() {
+        assert_eq!(SmcError::NotImplemented.to_rc(), 0x021A);
+        assert_eq!(SmcError::NotPermitted.to_rc(), 0x0C1A);
+
+        let err = check(42).unwrap_err();
+        assert!(matches!(err, SmcError::Unknown(_)));
+        assert_eq!(err.to_rc(), (42 << 9) | 26);
+    }
+}
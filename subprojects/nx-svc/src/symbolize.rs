@@ -0,0 +1,273 @@
+//! Loaded module enumeration and address symbolization
+//!
+//! Every module (the main NSO/NRO, and the ones loaded by `ro`) is mapped as a run of
+//! contiguous code regions: its executable text segment, followed by the read-only
+//! and read-write data segments. [`modules`] walks the process's ASLR region, where the
+//! loader and `ro` map every module, with [`query_memory`] to find them.
+//!
+//! An address is symbolized by locating its module, then looking up the nearest
+//! exported function in the module's dynamic symbol table, found through its MOD0
+//! header (see [`elf`]).
+//!
+//! ```ignore
+//! if let Some(location) = nx_svc::symbolize::resolve(addr) {
+//!     nx_svc::dbg_println!("{addr:#x}: {location}");
+//! }
+//! ```
+
+use core::{
+    fmt,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use crate::{
+    mem::core::{MemoryInfo, MemoryType, query_memory},
+    misc,
+};
+
+pub mod elf;
+
+use self::elf::{ParseError, SymbolTable};
+
+/// A module loaded in the current process.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Module {
+    base: usize,
+    size: usize,
+}
+
+impl Module {
+    /// Returns the module load address.
+    pub fn base(&self) -> usize {
+        self.base
+    }
+
+    /// Returns the size of the module's mapped segments, in bytes.
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Returns whether `addr` lies within the module.
+    pub fn contains(&self, addr: usize) -> bool {
+        addr >= self.base && addr - self.base < self.size
+    }
+
+    /// Returns the module's memory.
+    ///
+    /// # Safety
+    ///
+    /// The module must stay loaded while the returned slice is in use. The main module is
+    /// never unloaded, but modules loaded through `ro` may be.
+    pub unsafe fn image(&self) -> &[u8] {
+        // SAFETY: The module segments are contiguous and readable, as checked when
+        // locating the module, and the caller keeps them mapped.
+        unsafe { core::slice::from_raw_parts(self.base as *const u8, self.size) }
+    }
+
+    /// Parses the module's dynamic symbol table.
+    ///
+    /// # Safety
+    ///
+    /// The module must stay loaded while the returned table, or any [`Symbol`](elf::Symbol) read from
+    /// it, is in use. See [`image`](Self::image).
+    pub unsafe fn symbols(&self) -> Result<SymbolTable<'_>, ParseError> {
+        // SAFETY: The caller keeps the module loaded.
+        SymbolTable::from_image(unsafe { self.image() })
+    }
+
+    /// Locates the module whose text segment starts at `info`, by extending it over
+    /// the following readable code regions.
+    fn from_text_region(info: &MemoryInfo) -> Self {
+        let base = info.addr;
+        let mut end = info.addr + info.size;
+
+        while let Ok((next, _)) = query_memory(end) {
+            if is_module_start(&next) || !is_code(next.typ) || !next.perm.is_readable() {
+                break;
+            }
+            end = next.addr + next.size;
+        }
+
+        Self {
+            base,
+            size: end - base,
+        }
+    }
+}
+
+/// Iterator over the loaded modules, in address order, see [`modules`].
+#[derive(Debug, Clone)]
+pub struct Modules {
+    addr: Option<usize>,
+    end: usize,
+}
+
+impl Iterator for Modules {
+    type Item = Module;
+
+    fn next(&mut self) -> Option<Module> {
+        while let Some(addr) = self.addr.filter(|&addr| addr < self.end) {
+            let Ok((info, _)) = query_memory(addr) else {
+                self.addr = None;
+                break;
+            };
+
+            // The last region extends up to the end of the address space
+            let next = info.addr.checked_add(info.size).filter(|&next| next > addr);
+
+            if is_module_start(&info) {
+                let module = Module::from_text_region(&info);
+                self.addr = module.base.checked_add(module.size);
+                return Some(module);
+            }
+            self.addr = next;
+        }
+        None
+    }
+}
+
+/// Returns an iterator over the modules loaded in the current process.
+pub fn modules() -> Modules {
+    let (base, end) = module_region();
+    Modules {
+        addr: Some(base),
+        end,
+    }
+}
+
+/// Bounds of the region scanned by [`modules`], looked up on first use. An end of zero
+/// means not looked up yet.
+static MODULE_REGION_BASE: AtomicUsize = AtomicUsize::new(0);
+static MODULE_REGION_END: AtomicUsize = AtomicUsize::new(0);
+
+/// Returns the bounds of the ASLR region, falling back to the whole address space if it
+/// cannot be queried.
+fn module_region() -> (usize, usize) {
+    let end = MODULE_REGION_END.load(Ordering::Acquire);
+    if end != 0 {
+        return (MODULE_REGION_BASE.load(Ordering::Relaxed), end);
+    }
+
+    let (base, end) = match misc::get_aslr_region_info() {
+        Ok((base, size)) => (base, base.saturating_add(size)),
+        Err(_) => (0, usize::MAX),
+    };
+    // Racing initializations store the same values
+    MODULE_REGION_BASE.store(base, Ordering::Relaxed);
+    MODULE_REGION_END.store(end, Ordering::Release);
+    (base, end)
+}
+
+/// Returns the module containing `addr`.
+pub fn find_module(addr: usize) -> Option<Module> {
+    modules()
+        .take_while(|module| module.base <= addr)
+        .find(|module| module.contains(addr))
+}
+
+/// A symbolized address.
+#[derive(Debug, Clone, Copy)]
+pub struct Location {
+    /// The module containing the address.
+    pub module: Module,
+    /// Offset of the address from the module base.
+    pub offset: usize,
+    /// The name of the nearest exported function below the address, along with the
+    /// offset of the address into it, or `None` if the module has no usable symbol table.
+    pub symbol: Option<(SymbolName, usize)>,
+}
+
+impl fmt::Display for Location {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:#x} + {:#x}", self.module.base, self.offset)?;
+        if let Some((symbol, offset)) = self.symbol {
+            write!(f, " ({symbol} + {offset:#x})")?;
+        }
+        Ok(())
+    }
+}
+
+/// Resolves `addr` to its module, offset and nearest exported function.
+///
+/// Returns `None` if `addr` does not lie within a loaded module.
+pub fn resolve(addr: usize) -> Option<Location> {
+    let module = find_module(addr)?;
+    let offset = addr - module.base;
+    // SAFETY: The module was just found mapped, and the name is copied out of it before
+    // returning.
+    let symbol = unsafe { module.symbols() }
+        .ok()
+        .and_then(|symbols| symbols.nearest(offset))
+        .map(|(symbol, offset)| (SymbolName::new(symbol.name), offset));
+
+    Some(Location {
+        module,
+        offset,
+        symbol,
+    })
+}
+
+/// Maximum length of a [`SymbolName`], in bytes.
+pub const MAX_SYMBOL_NAME_LEN: usize = 128;
+
+/// A symbol name copied out of a module's string table, truncated to
+/// [`MAX_SYMBOL_NAME_LEN`] bytes.
+#[derive(Clone, Copy)]
+pub struct SymbolName {
+    buf: [u8; MAX_SYMBOL_NAME_LEN],
+    len: usize,
+}
+
+impl SymbolName {
+    /// Copies `name`, truncating it at a character boundary if it is too long.
+    fn new(name: &str) -> Self {
+        let mut len = name.len().min(MAX_SYMBOL_NAME_LEN);
+        while !name.is_char_boundary(len) {
+            len -= 1;
+        }
+
+        let mut buf = [0; MAX_SYMBOL_NAME_LEN];
+        buf[..len].copy_from_slice(&name.as_bytes()[..len]);
+        Self { buf, len }
+    }
+
+    /// Returns the name as a string slice.
+    pub fn as_str(&self) -> &str {
+        // SAFETY: The buffer holds a prefix of a `str`, cut at a character boundary.
+        unsafe { core::str::from_utf8_unchecked(&self.buf[..self.len]) }
+    }
+}
+
+impl fmt::Debug for SymbolName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self.as_str(), f)
+    }
+}
+
+impl fmt::Display for SymbolName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Returns whether a region belongs to a module's segments.
+fn is_code(typ: MemoryType) -> bool {
+    matches!(
+        typ,
+        MemoryType::CodeStatic
+            | MemoryType::CodeMutable
+            | MemoryType::ModuleCodeStatic
+            | MemoryType::ModuleCodeMutable
+    )
+}
+
+/// Returns whether a region is the text segment of a module.
+///
+/// `ModuleCodeStatic` is the state of the code mapped by `ro` (also known as
+/// `CodeDynamic`).
+fn is_module_start(info: &MemoryInfo) -> bool {
+    matches!(
+        info.typ,
+        MemoryType::CodeStatic | MemoryType::ModuleCodeStatic
+    ) && info.perm.is_read_execute()
+}
@@ -0,0 +1,584 @@
+//! Parsers for the MOD0 header and the ELF dynamic linking tables of a module
+//!
+//! Every parser works on a module _image_: a byte slice of the module's memory,
+//! starting at its base address. All reads are bounds-checked, and offsets read from
+//! the image wrap rather than overflow, so a malformed module yields a [`ParseError`]
+//! (or garbage) rather than a fault.
+//!
+//! Modules are position independent and linked at address 0, so the addresses found
+//! in the dynamic section and the symbol values are offsets into the image.
+//!
+//! ## References
+//! - [Switchbrew Wiki: NSO, MOD](https://switchbrew.org/wiki/NSO#MOD)
+//! - [System V ABI: Dynamic Section](https://refspecs.linuxfoundation.org/elf/gabi4+/ch5.dynamic.html)
+
+/// Magic of the MOD0 header (`"MOD0"`).
+pub const MOD0_MAGIC: u32 = u32::from_le_bytes(*b"MOD0");
+
+/// Offset of the MOD0 header offset field, right after the entry point branch.
+const MOD0_OFFSET_FIELD: usize = 4;
+
+/// Marks the end of the dynamic section.
+const DT_NULL: u64 = 0;
+/// Address of the SysV symbol hash table.
+const DT_HASH: u64 = 4;
+/// Address of the string table.
+const DT_STRTAB: u64 = 5;
+/// Address of the symbol table.
+const DT_SYMTAB: u64 = 6;
+/// Size of the string table, in bytes.
+const DT_STRSZ: u64 = 10;
+/// Size of a symbol table entry, in bytes.
+const DT_SYMENT: u64 = 11;
+/// Address of the GNU symbol hash table.
+const DT_GNU_HASH: u64 = 0x6fff_fef5;
+
+/// Size of an `Elf64_Dyn` entry.
+const DYN_ENTRY_SIZE: usize = 16;
+/// Size of an `Elf64_Sym` entry.
+const SYM_ENTRY_SIZE: usize = 24;
+
+/// Undefined section index, for symbols imported from other modules.
+const SHN_UNDEF: u16 = 0;
+/// Symbol type of functions.
+const STT_FUNC: u8 = 2;
+/// Symbol binding of global symbols.
+const STB_GLOBAL: u8 = 1;
+/// Symbol binding of weak symbols.
+const STB_WEAK: u8 = 2;
+
+/// The MOD0 header, locating the dynamic section and the other runtime structures
+/// of a module.
+///
+/// The offsets are relative to the module base.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Mod0Header {
+    /// Offset of the header itself.
+    pub offset: usize,
+    /// Offset of the dynamic section.
+    pub dynamic: usize,
+    /// Offset of the start of the BSS.
+    pub bss_start: usize,
+    /// Offset of the end of the BSS.
+    pub bss_end: usize,
+    /// Offset of the start of the `.eh_frame_hdr` section.
+    pub eh_frame_hdr_start: usize,
+    /// Offset of the end of the `.eh_frame_hdr` section.
+    pub eh_frame_hdr_end: usize,
+    /// Offset of the runtime module object.
+    pub module_object: usize,
+}
+
+impl Mod0Header {
+    /// Parses the MOD0 header, whose offset is stored right after the entry point
+    /// branch at the start of the image.
+    pub fn parse(image: &[u8]) -> Result<Self, ParseError> {
+        let offset = read_u32(image, MOD0_OFFSET_FIELD)? as usize;
+        if read_u32(image, offset)? != MOD0_MAGIC {
+            return Err(ParseError::InvalidMagic);
+        }
+
+        // Each field is an offset relative to the header
+        let field = |index: usize| -> Result<usize, ParseError> {
+            let rel = read_u32(image, offset + 4 * index)? as i32;
+            offset
+                .checked_add_signed(rel as isize)
+                .ok_or(ParseError::OutOfBounds)
+        };
+
+        Ok(Self {
+            offset,
+            dynamic: field(1)?,
+            bss_start: field(2)?,
+            bss_end: field(3)?,
+            eh_frame_hdr_start: field(4)?,
+            eh_frame_hdr_end: field(5)?,
+            module_object: field(6)?,
+        })
+    }
+}
+
+/// The symbol lookup information found in the dynamic section.
+///
+/// The offsets are relative to the module base.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Dynamic {
+    /// Offset of the symbol table (`.dynsym`).
+    pub symtab: usize,
+    /// Number of entries in the symbol table.
+    pub symbol_count: usize,
+    /// Offset of the string table (`.dynstr`).
+    pub strtab: usize,
+    /// Size of the string table, in bytes.
+    pub strtab_size: usize,
+}
+
+impl Dynamic {
+    /// Parses the dynamic section at `offset`.
+    ///
+    /// The ELF format does not record the size of the symbol table, so it is derived
+    /// from the SysV (`DT_HASH`) or GNU (`DT_GNU_HASH`) hash table.
+    pub fn parse(image: &[u8], offset: usize) -> Result<Self, ParseError> {
+        let mut symtab = None;
+        let mut strtab = None;
+        let mut strtab_size = None;
+        let mut hash = None;
+        let mut gnu_hash = None;
+
+        let mut entry = offset;
+        loop {
+            let tag = read_u64(image, entry)?;
+            let value = read_u64(image, entry + 8)? as usize;
+            match tag {
+                DT_NULL => break,
+                DT_HASH => hash = Some(value),
+                DT_STRTAB => strtab = Some(value),
+                DT_SYMTAB => symtab = Some(value),
+                DT_STRSZ => strtab_size = Some(value),
+                DT_SYMENT if value != SYM_ENTRY_SIZE => {
+                    return Err(ParseError::InvalidSymbolEntrySize(value));
+                }
+                DT_GNU_HASH => gnu_hash = Some(value),
+                _ => {}
+            }
+            entry += DYN_ENTRY_SIZE;
+        }
+
+        let symbol_count = match (hash, gnu_hash) {
+            // The SysV hash table has one chain entry per symbol
+            (Some(hash), _) => read_u32(image, hash.wrapping_add(4))? as usize,
+            (None, Some(gnu_hash)) => gnu_hash_symbol_count(image, gnu_hash)?,
+            (None, None) => return Err(ParseError::MissingEntry(DT_HASH)),
+        };
+
+        Ok(Self {
+            symtab: symtab.ok_or(ParseError::MissingEntry(DT_SYMTAB))?,
+            symbol_count,
+            strtab: strtab.ok_or(ParseError::MissingEntry(DT_STRTAB))?,
+            strtab_size: strtab_size.ok_or(ParseError::MissingEntry(DT_STRSZ))?,
+        })
+    }
+}
+
+/// Derives the number of symbols from a GNU hash table.
+///
+/// Symbols below `symoffset` are not hashed. The hashed ones are sorted by bucket,
+/// and each bucket's chain ends with an entry whose lowest bit is set, so the last
+/// symbol is at the end of the chain of the highest bucket.
+fn gnu_hash_symbol_count(image: &[u8], offset: usize) -> Result<usize, ParseError> {
+    let bucket_count = read_u32(image, offset)? as usize;
+    let symoffset = read_u32(image, offset.wrapping_add(4))? as usize;
+    let bloom_size = read_u32(image, offset.wrapping_add(8))? as usize;
+
+    let buckets = offset.wrapping_add(16 + bloom_size * 8);
+    let chains = buckets.wrapping_add(bucket_count * 4);
+
+    let mut last = 0;
+    for bucket in 0..bucket_count {
+        last = last.max(read_u32(image, buckets.wrapping_add(bucket * 4))? as usize);
+    }
+    if last < symoffset {
+        return Ok(symoffset);
+    }
+
+    loop {
+        let chain = read_u32(image, chains.wrapping_add((last - symoffset) * 4))?;
+        last += 1;
+        if chain & 1 != 0 {
+            return Ok(last);
+        }
+    }
+}
+
+/// An entry of the dynamic symbol table.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Symbol<'a> {
+    /// The symbol name.
+    pub name: &'a str,
+    /// Offset of the symbol from the module base.
+    pub value: usize,
+    /// Size of the symbol, in bytes, or 0 if unknown.
+    pub size: usize,
+    info: u8,
+    section: u16,
+}
+
+impl Symbol<'_> {
+    /// Returns whether the symbol is defined by the module (rather than imported).
+    pub fn is_defined(&self) -> bool {
+        self.section != SHN_UNDEF
+    }
+
+    /// Returns whether the symbol is a function.
+    pub fn is_function(&self) -> bool {
+        self.info & 0xf == STT_FUNC
+    }
+
+    /// Returns whether the symbol is defined and visible to other modules.
+    pub fn is_exported(&self) -> bool {
+        self.is_defined() && matches!(self.info >> 4, STB_GLOBAL | STB_WEAK)
+    }
+
+    /// Returns whether `offset` lies within the symbol, when its size is known.
+    pub fn contains(&self, offset: usize) -> bool {
+        offset >= self.value && offset - self.value < self.size
+    }
+}
+
+/// The dynamic symbol table of a module.
+#[derive(Debug, Clone, Copy)]
+pub struct SymbolTable<'a> {
+    image: &'a [u8],
+    dynamic: Dynamic,
+}
+
+impl<'a> SymbolTable<'a> {
+    /// Creates a symbol table from the parsed dynamic section.
+    pub fn new(image: &'a [u8], dynamic: Dynamic) -> Self {
+        Self { image, dynamic }
+    }
+
+    /// Locates the symbol table through the MOD0 header and the dynamic section.
+    pub fn from_image(image: &'a [u8]) -> Result<Self, ParseError> {
+        let header = Mod0Header::parse(image)?;
+        let dynamic = Dynamic::parse(image, header.dynamic)?;
+        Ok(Self::new(image, dynamic))
+    }
+
+    /// Returns the number of entries in the table, including the null entry.
+    pub fn len(&self) -> usize {
+        self.dynamic.symbol_count
+    }
+
+    /// Returns whether the table has no entries.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the symbol at `index`.
+    ///
+    /// Symbols whose name is not valid UTF-8 are given an empty name.
+    pub fn get(&self, index: usize) -> Result<Symbol<'a>, ParseError> {
+        if index >= self.len() {
+            return Err(ParseError::OutOfBounds);
+        }
+
+        let entry = self.dynamic.symtab.wrapping_add(index * SYM_ENTRY_SIZE);
+        let name = read_u32(self.image, entry)? as usize;
+        let info = read_u8(self.image, entry + 4)?;
+        let section = read_u16(self.image, entry + 6)?;
+        let value = read_u64(self.image, entry + 8)? as usize;
+        let size = read_u64(self.image, entry + 16)? as usize;
+
+        Ok(Symbol {
+            name: self.name(name)?,
+            value,
+            size,
+            info,
+            section,
+        })
+    }
+
+    /// Iterates over the symbols, stopping at the first malformed entry.
+    pub fn iter(&self) -> impl Iterator<Item = Symbol<'a>> + '_ {
+        (0..self.len()).map_while(|index| self.get(index).ok())
+    }
+
+    /// Finds the exported function closest below `offset`, returning it along with
+    /// the offset of `offset` into it.
+    ///
+    /// A symbol containing `offset` is preferred; otherwise, the nearest one is
+    /// returned, as internal functions do not appear in the dynamic symbol table.
+    pub fn nearest(&self, offset: usize) -> Option<(Symbol<'a>, usize)> {
+        let mut best: Option<Symbol<'a>> = None;
+        for symbol in self.iter() {
+            if !symbol.is_exported() || !symbol.is_function() || symbol.value > offset {
+                continue;
+            }
+
+            let better = match best {
+                None => true,
+                Some(best) if best.contains(offset) => {
+                    symbol.contains(offset) && symbol.value > best.value
+                }
+                Some(best) => symbol.contains(offset) || symbol.value > best.value,
+            };
+            if better {
+                best = Some(symbol);
+            }
+        }
+        best.map(|symbol| (symbol, offset - symbol.value))
+    }
+
+    /// Reads the NUL-terminated string at `offset` in the string table.
+    fn name(&self, offset: usize) -> Result<&'a str, ParseError> {
+        if offset >= self.dynamic.strtab_size {
+            return Err(ParseError::OutOfBounds);
+        }
+        let start = self.dynamic.strtab.wrapping_add(offset);
+        let end = self.dynamic.strtab.wrapping_add(self.dynamic.strtab_size);
+        let table = self.image.get(start..end).ok_or(ParseError::OutOfBounds)?;
+
+        let len = table
+            .iter()
+            .position(|&b| b == 0)
+            .ok_or(ParseError::OutOfBounds)?;
+        Ok(core::str::from_utf8(&table[..len]).unwrap_or(""))
+    }
+}
+
+/// Error type for the module parsers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+pub enum ParseError {
+    /// A structure extends past the end of the image.
+    #[error("Out of bounds read")]
+    OutOfBounds,
+    /// The MOD0 header magic does not match.
+    #[error("Invalid MOD0 magic")]
+    InvalidMagic,
+    /// A required dynamic section entry is missing.
+    #[error("Missing dynamic entry (tag {0:#x})")]
+    MissingEntry(u64),
+    /// The symbol table entries are not `Elf64_Sym`.
+    #[error("Invalid symbol entry size: {0}")]
+    InvalidSymbolEntrySize(usize),
+}
+
+fn read_bytes<const N: usize>(image: &[u8], offset: usize) -> Result<[u8; N], ParseError> {
+    offset
+        .checked_add(N)
+        .and_then(|end| image.get(offset..end))
+        .map(|bytes| bytes.try_into().unwrap())
+        .ok_or(ParseError::OutOfBounds)
+}
+
+fn read_u8(image: &[u8], offset: usize) -> Result<u8, ParseError> {
+    read_bytes::<1>(image, offset).map(|[b]| b)
+}
+
+fn read_u16(image: &[u8], offset: usize) -> Result<u16, ParseError> {
+    read_bytes(image, offset).map(u16::from_le_bytes)
+}
+
+fn read_u32(image: &[u8], offset: usize) -> Result<u32, ParseError> {
+    read_bytes(image, offset).map(u32::from_le_bytes)
+}
+
+fn read_u64(image: &[u8], offset: usize) -> Result<u64, ParseError> {
+    read_bytes(image, offset).map(u64::from_le_bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Sample image layout
+    const MOD0: usize = 0x10;
+    const DYNAMIC: usize = 0x40;
+    const HASH: usize = 0xC0;
+    const SYMTAB: usize = 0x100;
+    const STRTAB: usize = 0x200;
+    const TEXT: usize = 0x1000;
+
+    const STRINGS: &[u8] = b"\0_start\0main\0helper\0imported\0";
+
+    struct Image([u8; 0x300]);
+
+    impl Image {
+        /// Builds a module image with a MOD0 header, a dynamic section, a SysV
+        /// hash table and five symbols (including the null one).
+        fn sample() -> Self {
+            let mut image = Self([0; 0x300]);
+            image.put_u32(MOD0_OFFSET_FIELD, MOD0 as u32);
+            image.put_u32(MOD0, MOD0_MAGIC);
+            for (index, target) in [DYNAMIC, 0x280, 0x2C0, 0x2C0, 0x2C0, 0x2E0]
+                .into_iter()
+                .enumerate()
+            {
+                let rel = target as i32 - MOD0 as i32;
+                image.put_u32(MOD0 + 4 * (index + 1), rel as u32);
+            }
+
+            image.put_dynamic(&[
+                (DT_HASH, HASH as u64),
+                (DT_STRTAB, STRTAB as u64),
+                (DT_SYMTAB, SYMTAB as u64),
+                (DT_STRSZ, STRINGS.len() as u64),
+                (DT_SYMENT, SYM_ENTRY_SIZE as u64),
+            ]);
+
+            // nbucket, nchain
+            image.put_u32(HASH, 1);
+            image.put_u32(HASH + 4, 5);
+
+            image.0[STRTAB..STRTAB + STRINGS.len()].copy_from_slice(STRINGS);
+
+            let global_func = (STB_GLOBAL << 4) | STT_FUNC;
+            image.put_symbol(1, 1, global_func, 1, TEXT, 0x10);
+            image.put_symbol(2, 8, global_func, 1, TEXT + 0x100, 0x40);
+            image.put_symbol(3, 13, global_func, 1, TEXT + 0x80, 0);
+            image.put_symbol(4, 20, global_func, SHN_UNDEF, 0, 0);
+            image
+        }
+
+        fn put_u32(&mut self, offset: usize, value: u32) {
+            self.0[offset..offset + 4].copy_from_slice(&value.to_le_bytes());
+        }
+
+        fn put_u64(&mut self, offset: usize, value: u64) {
+            self.0[offset..offset + 8].copy_from_slice(&value.to_le_bytes());
+        }
+
+        fn put_dynamic(&mut self, entries: &[(u64, u64)]) {
+            let mut entry = DYNAMIC;
+            for &(tag, value) in entries.iter().chain([(DT_NULL, 0)].iter()) {
+                self.put_u64(entry, tag);
+                self.put_u64(entry + 8, value);
+                entry += DYN_ENTRY_SIZE;
+            }
+        }
+
+        fn put_symbol(
+            &mut self,
+            index: usize,
+            name: u32,
+            info: u8,
+            section: u16,
+            value: usize,
+            size: u64,
+        ) {
+            let entry = SYMTAB + index * SYM_ENTRY_SIZE;
+            self.put_u32(entry, name);
+            self.0[entry + 4] = info;
+            self.0[entry + 6..entry + 8].copy_from_slice(&section.to_le_bytes());
+            self.put_u64(entry + 8, value as u64);
+            self.put_u64(entry + 16, size);
+        }
+    }
+
+    #[test]
+    fn mod0_header() {
+        let image = Image::sample();
+        let header = Mod0Header::parse(&image.0).unwrap();
+
+        assert_eq!(header.offset, MOD0);
+        assert_eq!(header.dynamic, DYNAMIC);
+        assert_eq!(header.bss_start, 0x280);
+        assert_eq!(header.module_object, 0x2E0);
+    }
+
+    #[test]
+    fn mod0_header_errors() {
+        let mut image = Image::sample();
+        image.put_u32(MOD0, 0);
+        assert_eq!(Mod0Header::parse(&image.0), Err(ParseError::InvalidMagic));
+
+        image.put_u32(MOD0_OFFSET_FIELD, 0x1000);
+        assert_eq!(Mod0Header::parse(&image.0), Err(ParseError::OutOfBounds));
+
+        assert_eq!(Mod0Header::parse(&[0; 6]), Err(ParseError::OutOfBounds));
+    }
+
+    #[test]
+    fn dynamic_section() {
+        let image = Image::sample();
+        let dynamic = Dynamic::parse(&image.0, DYNAMIC).unwrap();
+
+        assert_eq!(
+            dynamic,
+            Dynamic {
+                symtab: SYMTAB,
+                symbol_count: 5,
+                strtab: STRTAB,
+                strtab_size: STRINGS.len(),
+            }
+        );
+    }
+
+    #[test]
+    fn dynamic_section_errors() {
+        let mut image = Image::sample();
+        image.put_dynamic(&[(DT_HASH, HASH as u64), (DT_SYMTAB, SYMTAB as u64)]);
+        assert_eq!(
+            Dynamic::parse(&image.0, DYNAMIC),
+            Err(ParseError::MissingEntry(DT_STRTAB))
+        );
+
+        image.put_dynamic(&[(DT_SYMENT, 16)]);
+        assert_eq!(
+            Dynamic::parse(&image.0, DYNAMIC),
+            Err(ParseError::InvalidSymbolEntrySize(16))
+        );
+
+        // Unterminated section
+        assert_eq!(
+            Dynamic::parse(&image.0[..DYNAMIC + 8], DYNAMIC),
+            Err(ParseError::OutOfBounds)
+        );
+    }
+
+    #[test]
+    fn gnu_hash_symbol_count() {
+        const GNU_HASH: usize = 0x280;
+
+        let mut image = Image::sample();
+        image.put_dynamic(&[
+            (DT_GNU_HASH, GNU_HASH as u64),
+            (DT_STRTAB, STRTAB as u64),
+            (DT_SYMTAB, SYMTAB as u64),
+            (DT_STRSZ, STRINGS.len() as u64),
+        ]);
+
+        // 2 buckets, symoffset 2, 1 bloom word
+        image.put_u32(GNU_HASH, 2);
+        image.put_u32(GNU_HASH + 4, 2);
+        image.put_u32(GNU_HASH + 8, 1);
+        // Buckets start at symbols 2 and 3; chains for symbols 2..=4
+        let buckets = GNU_HASH + 16 + 8;
+        image.put_u32(buckets, 2);
+        image.put_u32(buckets + 4, 3);
+        image.put_u32(buckets + 8, 0x11);
+        image.put_u32(buckets + 12, 0x20);
+        image.put_u32(buckets + 16, 0x31);
+
+        let dynamic = Dynamic::parse(&image.0, DYNAMIC).unwrap();
+        assert_eq!(dynamic.symbol_count, 5);
+    }
+
+    #[test]
+    fn symbol_table() {
+        let image = Image::sample();
+        let symbols = SymbolTable::from_image(&image.0).unwrap();
+
+        assert_eq!(symbols.len(), 5);
+        let names = [
+            symbols.get(0).unwrap().name,
+            symbols.get(2).unwrap().name,
+            symbols.get(4).unwrap().name,
+        ];
+        assert_eq!(names, ["", "main", "imported"]);
+        assert_eq!(symbols.iter().count(), 5);
+
+        let imported = symbols.get(4).unwrap();
+        assert!(!imported.is_defined());
+        assert!(!imported.is_exported());
+        assert_eq!(symbols.get(5), Err(ParseError::OutOfBounds));
+    }
+
+    #[test]
+    fn nearest_symbol() {
+        let image = Image::sample();
+        let symbols = SymbolTable::from_image(&image.0).unwrap();
+        let nearest = |offset| symbols.nearest(offset).map(|(sym, off)| (sym.name, off));
+
+        // Within a sized symbol
+        assert_eq!(nearest(TEXT + 0x8), Some(("_start", 0x8)));
+        assert_eq!(nearest(TEXT + 0x120), Some(("main", 0x20)));
+        // Past the end of `_start`: the closest symbol below is the unsized one
+        assert_eq!(nearest(TEXT + 0x90), Some(("helper", 0x10)));
+        // Past the end of `main`
+        assert_eq!(nearest(TEXT + 0x200), Some(("main", 0x100)));
+        // Before any symbol
+        assert_eq!(nearest(TEXT - 1), None);
+    }
+}
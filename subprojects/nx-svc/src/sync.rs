@@ -1,10 +1,13 @@
 //! Synchronization primitives
 
+pub mod address_arbiter;
+
 use crate::{
-    error::{KernelError as KError, ResultCode, ToRawResultCode},
+    error::{KernelError as KError, ResultCodeError},
     handle::Waitable,
     raw::{self, Handle},
     result::{Error, Result, raw::Result as RawResult},
+    thread,
 };
 
 /// Bitmask for the _waiters bitflag_ in mutex raw tag values.
@@ -198,44 +201,27 @@ pub unsafe fn wait_process_wide_key_atomic(
     timeout_ns: u64,
 ) -> Result<(), WaitProcessWideKeyError> {
     let res = unsafe { raw::wait_process_wide_key_atomic(mutex, condvar, tag, timeout_ns) };
-    RawResult::from_raw(res).map((), |rc| match rc.description() {
-        desc if KError::InvalidAddress == desc => WaitProcessWideKeyError::InvalidMemState,
-        desc if KError::TerminationRequested == desc => WaitProcessWideKeyError::ThreadTerminating,
-        desc if KError::TimedOut == desc => WaitProcessWideKeyError::TimedOut,
-        _ => WaitProcessWideKeyError::Unknown(Error::from(rc)),
-    })
+    RawResult::from_raw(res).map((), |rc| Error::from(rc).into())
 }
 
 /// Error type for [`wait_process_wide_key_atomic`]
-#[derive(Debug, thiserror::Error)]
+#[derive(Debug, ResultCodeError)]
 pub enum WaitProcessWideKeyError {
     /// The mutex or condvar memory address cannot be accessed.
-    #[error("Invalid memory state")]
+    #[rc(desc = InvalidAddress, msg = "Invalid memory state")]
     InvalidMemState,
     /// The current thread is marked for termination.
-    #[error("Thread terminating")]
+    #[rc(desc = TerminationRequested)]
     ThreadTerminating,
     /// The wait operation timed out.
-    #[error("Operation timed out")]
+    #[rc(desc = TimedOut, msg = "Operation timed out")]
     TimedOut,
     /// An unknown error occurred.
     ///
     /// This variant is used when the error code is not recognized.
-    #[error("Unknown error: {0}")]
     Unknown(Error),
 }
 
-impl ToRawResultCode for WaitProcessWideKeyError {
-    fn to_rc(self) -> ResultCode {
-        match self {
-            WaitProcessWideKeyError::InvalidMemState => KError::InvalidAddress.to_rc(),
-            WaitProcessWideKeyError::ThreadTerminating => KError::TerminationRequested.to_rc(),
-            WaitProcessWideKeyError::TimedOut => KError::TimedOut.to_rc(),
-            WaitProcessWideKeyError::Unknown(err) => err.to_raw(),
-        }
-    }
-}
-
 /// Signals a condition variable to wake waiting threads
 ///
 /// Wakes up one or more threads waiting on the specified condition variable.
@@ -382,7 +368,7 @@ where
 /// 3. Otherwise, block the current thread until either:
 ///    - One of the objects becomes signalled → success, returning its index.
 ///    - The timeout expires              → [`WaitSyncError::TimedOut`].
-///    - The wait gets cancelled via [`__nx_svc_cancel_synchronization`] → [`WaitSyncError::Cancelled`].
+///    - The wait gets cancelled via [`cancel_synchronization`] → [`WaitSyncError::Cancelled`].
 ///
 /// # Notes
 /// - Passing an empty slice results in a sleep until `timeout_ns` elapses (or indefinitely when
@@ -414,13 +400,7 @@ unsafe fn wait_synchronization(handles: &[Handle], timeout: u64) -> Result<usize
         raw::wait_synchronization(&mut idx, handles.as_ptr(), handles.len() as i32, timeout)
     };
 
-    RawResult::from_raw(rc).map(idx as usize, |rc| match rc.description() {
-        desc if KError::InvalidHandle == desc => WaitSyncError::InvalidHandle,
-        desc if KError::TimedOut == desc => WaitSyncError::TimedOut,
-        desc if KError::Cancelled == desc => WaitSyncError::Cancelled,
-        desc if KError::OutOfRange == desc => WaitSyncError::OutOfRange,
-        _ => WaitSyncError::Unknown(Error::from(rc)),
-    })
+    RawResult::from_raw(rc).map(idx as usize, |rc| Error::from(rc).into())
 }
 
 /// Error type returned by [`wait_synchronization_multiple`] and
@@ -439,33 +419,47 @@ unsafe fn wait_synchronization(handles: &[Handle], timeout: u64) -> Result<usize
 ///
 /// The `Unknown` catch-all is kept for forward-compatibility in case Nintendo extends the
 /// interface with additional error codes.
-#[derive(Debug, thiserror::Error)]
+#[derive(Debug, ResultCodeError)]
 pub enum WaitSyncError {
     /// One (or more) of the supplied handles is invalid.
-    #[error("Invalid handle")]
+    #[rc(desc = InvalidHandle)]
     InvalidHandle,
     /// The wait operation timed out.
-    #[error("Operation timed out")]
+    #[rc(desc = TimedOut, msg = "Operation timed out")]
     TimedOut,
-    /// The wait was cancelled via [`a__nx_svc_cancel_synchronization`].
-    #[error("Wait cancelled")]
+    /// The wait was cancelled by another thread via [`cancel_synchronization`].
+    ///
+    /// None of the supplied handles has necessarily been signalled. Callers that use cancellation
+    /// as an interruption mechanism should check their own shutdown condition before retrying.
+    #[rc(desc = Cancelled, msg = "Wait cancelled")]
     Cancelled,
     /// The number of handles supplied is out of range (must be ≤ 0x40).
-    #[error("Out of range")]
+    #[rc(desc = OutOfRange)]
     OutOfRange,
     /// An unknown error occurred.
-    #[error("Unknown error: {0}")]
     Unknown(Error),
 }
 
-impl ToRawResultCode for WaitSyncError {
-    fn to_rc(self) -> ResultCode {
-        match self {
-            WaitSyncError::InvalidHandle => KError::InvalidHandle.to_rc(),
-            WaitSyncError::TimedOut => KError::TimedOut.to_rc(),
-            WaitSyncError::Cancelled => KError::Cancelled.to_rc(),
-            WaitSyncError::OutOfRange => KError::OutOfRange.to_rc(),
-            WaitSyncError::Unknown(err) => err.to_raw(),
-        }
-    }
+/// Cancels the ongoing (or next) synchronization wait of a thread.
+///
+/// If `handle`'s thread is blocked in `svcWaitSynchronization`, the wait returns early with
+/// [`WaitSyncError::Cancelled`]. If the thread is not currently waiting, the cancellation is
+/// latched by the kernel and its *next* wait returns [`WaitSyncError::Cancelled`] immediately.
+///
+/// This is a safe wrapper around [`raw::cancel_synchronization`].
+pub fn cancel_synchronization(handle: thread::Handle) -> Result<(), CancelSyncError> {
+    let rc = unsafe { raw::cancel_synchronization(handle.to_raw()) };
+    RawResult::from_raw(rc).map((), |rc| Error::from(rc).into())
+}
+
+/// Error type for [`cancel_synchronization`].
+#[derive(Debug, ResultCodeError)]
+pub enum CancelSyncError {
+    /// The supplied handle is not a valid thread handle —
+    /// `KernelError::InvalidHandle` (raw code `0xE401`).
+    #[rc(desc = InvalidHandle)]
+    InvalidHandle,
+    /// Any unforeseen kernel error. Contains the original [`Error`] so callers
+    /// can inspect the raw result (`Error::to_raw`).
+    Unknown(Error),
 }
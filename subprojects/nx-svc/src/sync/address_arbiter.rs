@@ -0,0 +1,186 @@
+//! Address arbiter
+//!
+//! Safe wrappers around `svcWaitForAddress` and `svcSignalToAddress` \[4.0.0+\], the kernel's
+//! futex-like primitives. A thread can block on a 32-bit word in user memory until another thread
+//! signals that same address, optionally with the kernel atomically checking (and updating) the
+//! word's value.
+//!
+//! The word is passed as an [`AtomicI32`], which guarantees the 4-byte alignment the kernel
+//! requires and that the memory stays valid for the duration of the call. The kernel accesses it
+//! with exclusive load/store instructions, so it is safe to use concurrently from user space.
+//!
+//! ## References
+//! - [Switchbrew Wiki: SVC](https://switchbrew.org/wiki/SVC#WaitForAddress)
+
+use core::{sync::atomic::AtomicI32, time::Duration};
+
+use crate::{
+    error::ResultCodeError,
+    raw::{self, ArbitrationType, SignalType},
+    result::{Error, raw::Result as RawResult},
+};
+
+/// Pass as the `count` of a signal operation to wake up every waiting thread.
+pub const SIGNAL_ALL: i32 = -1;
+
+/// Waits on `addr` if its value is less than `value`.
+///
+/// Returns once the address is signalled, or `timeout` elapses (`None` waits forever).
+///
+/// If the value is not less than `value` the call returns immediately with
+/// [`WaitForAddressError::ValueMismatch`].
+pub fn wait_if_less_than(
+    addr: &AtomicI32,
+    value: i32,
+    timeout: Option<Duration>,
+) -> Result<(), WaitForAddressError> {
+    wait_for_address(addr, ArbitrationType::WaitIfLessThan, value, timeout)
+}
+
+/// Atomically decrements the value at `addr` and waits on it if it was less than `value`.
+///
+/// The value is only decremented if the comparison succeeds. Returns once the address is
+/// signalled, or `timeout` elapses (`None` waits forever).
+///
+/// If the value is not less than `value` the call returns immediately with
+/// [`WaitForAddressError::ValueMismatch`].
+pub fn decrement_and_wait_if_less_than(
+    addr: &AtomicI32,
+    value: i32,
+    timeout: Option<Duration>,
+) -> Result<(), WaitForAddressError> {
+    wait_for_address(
+        addr,
+        ArbitrationType::DecrementAndWaitIfLessThan,
+        value,
+        timeout,
+    )
+}
+
+/// Waits on `addr` if its value is equal to `value`.
+///
+/// Returns once the address is signalled, or `timeout` elapses (`None` waits forever).
+///
+/// If the value differs from `value` the call returns immediately with
+/// [`WaitForAddressError::ValueMismatch`].
+pub fn wait_if_equal(
+    addr: &AtomicI32,
+    value: i32,
+    timeout: Option<Duration>,
+) -> Result<(), WaitForAddressError> {
+    wait_for_address(addr, ArbitrationType::WaitIfEqual, value, timeout)
+}
+
+/// Wakes up to `count` threads waiting on `addr` ([`SIGNAL_ALL`] wakes them all).
+pub fn signal(addr: &AtomicI32, count: i32) -> Result<(), SignalToAddressError> {
+    signal_to_address(addr, SignalType::Signal, 0, count)
+}
+
+/// If the value at `addr` equals `value`, atomically increments it and wakes up to `count`
+/// threads waiting on it ([`SIGNAL_ALL`] wakes them all).
+///
+/// If the value differs from `value`, nothing is modified nor woken up and
+/// [`SignalToAddressError::ValueMismatch`] is returned.
+pub fn signal_and_increment_if_equal(
+    addr: &AtomicI32,
+    value: i32,
+    count: i32,
+) -> Result<(), SignalToAddressError> {
+    signal_to_address(addr, SignalType::SignalAndIncrementIfEqual, value, count)
+}
+
+/// If the value at `addr` equals `value`, atomically updates it based on the number of waiting
+/// threads and wakes up to `count` of them ([`SIGNAL_ALL`] wakes them all).
+///
+/// Roughly, the kernel increments the value when there are no waiters, decrements it when every
+/// waiter gets woken up, and leaves it unchanged otherwise. This is the building block for
+/// semaphore-like primitives.
+///
+/// If the value differs from `value`, nothing is modified nor woken up and
+/// [`SignalToAddressError::ValueMismatch`] is returned.
+pub fn signal_and_modify_by_waiting_count_if_equal(
+    addr: &AtomicI32,
+    value: i32,
+    count: i32,
+) -> Result<(), SignalToAddressError> {
+    signal_to_address(
+        addr,
+        SignalType::SignalAndModifyBasedOnWaitingThreadCountIfEqual,
+        value,
+        count,
+    )
+}
+
+fn wait_for_address(
+    addr: &AtomicI32,
+    arb_type: ArbitrationType,
+    value: i32,
+    timeout: Option<Duration>,
+) -> Result<(), WaitForAddressError> {
+    // A negative timeout waits forever
+    let timeout = timeout.map_or(-1, |t| i64::try_from(t.as_nanos()).unwrap_or(i64::MAX));
+
+    // SAFETY: `addr` is a valid, 4-byte aligned atomic living at least as long as the call.
+    let rc =
+        unsafe { raw::wait_for_address(addr.as_ptr().cast(), arb_type, value as i64, timeout) };
+    RawResult::from_raw(rc).map((), |rc| Error::from(rc).into())
+}
+
+/// Error type for the address arbiter wait functions.
+#[derive(Debug, ResultCodeError)]
+pub enum WaitForAddressError {
+    /// The address is not 4-byte aligned or lies in kernel space —
+    /// `KernelError::InvalidAddress` (raw code `0xCC01`).
+    #[rc(desc = InvalidAddress)]
+    InvalidAddress,
+    /// The address could not be accessed by the kernel —
+    /// `KernelError::InvalidCurrentMemory` (raw code `0xD401`).
+    #[rc(desc = InvalidCurrentMemory, msg = "Invalid memory state")]
+    InvalidMemState,
+    /// The value did not satisfy the arbitration condition, so the thread did not wait —
+    /// `KernelError::InvalidState` (raw code `0xFA01`).
+    #[rc(desc = InvalidState)]
+    ValueMismatch,
+    /// The timeout elapsed before the address was signalled —
+    /// `KernelError::TimedOut` (raw code `0xEA01`).
+    #[rc(desc = TimedOut, msg = "Operation timed out")]
+    TimedOut,
+    /// The current thread is being terminated —
+    /// `KernelError::TerminationRequested` (raw code `0x7601`).
+    #[rc(desc = TerminationRequested)]
+    ThreadTerminating,
+    /// Any unforeseen kernel error. Contains the original [`Error`] so callers
+    /// can inspect the raw result (`Error::to_raw`).
+    Unknown(Error),
+}
+
+fn signal_to_address(
+    addr: &AtomicI32,
+    signal_type: SignalType,
+    value: i32,
+    count: i32,
+) -> Result<(), SignalToAddressError> {
+    // SAFETY: `addr` is a valid, 4-byte aligned atomic living at least as long as the call.
+    let rc = unsafe { raw::signal_to_address(addr.as_ptr().cast(), signal_type, value, count) };
+    RawResult::from_raw(rc).map((), |rc| Error::from(rc).into())
+}
+
+/// Error type for the address arbiter signal functions.
+#[derive(Debug, ResultCodeError)]
+pub enum SignalToAddressError {
+    /// The address is not 4-byte aligned or lies in kernel space —
+    /// `KernelError::InvalidAddress` (raw code `0xCC01`).
+    #[rc(desc = InvalidAddress)]
+    InvalidAddress,
+    /// The address could not be accessed by the kernel —
+    /// `KernelError::InvalidCurrentMemory` (raw code `0xD401`).
+    #[rc(desc = InvalidCurrentMemory, msg = "Invalid memory state")]
+    InvalidMemState,
+    /// The value did not equal the expected one, so nothing was modified nor signalled —
+    /// `KernelError::InvalidState` (raw code `0xFA01`).
+    #[rc(desc = InvalidState)]
+    ValueMismatch,
+    /// Any unforeseen kernel error. Contains the original [`Error`] so callers
+    /// can inspect the raw result (`Error::to_raw`).
+    Unknown(Error),
+}
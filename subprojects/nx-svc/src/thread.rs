@@ -8,9 +8,11 @@
 use core::ffi::c_void;
 
 use crate::{
-    error::{KernelError as KError, ToRawResultCode},
+    error::ResultCodeError,
+    handle::OwnedHandle,
+    misc::{self, GetInfoError, InfoType},
     raw,
-    result::{Error, ResultCode, raw::Result as RawResult},
+    result::{Error, raw::Result as RawResult},
 };
 
 define_waitable_handle_type! {
@@ -54,65 +56,45 @@ pub fn create(
     stack_top: *mut c_void,
     prio: i32,
     cpuid: i32,
-) -> Result<Handle, CreateThreadError> {
+) -> Result<OwnedHandle<Handle>, CreateThreadError> {
     let mut handle = raw::INVALID_HANDLE;
     let rc = unsafe { raw::create_thread(&mut handle, entry, arg, stack_top, prio, cpuid) };
 
-    RawResult::from_raw(rc).map(Handle(handle), |rc| match rc.description() {
-        desc if KError::OutOfMemory == desc => CreateThreadError::OutOfMemory,
-        desc if KError::OutOfResource == desc => CreateThreadError::OutOfResource,
-        desc if KError::LimitReached == desc => CreateThreadError::LimitReached,
-        desc if KError::OutOfHandles == desc => CreateThreadError::OutOfHandles,
-        desc if KError::InvalidPriority == desc => CreateThreadError::InvalidPriority,
-        desc if KError::InvalidCoreId == desc => CreateThreadError::InvalidCoreId,
-        _ => CreateThreadError::Unknown(rc.into()),
-    })
+    RawResult::from_raw(rc).map_err(|rc| CreateThreadError::from(Error::from(rc)))?;
+
+    // SAFETY: On success, the kernel returned a new handle that is not owned elsewhere.
+    Ok(unsafe { OwnedHandle::new(Handle(handle)) })
 }
 
-#[derive(Debug, thiserror::Error)]
+#[derive(Debug, ResultCodeError)]
 pub enum CreateThreadError {
-    #[error("Out of memory")]
+    #[rc(desc = OutOfMemory)]
     OutOfMemory,
     /// The kernel ran out of generic thread-related resources — maps to
-    /// `KernelError::OutOfResource` (raw code `0x267`).
-    #[error("Out of generic thread resources")]
+    /// `KernelError::OutOfResource` (raw code `0xCE01`).
+    #[rc(desc = OutOfResource, msg = "Out of generic thread resources")]
     OutOfResource,
     /// The per-process thread quota has been exhausted —
-    /// `KernelError::LimitReached` (raw code `0x284`).
-    #[error("Thread limit reached for process")]
+    /// `KernelError::LimitReached` (raw code `0x10801`).
+    #[rc(desc = LimitReached, msg = "Thread limit reached for process")]
     LimitReached,
     /// The process handle table contains no free slots —
-    /// `KernelError::OutOfHandles` (raw code `0x269`).
-    #[error("Handle table full")]
+    /// `KernelError::OutOfHandles` (raw code `0xD201`).
+    #[rc(desc = OutOfHandles, msg = "Handle table full")]
     OutOfHandles,
     /// The supplied priority is outside `0..=0x3F` or not permitted by the
     /// process — `KernelError::InvalidPriority` (raw code `0x270`).
-    #[error("Invalid priority")]
+    #[rc(desc = InvalidPriority)]
     InvalidPriority,
     /// The requested CPU core is invalid or outside the process affinity mask —
     /// `KernelError::InvalidCoreId` (raw code `0x271`).
-    #[error("Invalid core id")]
+    #[rc(desc = InvalidCoreId)]
     InvalidCoreId,
     /// Any unforeseen kernel error. Contains the original [`Error`] so callers
     /// can inspect the raw result (`Error::to_raw`).
-    #[error("Unknown error: {0}")]
     Unknown(Error),
 }
 
-impl ToRawResultCode for CreateThreadError {
-    fn to_rc(self) -> ResultCode {
-        match self {
-            Self::OutOfMemory => KError::OutOfMemory.to_rc(),
-            Self::OutOfResource => KError::OutOfResource.to_rc(),
-            Self::LimitReached => KError::LimitReached.to_rc(),
-            Self::OutOfHandles => KError::OutOfHandles.to_rc(),
-            Self::InvalidPriority => KError::InvalidPriority.to_rc(),
-            Self::InvalidCoreId => KError::InvalidCoreId.to_rc(),
-            Self::Unknown(err) => err.to_raw(),
-        }
-    }
-}
-
 /// Transitions a thread from the *created* state to *runnable*.
 ///
 /// The target `handle` must refer to a thread that has been successfully
@@ -120,99 +102,60 @@ impl ToRawResultCode for CreateThreadError {
 /// already-running or invalid thread results in [`StartThreadError::InvalidHandle`].
 pub fn start(handle: Handle) -> Result<(), StartThreadError> {
     let rc = unsafe { raw::start_thread(handle.to_raw()) };
-    RawResult::from_raw(rc).map((), |rc| match rc.description() {
-        desc if KError::InvalidHandle == desc => StartThreadError::InvalidHandle,
-        _ => StartThreadError::Unknown(rc.into()),
-    })
+    RawResult::from_raw(rc).map((), |rc| Error::from(rc).into())
 }
 
-#[derive(Debug, thiserror::Error)]
+#[derive(Debug, ResultCodeError)]
 pub enum StartThreadError {
     /// The supplied handle is not a valid thread handle —
     /// `KernelError::InvalidHandle` (raw code `0xE401`).
-    #[error("Invalid handle")]
+    #[rc(desc = InvalidHandle)]
     InvalidHandle,
     /// Any unforeseen kernel error. Contains the original [`Error`] so callers
     /// can inspect the raw result (`Error::to_raw`).
-    #[error("Unknown error: {0}")]
     Unknown(Error),
 }
 
-impl ToRawResultCode for StartThreadError {
-    fn to_rc(self) -> ResultCode {
-        match self {
-            Self::InvalidHandle => KError::InvalidHandle.to_rc(),
-            Self::Unknown(err) => err.to_raw(),
-        }
-    }
-}
-
 /// Pauses a thread.
 ///
 /// Under the hood this invokes [`raw::set_thread_activity`] with [`ThreadActivity::Paused`].
 /// The operation is asynchronous: a successful return only indicates the request was enqueued.
 pub fn pause(handle: Handle) -> Result<(), PauseThreadError> {
     let rc = unsafe { raw::set_thread_activity(handle.to_raw(), raw::ThreadActivity::Paused) };
-    RawResult::from_raw(rc).map((), |rc| match rc.description() {
-        desc if KError::InvalidHandle == desc => PauseThreadError::InvalidHandle,
-        _ => PauseThreadError::Unknown(rc.into()),
-    })
+    RawResult::from_raw(rc).map((), |rc| Error::from(rc).into())
 }
 
-#[derive(Debug, thiserror::Error)]
+#[derive(Debug, ResultCodeError)]
 pub enum PauseThreadError {
     /// The supplied handle is not a valid thread handle —
     /// `KernelError::InvalidHandle` (raw code `0xE401`).
-    #[error("Invalid handle")]
+    #[rc(desc = InvalidHandle)]
     InvalidHandle,
     /// Any unforeseen kernel error. Contains the original [`Error`] so callers
     /// can inspect the raw result (`Error::to_raw`).
-    #[error("Unknown error: {0}")]
     Unknown(Error),
 }
 
-impl ToRawResultCode for PauseThreadError {
-    fn to_rc(self) -> ResultCode {
-        match self {
-            Self::InvalidHandle => KError::InvalidHandle.to_rc(),
-            Self::Unknown(err) => err.to_raw(),
-        }
-    }
-}
-
 /// Resumes a previously paused thread.
 ///
 /// Under the hood this invokes [`raw::set_thread_activity`] with [`ThreadActivity::Runnable`].
 /// The operation is asynchronous: a successful return only indicates the request was enqueued.
 pub fn resume(handle: Handle) -> Result<(), ResumeThreadError> {
     let rc = unsafe { raw::set_thread_activity(handle.to_raw(), raw::ThreadActivity::Runnable) };
-    RawResult::from_raw(rc).map((), |rc| match rc.description() {
-        desc if KError::InvalidHandle == desc => ResumeThreadError::InvalidHandle,
-        _ => ResumeThreadError::Unknown(rc.into()),
-    })
+    RawResult::from_raw(rc).map((), |rc| Error::from(rc).into())
 }
 
-#[derive(Debug, thiserror::Error)]
+#[derive(Debug, ResultCodeError)]
 pub enum ResumeThreadError {
     /// The supplied handle is not a valid thread handle —
     /// `KernelError::InvalidHandle` (raw code `0xE401`).
-    #[error("Invalid handle")]
+    #[rc(desc = InvalidHandle)]
     InvalidHandle,
     /// Any unforeseen kernel error. Contains the original [`Error`] so callers
     /// can inspect the raw result (`Error::to_raw`).
-    #[error("Unknown error: {0}")]
     Unknown(Error),
 }
 
-impl ToRawResultCode for ResumeThreadError {
-    fn to_rc(self) -> ResultCode {
-        match self {
-            Self::InvalidHandle => KError::InvalidHandle.to_rc(),
-            Self::Unknown(err) => err.to_raw(),
-        }
-    }
-}
-
 /// Exits the current thread and never returns.
 ///
 /// Internally this issues the `svcExitThread` syscall. The kernel will perform
@@ -230,33 +173,20 @@ pub fn exit() -> ! {
 /// the thread—it merely drops the user-space reference.
 pub fn close_handle(handle: Handle) -> Result<(), CloseHandleError> {
     let rc = unsafe { raw::close_handle(handle.to_raw()) };
-    RawResult::from_raw(rc).map((), |rc| match rc.description() {
-        desc if KError::InvalidHandle == desc => CloseHandleError::InvalidHandle,
-        _ => CloseHandleError::Unknown(rc.into()),
-    })
+    RawResult::from_raw(rc).map((), |rc| Error::from(rc).into())
 }
 
-#[derive(Debug, thiserror::Error)]
+#[derive(Debug, ResultCodeError)]
 pub enum CloseHandleError {
     /// The supplied handle is not a valid thread handle —
     /// `KernelError::InvalidHandle` (raw code `0xE401`).
-    #[error("Invalid handle")]
+    #[rc(desc = InvalidHandle)]
     InvalidHandle,
     /// Any unforeseen kernel error. Contains the original [`Error`] so callers
     /// can inspect the raw result (`Error::to_raw`).
-    #[error("Unknown error: {0}")]
     Unknown(Error),
 }
 
-impl ToRawResultCode for CloseHandleError {
-    fn to_rc(self) -> ResultCode {
-        match self {
-            Self::InvalidHandle => KError::InvalidHandle.to_rc(),
-            Self::Unknown(err) => err.to_raw(),
-        }
-    }
-}
-
 /// Dumps the CPU context of a *paused* thread into `ctx`.
 ///
 /// The target thread must have been paused beforehand (see [`pause`]) to ensure
@@ -264,29 +194,16 @@ impl ToRawResultCode for CloseHandleError {
 pub fn get_context3(thread: Handle) -> Result<raw::ThreadContext, GetContext3Error> {
     let mut ctx = raw::ThreadContext::zeroed();
     let rc = unsafe { raw::get_thread_context3(&mut ctx, thread.0) };
-    RawResult::from_raw(rc).map(ctx, |rc| match rc.description() {
-        desc if KError::InvalidHandle == desc => GetContext3Error::InvalidHandle,
-        _ => GetContext3Error::Unknown(rc.into()),
-    })
+    RawResult::from_raw(rc).map(ctx, |rc| Error::from(rc).into())
 }
 
-#[derive(Debug, thiserror::Error)]
+#[derive(Debug, ResultCodeError)]
 pub enum GetContext3Error {
-    #[error("Invalid handle")]
+    #[rc(desc = InvalidHandle)]
     InvalidHandle,
-    #[error("Unknown error: {0}")]
     Unknown(Error),
 }
 
-impl ToRawResultCode for GetContext3Error {
-    fn to_rc(self) -> ResultCode {
-        match self {
-            Self::InvalidHandle => KError::InvalidHandle.to_rc(),
-            Self::Unknown(err) => err.to_raw(),
-        }
-    }
-}
-
 /// Suspends the current thread for *at least* the specified number of
 /// nanoseconds.
 ///
@@ -348,6 +265,203 @@ pub fn get_current_processor_number() -> u32 {
     unsafe { raw::get_current_processor_number() }
 }
 
+/// Thread priority.
+///
+/// Priorities range from `0` (highest) to `0x3F` (lowest). A process may only use the priorities
+/// allowed by its priority mask (see [`InfoType::PriorityMask`]).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[repr(transparent)]
+pub struct Priority(u8);
+
+impl Priority {
+    /// The highest priority the kernel supports.
+    pub const HIGHEST: Priority = Priority(0);
+
+    /// The lowest priority the kernel supports.
+    pub const LOWEST: Priority = Priority(0x3F);
+
+    /// Creates a [`Priority`], validating it against the current process's priority mask.
+    ///
+    /// Returns an error if `priority` is outside `0..=0x3F` or is not allowed by
+    /// [`InfoType::PriorityMask`].
+    pub fn new(priority: u8) -> Result<Priority, PriorityError> {
+        if priority > Self::LOWEST.0 {
+            return Err(PriorityError::OutOfRange { priority });
+        }
+
+        let mask = misc::get_info(InfoType::PriorityMask, raw::CUR_PROCESS_HANDLE)?;
+        if mask & (1 << priority) == 0 {
+            return Err(PriorityError::NotPermitted { priority, mask });
+        }
+
+        Ok(Priority(priority))
+    }
+
+    /// Creates a [`Priority`] without validation.
+    ///
+    /// Values outside `0..=0x3F`, or not allowed by the process priority mask, are rejected by the
+    /// kernel with [`SetPriorityError::InvalidPriority`].
+    pub const fn new_unchecked(priority: u8) -> Priority {
+        Priority(priority)
+    }
+
+    /// Returns the raw priority value.
+    pub const fn get(self) -> u8 {
+        self.0
+    }
+}
+
+/// Error type for [`Priority::new`].
+#[derive(Debug, thiserror::Error)]
+pub enum PriorityError {
+    /// The priority is outside `0..=0x3F`.
+    #[error("Invalid priority {priority}: must be in range 0..=0x3F")]
+    OutOfRange {
+        /// The invalid priority that was provided.
+        priority: u8,
+    },
+    /// The priority is not allowed by the process priority mask.
+    #[error("Priority {priority} not permitted by process priority mask 0x{mask:X}")]
+    NotPermitted {
+        /// The priority that was provided.
+        priority: u8,
+        /// The process priority mask.
+        mask: u64,
+    },
+    /// The process priority mask could not be read.
+    #[error(transparent)]
+    GetInfo(#[from] GetInfoError),
+}
+
+/// Gets the priority of a thread.
+///
+/// This is a safe wrapper around [`raw::get_thread_priority`].
+pub fn get_priority(handle: Handle) -> Result<Priority, GetPriorityError> {
+    let mut priority = 0i32;
+    let rc = unsafe { raw::get_thread_priority(&mut priority, handle.to_raw()) };
+    RawResult::from_raw(rc).map(Priority(priority as u8), |rc| Error::from(rc).into())
+}
+
+/// Error type for [`get_priority`].
+#[derive(Debug, ResultCodeError)]
+pub enum GetPriorityError {
+    /// The supplied handle is not a valid thread handle —
+    /// `KernelError::InvalidHandle` (raw code `0xE401`).
+    #[rc(desc = InvalidHandle)]
+    InvalidHandle,
+    /// Any unforeseen kernel error. Contains the original [`Error`] so callers
+    /// can inspect the raw result (`Error::to_raw`).
+    Unknown(Error),
+}
+
+/// Sets the priority of a thread.
+///
+/// This is a safe wrapper around [`raw::set_thread_priority`]. The new priority takes effect
+/// immediately, including for a thread that is currently running.
+pub fn set_priority(handle: Handle, priority: Priority) -> Result<(), SetPriorityError> {
+    let rc = unsafe { raw::set_thread_priority(handle.to_raw(), priority.0 as u32) };
+    RawResult::from_raw(rc).map((), |rc| Error::from(rc).into())
+}
+
+/// Error type for [`set_priority`].
+#[derive(Debug, ResultCodeError)]
+pub enum SetPriorityError {
+    /// The supplied handle is not a valid thread handle —
+    /// `KernelError::InvalidHandle` (raw code `0xE401`).
+    #[rc(desc = InvalidHandle)]
+    InvalidHandle,
+    /// The priority is outside `0..=0x3F` or not permitted by the process —
+    /// `KernelError::InvalidPriority` (raw code `0xE001`).
+    #[rc(desc = InvalidPriority)]
+    InvalidPriority,
+    /// Any unforeseen kernel error. Contains the original [`Error`] so callers
+    /// can inspect the raw result (`Error::to_raw`).
+    Unknown(Error),
+}
+
+/// A kernel thread ID.
+///
+/// Thread IDs are unique system-wide and are never reused while the system is running.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[repr(transparent)]
+pub struct ThreadId(pub u64);
+
+impl ThreadId {
+    /// Returns the raw thread ID.
+    pub const fn get(self) -> u64 {
+        self.0
+    }
+}
+
+impl From<u64> for ThreadId {
+    fn from(id: u64) -> Self {
+        Self(id)
+    }
+}
+
+impl From<ThreadId> for u64 {
+    fn from(id: ThreadId) -> Self {
+        id.0
+    }
+}
+
+/// Gets the ID of a thread.
+///
+/// This is a safe wrapper around [`raw::get_thread_id`].
+pub fn get_id(handle: Handle) -> Result<ThreadId, GetThreadIdError> {
+    let mut id = 0u64;
+    let rc = unsafe { raw::get_thread_id(&mut id, handle.to_raw()) };
+    RawResult::from_raw(rc).map(ThreadId(id), |rc| Error::from(rc).into())
+}
+
+/// Error type for [`get_id`].
+#[derive(Debug, ResultCodeError)]
+pub enum GetThreadIdError {
+    /// The supplied handle is not a valid thread handle —
+    /// `KernelError::InvalidHandle` (raw code `0xE401`).
+    #[rc(desc = InvalidHandle)]
+    InvalidHandle,
+    /// Any unforeseen kernel error. Contains the original [`Error`] so callers
+    /// can inspect the raw result (`Error::to_raw`).
+    Unknown(Error),
+}
+
+/// Gets the CPU core affinity of a thread.
+///
+/// This is a safe wrapper around [`raw::get_thread_core_mask`]. The result is decoded into a
+/// [`CoreAffinity`]:
+/// - [`CoreAffinity::Specific`] if the thread has a preferred core.
+/// - [`CoreAffinity::Any`] if it has none.
+///
+/// The kernel resolves the `ProcessDefault` and `NoUpdate` settings when they are applied, so
+/// they are never returned.
+pub fn get_core_mask(handle: Handle) -> Result<CoreAffinity, GetCoreMaskError> {
+    let mut core_id = 0i32;
+    let mut affinity_mask = 0u64;
+    let rc =
+        unsafe { raw::get_thread_core_mask(&mut core_id, &mut affinity_mask, handle.to_raw()) };
+
+    let mask = CoreAffinityMask::from_bits_retain(affinity_mask as u32);
+    let affinity = match u8::try_from(core_id) {
+        Ok(core) => CoreAffinity::Specific(SpecificCoreAffinity { core, mask }),
+        Err(_) => CoreAffinity::Any(AnyCoreAffinity { mask }),
+    };
+
+    RawResult::from_raw(rc).map(affinity, |rc| Error::from(rc).into())
+}
+
+/// Error type for [`get_core_mask`].
+#[derive(Debug, ResultCodeError)]
+pub enum GetCoreMaskError {
+    /// The supplied handle is not a valid thread handle —
+    /// `KernelError::InvalidHandle` (raw code `0xE401`).
+    #[rc(desc = InvalidHandle)]
+    InvalidHandle,
+    /// Any unforeseen kernel error. Contains the original [`Error`] so callers
+    /// can inspect the raw result (`Error::to_raw`).
+    Unknown(Error),
+}
+
 /// Sets the CPU core affinity for a thread.
 ///
 /// This function configures which CPU cores the specified thread is allowed
@@ -360,13 +474,7 @@ pub fn set_core_mask(
 ) -> Result<(), SetCoreMaskError> {
     let (core_id, affinity_mask) = affinity.to_core_id_and_mask();
     let rc = unsafe { raw::set_thread_core_mask(handle.to_raw(), core_id, affinity_mask) };
-    RawResult::from_raw(rc).map((), |rc| match rc.description() {
-        desc if KError::InvalidHandle == desc => SetCoreMaskError::InvalidHandle,
-        desc if KError::InvalidCoreId == desc => SetCoreMaskError::InvalidCoreId,
-        desc if KError::InvalidCombination == desc => SetCoreMaskError::InvalidCombination,
-        desc if KError::TerminationRequested == desc => SetCoreMaskError::TerminationRequested,
-        _ => SetCoreMaskError::Unknown(rc.into()),
-    })
+    RawResult::from_raw(rc).map((), |rc| Error::from(rc).into())
 }
 
 /// CPU core affinity configuration for threads.
@@ -512,6 +620,18 @@ pub struct SpecificCoreAffinity {
     mask: CoreAffinityMask,
 }
 
+impl SpecificCoreAffinity {
+    /// Returns the preferred core.
+    pub const fn core(&self) -> u8 {
+        self.core
+    }
+
+    /// Returns the mask of allowed cores.
+    pub const fn mask(&self) -> CoreAffinityMask {
+        self.mask
+    }
+}
+
 /// Error type for core affinity validation failures.
 #[derive(Debug, thiserror::Error)]
 pub enum SpecificCoreAffinityError {
@@ -532,6 +652,13 @@ pub struct AnyCoreAffinity {
     mask: CoreAffinityMask,
 }
 
+impl AnyCoreAffinity {
+    /// Returns the mask of allowed cores.
+    pub const fn mask(&self) -> CoreAffinityMask {
+        self.mask
+    }
+}
+
 /// Configuration for using the process's default core.
 ///
 /// This instructs the kernel to use the process's default core configuration.
@@ -551,6 +678,13 @@ pub struct NoUpdateCoreAffinity {
     mask: CoreAffinityMask,
 }
 
+impl NoUpdateCoreAffinity {
+    /// Returns the mask of allowed cores.
+    pub const fn mask(&self) -> CoreAffinityMask {
+        self.mask
+    }
+}
+
 /// Raw core affinity configuration without validation.
 ///
 /// This struct allows passing arbitrary core_id and affinity_mask values
@@ -621,46 +755,33 @@ bitflags::bitflags! {
     }
 }
 
-#[derive(Debug, thiserror::Error)]
+#[derive(Debug, ResultCodeError)]
 pub enum SetCoreMaskError {
     /// The supplied handle is not a valid thread handle —
     /// `KernelError::InvalidHandle` (raw code `0xE401`).
-    #[error("Invalid handle")]
+    #[rc(desc = InvalidHandle)]
     InvalidHandle,
 
     /// The specified core ID is invalid or affinity mask contains disallowed cores —
     /// `KernelError::InvalidCoreId` (raw code `0x271`).
-    #[error("Invalid core id")]
+    #[rc(desc = InvalidCoreId)]
     InvalidCoreId,
 
     /// Zero affinity mask or core ID not in affinity mask —
     /// `KernelError::InvalidCombination` (raw code `0x274`).
-    #[error("Invalid combination")]
+    #[rc(desc = InvalidCombination)]
     InvalidCombination,
 
     /// Thread termination was requested during the operation —
     /// `KernelError::TerminationRequested` (raw code `0x23B`).
-    #[error("Termination requested")]
+    #[rc(desc = TerminationRequested)]
     TerminationRequested,
 
     /// Any unforeseen kernel error. Contains the original [`Error`] so callers
     /// can inspect the raw result (`Error::to_raw`).
-    #[error("Unknown error: {0}")]
     Unknown(Error),
 }
 
-impl ToRawResultCode for SetCoreMaskError {
-    fn to_rc(self) -> ResultCode {
-        match self {
-            Self::InvalidHandle => KError::InvalidHandle.to_rc(),
-            Self::InvalidCoreId => KError::InvalidCoreId.to_rc(),
-            Self::InvalidCombination => KError::InvalidCombination.to_rc(),
-            Self::TerminationRequested => KError::TerminationRequested.to_rc(),
-            Self::Unknown(err) => err.to_raw(),
-        }
-    }
-}
-
 mod _priv {
     /// Sealed trait to prevent external implementations.
     pub trait Sealed {}
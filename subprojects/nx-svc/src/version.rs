@@ -0,0 +1,219 @@
+//! Horizon OS version detection
+//!
+//! Many SVCs and [`InfoType`] variants are only available starting with a given firmware
+//! version. This module determines the major version of the running firmware, and caches it so
+//! that feature checks ([`is_at_least`]) are cheap.
+//!
+//! ## Detection
+//!
+//! The kernel does not report the firmware version, so it is derived by probing `svcGetInfo` for
+//! the info types each firmware introduced: the kernel rejects the ones it does not know with
+//! `KernelError::InvalidEnumValue`. This only yields a *lower bound* of the major version: the
+//! newest major version that introduced an info type the kernel knows. Major versions that did
+//! not introduce one cannot be told apart from the previous one (e.g. a 7.0.1 console is detected
+//! as 6), and minor and micro versions are never detected.
+//!
+//! The checks are therefore limited to major versions, and only exact for the ones introducing an
+//! info type: 2, 3, 4, 5, 6, 9, 11, 13, 14, 16, 18 and 19. Checking for any other major version
+//! conservatively fails on that version.
+//!
+//! When the exact version is known from elsewhere (e.g. `set:sys`'s `GetFirmwareVersion`), it can
+//! be provided with [`set_hos_version`], which takes precedence over the detected one.
+//!
+//! ## References
+//! - [Switchbrew Wiki: SVC](https://switchbrew.org/wiki/SVC#GetInfo)
+
+use core::{
+    fmt,
+    sync::atomic::{AtomicU32, Ordering},
+};
+
+use crate::{
+    error::KernelError,
+    misc::InfoType,
+    raw,
+    result::raw::{Result as RawResult, ResultCode},
+};
+
+/// A Horizon OS (firmware) version.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Version {
+    /// Major version
+    pub major: u8,
+    /// Minor version
+    pub minor: u8,
+    /// Micro version
+    pub micro: u8,
+}
+
+impl Version {
+    /// Creates a new [`Version`].
+    pub const fn new(major: u8, minor: u8, micro: u8) -> Self {
+        Self {
+            major,
+            minor,
+            micro,
+        }
+    }
+
+    /// Packs the version into a `u32`, in the same format as libnx's `MAKEHOSVERSION`.
+    pub const fn to_u32(self) -> u32 {
+        ((self.major as u32) << 16) | ((self.minor as u32) << 8) | self.micro as u32
+    }
+
+    /// Unpacks a version packed with [`to_u32`](Self::to_u32).
+    pub const fn from_u32(value: u32) -> Self {
+        Self {
+            major: (value >> 16) as u8,
+            minor: (value >> 8) as u8,
+            micro: value as u8,
+        }
+    }
+}
+
+impl fmt::Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.micro)
+    }
+}
+
+/// Cached packed [`Version`], or `0` if not detected yet.
+///
+/// Either the exact version provided with [`set_hos_version`], or the detected major version
+/// (`major.0.0`).
+static HOS_VERSION: AtomicU32 = AtomicU32::new(0);
+
+/// Returns the running Horizon OS version, as known to this module.
+///
+/// Unless set with [`set_hos_version`], only the major version is meaningful, and it is a lower
+/// bound. See the [module documentation](self).
+pub(crate) fn current() -> Version {
+    let cached = HOS_VERSION.load(Ordering::Relaxed);
+    if cached != 0 {
+        return Version::from_u32(cached);
+    }
+
+    // Detection is idempotent, so concurrent callers may race harmlessly. Do not overwrite a
+    // version set in the meantime, though.
+    let detected = Version::new(detect(), 0, 0);
+    match HOS_VERSION.compare_exchange(0, detected.to_u32(), Ordering::Relaxed, Ordering::Relaxed) {
+        Ok(_) => detected,
+        Err(current) => Version::from_u32(current),
+    }
+}
+
+/// Returns the major version of the running Horizon OS.
+///
+/// The version is detected on first use and cached. Unless set with [`set_hos_version`], it is
+/// a lower bound: see the [module documentation](self) for its accuracy.
+pub fn major_version() -> u8 {
+    current().major
+}
+
+/// Overrides the Horizon OS version, e.g. with the exact one reported by `set:sys`.
+pub fn set_hos_version(version: Version) {
+    HOS_VERSION.store(version.to_u32(), Ordering::Relaxed);
+}
+
+/// Returns `true` if the running Horizon OS major version is at least `major`.
+///
+/// Only exact for the major versions that introduced an info type, see the
+/// [module documentation](self).
+pub fn is_at_least(major: u8) -> bool {
+    major_version() >= major
+}
+
+/// Info types probed by [`detect`], newest first.
+const PROBES: &[InfoType] = &[
+    InfoType::TransferMemoryHint,
+    InfoType::AliasRegionExtraSize,
+    InfoType::IoRegionHint,
+    InfoType::IsSvcPermitted,
+    InfoType::ThreadTickCount { core: 0 },
+    InfoType::FreeThreadCount,
+    InfoType::IsApplication,
+    InfoType::TotalNonSystemMemorySize,
+    InfoType::UserExceptionContextAddress,
+    InfoType::InitialProcessIdRange { which: 0 },
+    InfoType::ProgramId,
+    InfoType::AslrRegionAddress,
+];
+
+/// Probes the kernel for the info types introduced by each firmware, returning the major version.
+fn detect() -> u8 {
+    detect_with(is_info_type_known)
+}
+
+/// Returns the major version introducing the newest info type `is_known` accepts.
+fn detect_with(is_known: impl Fn(InfoType) -> bool) -> u8 {
+    PROBES
+        .iter()
+        .find(|info_type| is_known(**info_type))
+        .map_or(1, |info_type| info_type.min_version().major)
+}
+/// Returns `true` unless the kernel rejects `info_type` as an unknown enum value.
+///
+/// Any other outcome, including errors caused by the handle or sub-ID, means the kernel knows
+/// the info type.
+fn is_info_type_known(info_type: InfoType) -> bool {
+    let (id0, id1) = info_type.to_ids();
+    let mut out = 0u64;
+
+    // NOTE: Issued directly, as `misc::get_info` relies on the detected version.
+    let rc = unsafe { raw::get_info(&mut out, id0, raw::CUR_PROCESS_HANDLE, id1) };
+    match RawResult::from_raw(rc) {
+        RawResult::Success => true,
+        RawResult::Error(rc) => !is_invalid_enum_value(rc),
+    }
+}
+
+fn is_invalid_enum_value(rc: ResultCode) -> bool {
+    KernelError::InvalidEnumValue == rc.description()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn version_packing() {
+        let version = Version::new(17, 0, 1);
+        assert_eq!(version.to_u32(), 0x11_00_01);
+        assert_eq!(Version::from_u32(0x11_00_01), version);
+        assert_eq!(
+            Version::from_u32(Version::new(4, 1, 0).to_u32()),
+            Version::new(4, 1, 0)
+        );
+    }
+
+    #[test]
+    fn version_ordering() {
+        assert!(Version::new(7, 0, 1) > Version::new(7, 0, 0));
+        assert!(Version::new(7, 1, 0) > Version::new(7, 0, 9));
+        assert!(Version::new(8, 0, 0) > Version::new(7, 9, 9));
+        // Packing preserves the ordering
+        assert!(Version::new(8, 0, 0).to_u32() > Version::new(7, 9, 9).to_u32());
+    }
+
+    #[test]
+    fn probes_are_newest_first() {
+        for pair in PROBES.windows(2) {
+            assert!(pair[0].min_version() > pair[1].min_version());
+        }
+    }
+
+    #[test]
+    fn detection() {
+        // Emulates a kernel knowing the info types available on `version`
+        let on =
+            |version: Version| detect_with(move |info_type| info_type.is_available_on(version));
+
+        assert_eq!(on(Version::new(1, 0, 0)), 1);
+        assert_eq!(on(Version::new(4, 1, 0)), 4);
+        assert_eq!(on(Version::new(6, 2, 0)), 6);
+        // 7.0.0 introduced no info type
+        assert_eq!(on(Version::new(7, 0, 1)), 6);
+        assert_eq!(on(Version::new(11, 0, 1)), 11);
+        assert_eq!(on(Version::new(20, 1, 0)), 19);
+    }
+}
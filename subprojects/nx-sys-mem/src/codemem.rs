@@ -0,0 +1,221 @@
+//! Code memory (JIT) management
+//!
+//! A [`CodeMemory`] owns a page-aligned heap buffer handed over to a code memory kernel object,
+//! and maps it twice into the ASLR code region:
+//!
+//! - a read/write *owner* view, through which code is emitted ([`CodeMemory::as_mut_slice`]);
+//! - a read/execute *slave* view, from which it runs ([`CodeMemory::rx_ptr`]).
+//!
+//! After writing, call [`CodeMemory::commit`] to push the new code out of the data cache and
+//! evict stale instructions, before jumping into the executable view.
+//!
+//! Both views are unmapped, the kernel object closed and the buffer freed on drop.
+//!
+//! Requires the `CreateCodeMemory` and `ControlCodeMemory` SVCs \[4.0.0+\].
+
+use alloc::alloc::{Layout, alloc_zeroed, dealloc};
+use core::{ffi::c_void, ptr::NonNull};
+
+use nx_cpu::cache;
+use nx_svc::mem::{
+    codemem::{self as svc, Handle},
+    core::MemoryPermission,
+};
+
+use crate::{
+    alignment::{PAGE_SIZE, round_up_to_page},
+    vmm,
+};
+
+/// Guard region size around each view (0x1000), per libnx.
+const GUARD_SIZE: usize = 0x1000;
+
+/// A JIT buffer with a writable and an executable view.
+#[derive(Debug)]
+pub struct CodeMemory {
+    handle: Handle,
+    src: NonNull<c_void>,
+    size: usize,
+    rw: NonNull<c_void>,
+    rx: NonNull<c_void>,
+}
+
+impl CodeMemory {
+    /// Allocates a code memory buffer of at least `size` bytes and maps both views.
+    ///
+    /// `size` is rounded up to the page size. The buffer is zero-filled.
+    pub fn new(size: usize) -> Result<Self, CreateError> {
+        if size == 0 {
+            return Err(CreateError::InvalidSize);
+        }
+        let size = round_up_to_page(size);
+
+        // Allocate page-aligned, zero-filled backing memory.
+        let layout =
+            Layout::from_size_align(size, PAGE_SIZE).map_err(|_| CreateError::OutOfMemory)?;
+        let Some(src) = NonNull::new(unsafe { alloc_zeroed(layout) }.cast::<c_void>()) else {
+            return Err(CreateError::OutOfMemory);
+        };
+
+        // Hand the buffer over to the kernel.
+        let handle = match svc::create_code_memory(src, size) {
+            // Closed by `release`, which must not free the buffer if closing fails.
+            Ok(handle) => handle.into_inner(),
+            Err(err) => {
+                unsafe { dealloc(src.as_ptr().cast(), layout) };
+                return Err(CreateError::Create(err));
+            }
+        };
+
+        // Map both views while holding the VMM lock, so that the chosen ranges stay free.
+        let mut vmm = vmm::lock();
+
+        let rw = match map_view(&mut vmm, size, |addr| {
+            svc::map_owner(
+                handle,
+                addr,
+                size,
+                MemoryPermission::R | MemoryPermission::W,
+            )
+        }) {
+            Ok(rw) => rw,
+            Err(err) => {
+                drop(vmm);
+                unsafe { release(handle, src, layout) };
+                return Err(err);
+            }
+        };
+
+        let rx = match map_view(&mut vmm, size, |addr| {
+            svc::map_slave(
+                handle,
+                addr,
+                size,
+                MemoryPermission::R | MemoryPermission::X,
+            )
+        }) {
+            Ok(rx) => rx,
+            Err(err) => {
+                drop(vmm);
+                if svc::unmap_owner(handle, rw, size).is_ok() {
+                    unsafe { release(handle, src, layout) };
+                }
+                return Err(err);
+            }
+        };
+
+        Ok(Self {
+            handle,
+            src,
+            size,
+            rw,
+            rx,
+        })
+    }
+
+    /// Returns the size of the buffer, in bytes.
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Returns the base address of the writable view.
+    pub fn rw_ptr(&self) -> NonNull<u8> {
+        self.rw.cast()
+    }
+
+    /// Returns the base address of the executable view.
+    pub fn rx_ptr(&self) -> NonNull<u8> {
+        self.rx.cast()
+    }
+
+    /// Returns the buffer contents through the writable view.
+    pub fn as_slice(&self) -> &[u8] {
+        unsafe { core::slice::from_raw_parts(self.rw.as_ptr().cast(), self.size) }
+    }
+
+    /// Returns the buffer contents through the writable view, for emitting code.
+    pub fn as_mut_slice(&mut self) -> &mut [u8] {
+        unsafe { core::slice::from_raw_parts_mut(self.rw.as_ptr().cast(), self.size) }
+    }
+
+    /// Makes everything written through the writable view visible to instruction fetches from
+    /// the executable view.
+    pub fn commit(&self) {
+        self.commit_range(0, self.size);
+    }
+
+    /// Like [`commit`](Self::commit), but only for `len` bytes starting at `offset`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the range is out of bounds.
+    pub fn commit_range(&self, offset: usize, len: usize) {
+        assert!(
+            offset.checked_add(len).is_some_and(|end| end <= self.size),
+            "commit range out of bounds"
+        );
+
+        // SAFETY: Both views are mapped for the lifetime of `self`, and the range was checked
+        // to be within them.
+        unsafe {
+            cache::clean_dcache_to_pou(self.rw.as_ptr().cast::<u8>().add(offset), len);
+            cache::invalidate_icache(self.rx.as_ptr().cast::<u8>().add(offset), len);
+        }
+    }
+}
+
+impl Drop for CodeMemory {
+    fn drop(&mut self) {
+        // If a view cannot be unmapped, the kernel still owns the backing buffer: leak it rather
+        // than handing it back to the allocator.
+        let rx = svc::unmap_slave(self.handle, self.rx, self.size);
+        let rw = svc::unmap_owner(self.handle, self.rw, self.size);
+        if rx.is_err() || rw.is_err() {
+            return;
+        }
+
+        let layout = Layout::from_size_align(self.size, PAGE_SIZE).unwrap();
+        unsafe { release(self.handle, self.src, layout) };
+    }
+}
+
+/// Finds a free range in the code region and maps a view there with `map`.
+fn map_view(
+    vmm: &mut vmm::VirtmemManager,
+    size: usize,
+    map: impl FnOnce(NonNull<c_void>) -> Result<(), svc::ControlCodeMemoryError>,
+) -> Result<NonNull<c_void>, CreateError> {
+    let addr = vmm
+        .find_code_memory(size, GUARD_SIZE)
+        .ok_or(CreateError::VirtAddressAllocFailed)?;
+    map(addr)?;
+    Ok(addr)
+}
+
+/// Closes the code memory object and frees its backing buffer.
+///
+/// # Safety
+///
+/// No view of the code memory may be mapped, and `src`/`layout` must describe the buffer the
+/// object was created from.
+unsafe fn release(handle: Handle, src: NonNull<c_void>, layout: Layout) {
+    // The kernel only returns the buffer to the process once the object is destroyed.
+    if svc::close_handle(handle).is_ok() {
+        unsafe { dealloc(src.as_ptr().cast(), layout) };
+    }
+}
+
+/// Error type for [`CodeMemory::new`].
+#[derive(Debug, thiserror::Error)]
+pub enum CreateError {
+    #[error("Invalid size")]
+    InvalidSize,
+    #[error("Out of memory")]
+    OutOfMemory,
+    #[error("Failed to allocate virtual address range")]
+    VirtAddressAllocFailed,
+    #[error(transparent)]
+    Create(#[from] svc::CreateCodeMemoryError),
+    #[error(transparent)]
+    Map(#[from] svc::ControlCodeMemoryError),
+}
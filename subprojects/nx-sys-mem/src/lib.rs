@@ -6,6 +6,7 @@ extern crate nx_alloc;
 
 pub mod alignment;
 pub mod buf;
+pub mod codemem;
 pub mod shmem;
 pub mod stack;
 pub mod tmem;
@@ -85,8 +85,9 @@ pub unsafe fn create(
     remote_perm: RemotePermissions,
 ) -> Result<SharedMemory<Unmapped>, CreateError> {
     match svc::create_shared_memory(size, local_perm, remote_perm) {
+        // Closed explicitly with `close_handle`, which reports failures to the caller.
         Ok(handle) => Ok(SharedMemory(Unmapped {
-            handle,
+            handle: handle.into_inner(),
             size,
             perm: Permissions::from_bits_truncate(local_perm.bits()),
         })),
@@ -69,8 +69,9 @@ pub unsafe fn create(
 
     // Attempt to create the kernel object around that memory.
     match svc::create_transfer_memory(addr, size, perm) {
+        // Closed explicitly with `close_handle`, which reports failures to the caller.
         Ok(handle) => Ok(TransferMemory(Unmapped {
-            handle,
+            handle: handle.into_inner(),
             size,
             perm,
             src: Some(addr),
@@ -105,8 +106,9 @@ pub unsafe fn create_from_memory(
     }
 
     match svc::create_transfer_memory(buf, size, perm) {
+        // Closed explicitly with `close_handle`, which reports failures to the caller.
         Ok(handle) => Ok(TransferMemory(Unmapped {
-            handle,
+            handle: handle.into_inner(),
             size,
             perm,
             src: Some(buf),
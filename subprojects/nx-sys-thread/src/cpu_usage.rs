@@ -0,0 +1,229 @@
+//! CPU utilisation monitoring
+//!
+//! The kernel keeps track of the time each core spends running its idle thread
+//! (`InfoType::IdleTickCount`), and of the time each thread spends running
+//! (`InfoType::ThreadTickCount`). Both counters are expressed in system
+//! counter-timer ticks, so sampling them twice and comparing the deltas with the
+//! elapsed ticks yields the utilisation over the sampling interval.
+//!
+//! The kernel only reports the idle tick count of the core the calling thread is
+//! running on. To sample every core, [`CpuUsageMonitor`] briefly pins the calling
+//! thread to each core the process is allowed to run on, and then restores its
+//! original affinity.
+
+use alloc::vec::Vec;
+use core::time::Duration;
+
+use nx_svc::{
+    handle::BorrowedHandle,
+    misc::{self, GetInfoError, INVALID_HANDLE, InfoType},
+    raw::CUR_PROCESS_HANDLE,
+    thread::{
+        self as svc, CoreAffinityMask, GetCoreMaskError, Handle, RawCoreAffinity, SetCoreMaskError,
+    },
+};
+use nx_time::tick;
+
+/// Number of CPU cores on the Nintendo Switch.
+pub const CORE_COUNT: usize = 4;
+
+/// Samples the idle tick counts of the CPU cores, and the tick counts of a set
+/// of watched threads, to compute their utilisation.
+///
+/// Each call to [`sample`](Self::sample) reports the utilisation since the
+/// previous sample (or since the monitor was created). Watched threads are
+/// borrowed, so their handles stay open for as long as the monitor lives.
+#[derive(Debug)]
+pub struct CpuUsageMonitor<'a> {
+    /// Cores the current process is allowed to run on, and thus to sample.
+    cores: CoreAffinityMask,
+    /// Idle tick count of each sampled core, at the last sample.
+    idle_ticks: [u64; CORE_COUNT],
+    /// Watched threads, with their tick count at the last sample.
+    threads: Vec<(BorrowedHandle<'a, Handle>, u64)>,
+    /// System tick at the last sample.
+    last_tick: u64,
+}
+
+impl<'a> CpuUsageMonitor<'a> {
+    /// Creates a new monitor, taking the initial sample.
+    pub fn new() -> Result<Self, CpuUsageError> {
+        let mask = misc::get_info(InfoType::CoreMask, CUR_PROCESS_HANDLE)?;
+        let cores = CoreAffinityMask::from_bits_truncate(mask as u32);
+
+        let idle_ticks = read_idle_ticks(cores)?;
+        Ok(Self {
+            cores,
+            idle_ticks,
+            threads: Vec::new(),
+            last_tick: tick::system_tick(),
+        })
+    }
+
+    /// Starts reporting the utilisation of the thread behind `handle`.
+    ///
+    /// Per-thread tick counts are only available on 13.0.0+; on earlier
+    /// firmware this fails with [`GetInfoError::UnsupportedOnThisFirmware`].
+    ///
+    /// The reported utilisation only covers the time since the thread was added.
+    pub fn watch_thread(
+        &mut self,
+        handle: BorrowedHandle<'a, Handle>,
+    ) -> Result<(), CpuUsageError> {
+        let ticks = read_thread_ticks(handle.get())?;
+        self.threads.push((handle, ticks));
+        Ok(())
+    }
+
+    /// Stops reporting the utilisation of the thread behind `handle`.
+    pub fn unwatch_thread(&mut self, handle: Handle) {
+        self.threads.retain(|(watched, _)| watched.get() != handle);
+    }
+
+    /// Samples the counters, returning the utilisation since the previous sample.
+    pub fn sample(&mut self) -> Result<CpuUsage, CpuUsageError> {
+        let idle_ticks = read_idle_ticks(self.cores)?;
+        let thread_ticks = self
+            .threads
+            .iter()
+            .map(|(handle, _)| read_thread_ticks(handle.get()))
+            .collect::<Result<Vec<_>, _>>()?;
+        let now = tick::system_tick();
+        let elapsed = now.saturating_sub(self.last_tick);
+
+        let mut cores = [None; CORE_COUNT];
+        for (core, usage) in cores.iter_mut().enumerate() {
+            if self.cores.contains(core_mask(core)) {
+                let idle = idle_ticks[core].saturating_sub(self.idle_ticks[core]);
+                *usage = Some(100.0 - percent(idle, elapsed));
+            }
+        }
+
+        let threads = self
+            .threads
+            .iter_mut()
+            .zip(thread_ticks)
+            .map(|((handle, last), ticks)| {
+                let usage = ThreadUsage {
+                    handle: handle.get(),
+                    percent: percent(ticks.saturating_sub(*last), elapsed),
+                };
+                *last = ticks;
+                usage
+            })
+            .collect();
+
+        self.idle_ticks = idle_ticks;
+        self.last_tick = now;
+
+        Ok(CpuUsage {
+            interval: ticks_to_duration(elapsed),
+            cores,
+            threads,
+        })
+    }
+
+    /// Samples the counters, sleeps for `interval`, and returns the utilisation
+    /// over that interval.
+    pub fn measure(&mut self, interval: Duration) -> Result<CpuUsage, CpuUsageError> {
+        self.sample()?;
+        crate::sleep(interval);
+        self.sample()
+    }
+}
+
+/// CPU utilisation over a sampling interval, as reported by [`CpuUsageMonitor`].
+#[derive(Debug, Clone)]
+pub struct CpuUsage {
+    /// Length of the sampling interval.
+    pub interval: Duration,
+    /// Utilisation percentage (`0.0..=100.0`) of each core, or `None` for the
+    /// cores the process is not allowed to run on.
+    pub cores: [Option<f32>; CORE_COUNT],
+    /// Utilisation of each watched thread.
+    pub threads: Vec<ThreadUsage>,
+}
+
+/// Utilisation of a single thread over a sampling interval.
+#[derive(Debug, Clone, Copy)]
+pub struct ThreadUsage {
+    /// The thread handle.
+    pub handle: Handle,
+    /// Utilisation percentage (`0.0..=100.0`), relative to a single core.
+    pub percent: f32,
+}
+
+/// Error type for [`CpuUsageMonitor`] operations.
+#[derive(Debug, thiserror::Error)]
+pub enum CpuUsageError {
+    /// Reading a tick count (or the process core mask) failed.
+    #[error("Failed to read tick count: {0}")]
+    GetInfo(#[from] GetInfoError),
+    /// Reading the current thread's core affinity failed.
+    #[error("Failed to get core mask: {0}")]
+    GetCoreMask(#[from] GetCoreMaskError),
+    /// Pinning the current thread to a core, or restoring its affinity, failed.
+    #[error("Failed to set core mask: {0}")]
+    SetCoreMask(#[from] SetCoreMaskError),
+}
+
+/// Reads the idle tick count of each core in `cores`, by pinning the current
+/// thread to each of them in turn.
+fn read_idle_ticks(cores: CoreAffinityMask) -> Result<[u64; CORE_COUNT], CpuUsageError> {
+    let current = Handle::current_thread();
+    let original = svc::get_core_mask(current)?;
+
+    let mut ticks = [0; CORE_COUNT];
+    let res = (|| {
+        for (core, ticks) in ticks.iter_mut().enumerate() {
+            if !cores.contains(core_mask(core)) {
+                continue;
+            }
+
+            // The kernel migrates the current thread before returning
+            let pinned = RawCoreAffinity::new_unchecked(core as i32, core_mask(core).bits());
+            svc::set_core_mask(current, pinned)?;
+
+            *ticks = misc::get_info(
+                InfoType::IdleTickCount { core: core as u64 },
+                INVALID_HANDLE,
+            )?;
+        }
+        Ok::<_, CpuUsageError>(())
+    })();
+
+    // Always restore the original affinity, but report the sampling error first
+    let restored = svc::set_core_mask(current, original);
+    res?;
+    restored?;
+
+    Ok(ticks)
+}
+
+/// Reads the total tick count of a thread, across all cores.
+fn read_thread_ticks(handle: Handle) -> Result<u64, GetInfoError> {
+    // A core sub-ID of -1 selects the sum over all cores
+    misc::get_info(
+        InfoType::ThreadTickCount { core: u64::MAX },
+        handle.to_raw(),
+    )
+}
+
+fn core_mask(core: usize) -> CoreAffinityMask {
+    CoreAffinityMask::from_bits_truncate(1 << core)
+}
+
+/// Returns `part` as a percentage of `total`, clamped to `0.0..=100.0`.
+fn percent(part: u64, total: u64) -> f32 {
+    if total == 0 {
+        return 0.0;
+    }
+    (part as f32 * 100.0 / total as f32).clamp(0.0, 100.0)
+}
+
+/// Converts a number of system ticks into a [`Duration`], using the system
+/// counter-timer frequency.
+fn ticks_to_duration(ticks: u64) -> Duration {
+    let nanos = ticks as u128 * 1_000_000_000 / tick::system_tick_freq() as u128;
+    Duration::from_nanos(nanos.min(u64::MAX as u128) as u64)
+}
@@ -5,6 +5,7 @@ extern crate alloc;
 // The `nx-alloc` crate exposes the `#[global_allocator]` for the dependent crates.
 extern crate nx_alloc;
 
+pub mod cpu_usage;
 mod init;
 mod registry;
 mod thread_impl;
@@ -3,6 +3,7 @@
 use nx_svc::thread as svc;
 
 mod activity;
+mod cancel;
 mod context;
 mod exit;
 mod handle;
@@ -11,6 +12,7 @@ mod stackmem;
 mod wait;
 
 pub use activity::*;
+pub use cancel::*;
 pub use context::*;
 pub use exit::*;
 pub use handle::*;
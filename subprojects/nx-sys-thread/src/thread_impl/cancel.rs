@@ -0,0 +1,256 @@
+//! Cancellable waits
+//!
+//! A [`CancelToken`] lets one thread interrupt another thread blocked in
+//! `svcWaitSynchronization`, e.g. to shut down a worker waiting on events.
+//!
+//! The waiting thread performs its waits *through* the token, which records its
+//! thread handle for the duration of the wait. [`CancelToken::cancel`] then
+//! raises the token's flag and issues `svcCancelSynchronization` against the
+//! recorded thread, making the wait return [`WaitSyncError::Cancelled`].
+//!
+//! Spurious cancellations (latched by the kernel from an earlier, unrelated
+//! `svcCancelSynchronization`) are retried transparently: a token wait only
+//! reports [`WaitSyncError::Cancelled`] when the token itself was cancelled.
+//!
+//! Conversely, when [`CancelToken::cancel`] races with the end of a token wait,
+//! the kernel may latch the cancellation on the waiting thread after the wait
+//! has returned. The token wait consumes such a latch before returning, so that
+//! the thread's next, unrelated wait is not cancelled spuriously.
+
+use core::{
+    sync::atomic::{AtomicBool, AtomicU32, Ordering},
+    time::Duration,
+};
+
+use nx_svc::{
+    handle::Waitable,
+    raw::INVALID_HANDLE,
+    sync::{self, WaitSyncError},
+    thread::{self as svc, Handle},
+};
+use nx_time::Instant;
+
+use super::handle::get_current_thread_handle;
+
+/// A cancellation token for synchronization waits.
+///
+/// Only one thread may wait through a given token at a time.
+#[derive(Debug)]
+pub struct CancelToken {
+    /// Set once [`CancelToken::cancel`] has been called.
+    cancelled: AtomicBool,
+    /// Raw handle of the thread currently waiting through the token, or
+    /// [`INVALID_HANDLE`] if none.
+    waiter: AtomicU32,
+    /// Number of [`CancelToken::cancel`] calls that may be issuing
+    /// `svcCancelSynchronization` against the waiter.
+    cancelling: AtomicU32,
+    /// Number of `svcCancelSynchronization` calls issued against a registered
+    /// waiter (wrapping). Unlike `cancelled`, it is never cleared, see
+    /// `WaiterGuard`.
+    issued: AtomicU32,
+}
+
+impl CancelToken {
+    /// Creates a new, non-cancelled token.
+    pub const fn new() -> Self {
+        Self {
+            cancelled: AtomicBool::new(false),
+            waiter: AtomicU32::new(INVALID_HANDLE),
+            cancelling: AtomicU32::new(0),
+            issued: AtomicU32::new(0),
+        }
+    }
+
+    /// Returns `true` if the token has been cancelled.
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::SeqCst)
+    }
+
+    /// Cancels the token.
+    ///
+    /// If a thread is currently waiting through the token, its wait is
+    /// interrupted and returns [`WaitSyncError::Cancelled`]. Any subsequent
+    /// wait through the token fails immediately with the same error, until
+    /// [`reset`](Self::reset) is called.
+    ///
+    /// If the waiter is just finishing its wait, the kernel latches the
+    /// cancellation on the waiting thread instead, which would make the
+    /// thread's next wait of any kind fail with [`WaitSyncError::Cancelled`].
+    /// The token wait does not return before that latch is consumed, so other
+    /// waits on the thread are not affected.
+    pub fn cancel(&self) {
+        // Raise the flag *before* looking for a waiter: a thread registering
+        // concurrently either sees the flag, or is seen here and gets its wait
+        // cancelled by the kernel (latched if it has not started waiting yet).
+        self.cancelled.store(true, Ordering::SeqCst);
+
+        // Announce the cancellation before looking for a waiter, so that a
+        // waiter unregistering concurrently waits for it, see `WaiterGuard`.
+        self.cancelling.fetch_add(1, Ordering::SeqCst);
+        let waiter = self.waiter.load(Ordering::SeqCst);
+        if waiter != INVALID_HANDLE {
+            // The waiter is still registered, so its handle is open; the wait
+            // itself may have returned already, in which case the kernel
+            // latches the cancellation.
+            self.issued.fetch_add(1, Ordering::SeqCst);
+            let _ = sync::cancel_synchronization(unsafe { Handle::from_raw(waiter) });
+        }
+        self.cancelling.fetch_sub(1, Ordering::SeqCst);
+    }
+
+    /// Clears the cancelled flag, so the token can be reused.
+    ///
+    /// This may race with the end of a cancelled wait: a cancellation latched
+    /// by the kernel is still consumed by the waiter.
+    pub fn reset(&self) {
+        self.cancelled.store(false, Ordering::SeqCst);
+    }
+
+    /// Waits until `handle` is signalled, `timeout` elapses or the token is
+    /// cancelled.
+    ///
+    /// Passing `Duration::MAX` waits indefinitely.
+    ///
+    /// # Safety
+    ///
+    /// See [`sync::wait_synchronization_single`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if another thread is already waiting through this token.
+    pub unsafe fn wait_single<W>(&self, handle: &W, timeout: Duration) -> Result<(), WaitSyncError>
+    where
+        W: Waitable,
+    {
+        // SAFETY: The caller upholds the invariants for `handle`.
+        self.wait_with(timeout, |timeout_ns| unsafe {
+            sync::wait_synchronization_single(handle, timeout_ns)
+        })
+    }
+
+    /// Waits until one of `handles` is signalled, `timeout` elapses or the
+    /// token is cancelled, returning the index of the signalled handle.
+    ///
+    /// Passing `Duration::MAX` waits indefinitely.
+    ///
+    /// # Safety
+    ///
+    /// See [`sync::wait_synchronization_multiple`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if another thread is already waiting through this token.
+    pub unsafe fn wait_multiple<W>(
+        &self,
+        handles: &[W],
+        timeout: Duration,
+    ) -> Result<usize, WaitSyncError>
+    where
+        W: Waitable,
+    {
+        // SAFETY: The caller upholds the invariants for `handles`.
+        self.wait_with(timeout, |timeout_ns| unsafe {
+            sync::wait_synchronization_multiple(handles, timeout_ns)
+        })
+    }
+
+    /// Registers the current thread as the token's waiter and runs the `wait`
+    /// loop until it succeeds, fails, times out or the token is cancelled.
+    fn wait_with<T>(
+        &self,
+        timeout: Duration,
+        mut wait: impl FnMut(u64) -> Result<T, WaitSyncError>,
+    ) -> Result<T, WaitSyncError> {
+        let _waiter = WaiterGuard::register(self);
+
+        let deadline = if timeout != Duration::MAX {
+            Some(Instant::now() + timeout)
+        } else {
+            None
+        };
+
+        loop {
+            // Checked after registering, see `cancel`.
+            if self.is_cancelled() {
+                return Err(WaitSyncError::Cancelled);
+            }
+
+            let timeout_ns = match deadline {
+                Some(d) => d.saturating_duration_since(Instant::now()).as_nanos() as u64,
+                None => u64::MAX,
+            };
+
+            match wait(timeout_ns) {
+                // Either our own cancellation (reported at the top of the
+                // loop) or a stray one: retry.
+                Err(WaitSyncError::Cancelled) => continue,
+                Err(WaitSyncError::TimedOut) if deadline.is_none() => continue,
+                res => return res,
+            }
+        }
+    }
+}
+
+impl Default for CancelToken {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Records the current thread as a [`CancelToken`]'s waiter until dropped.
+struct WaiterGuard<'a> {
+    token: &'a CancelToken,
+    /// Value of [`CancelToken::issued`] before registering.
+    issued: u32,
+}
+
+impl<'a> WaiterGuard<'a> {
+    fn register(token: &'a CancelToken) -> Self {
+        let handle = get_current_thread_handle().to_raw();
+        // Read before registering, so that any cancellation issued against
+        // this waiter is counted after it.
+        let issued = token.issued.load(Ordering::SeqCst);
+        let registered = token
+            .waiter
+            .compare_exchange(INVALID_HANDLE, handle, Ordering::SeqCst, Ordering::SeqCst)
+            .is_ok();
+        assert!(
+            registered,
+            "CancelToken is already in use by another thread"
+        );
+        Self { token, issued }
+    }
+}
+
+impl Drop for WaiterGuard<'_> {
+    fn drop(&mut self) {
+        let token = self.token;
+        token.waiter.store(INVALID_HANDLE, Ordering::SeqCst);
+
+        // No cancellation can target this thread once it is unregistered, but
+        // one that saw it registered may still be in flight: let it complete,
+        // then consume the cancellation it may have latched.
+        //
+        // The `cancelled` flag cannot tell whether that happened, as `reset`
+        // may have cleared it in the meantime. A cancellation issued against
+        // a later waiter may be counted too, in which case the latch is
+        // merely checked for nothing.
+        while token.cancelling.load(Ordering::SeqCst) != 0 {
+            svc::yield_to_any_thread();
+        }
+        if token.issued.load(Ordering::SeqCst) != self.issued {
+            consume_cancellation_latch();
+        }
+    }
+}
+
+/// Clears a cancellation latched on the current thread by the kernel, if any.
+fn consume_cancellation_latch() {
+    // The kernel checks the latch after the zero-timeout check, so wait on no
+    // handles with the shortest non-zero timeout: a latched cancellation is
+    // reported (and cleared) right away, otherwise the wait times out.
+    let no_handles: &[Handle] = &[];
+    // SAFETY: No handle is passed to the kernel.
+    let _ = unsafe { sync::wait_synchronization_multiple(no_handles, 1) };
+}
@@ -13,7 +13,7 @@
 //! [`crate::thread_impl::activity::pause`]) to guarantee a consistent
 //! snapshot.
 
-use nx_svc::{raw, thread as svc};
+use nx_svc::{backtrace::Backtrace, raw, thread as svc};
 
 use super::handle::Thread;
 
@@ -102,6 +102,14 @@ impl Context {
     pub fn as_raw_mut(&mut self) -> &mut raw::ThreadContext {
         unsafe { &mut *(self as *mut Context as *mut raw::ThreadContext) }
     }
+
+    /// Unwinds the thread's stack from the saved frame pointer.
+    ///
+    /// See [`Backtrace::from_thread_context`].
+    #[inline]
+    pub fn backtrace(&self) -> Backtrace {
+        Backtrace::from_thread_context(self.as_raw())
+    }
 }
 
 /// Loss-free conversion from the raw SVC representation.
@@ -108,6 +108,9 @@ use nx_svc::thread::Handle;
 /// Size of the Thread Local Storage (TLS) region.
 pub const TLS_REGION_SIZE: usize = 0x200;
 
+/// Size of the IPC message buffer located at the start of the TLS region.
+pub use nx_svc::ipc::MESSAGE_BUFFER_SIZE as IPC_BUFFER_SIZE;
+
 /// Start of the user-mode TLS region.
 pub const USER_TLS_REGION_BEGIN: usize = 0x108;
 
@@ -211,6 +214,15 @@ pub fn get_ptr() -> *mut c_void {
     get_base_addr() as *mut c_void
 }
 
+/// Returns a raw pointer to the current thread's IPC message buffer.
+///
+/// The buffer occupies the first [`IPC_BUFFER_SIZE`] bytes of the TLS region. It is the buffer
+/// the kernel reads requests from and writes replies to in `nx_svc::ipc::send_sync_request`.
+#[inline]
+pub fn ipc_buffer_ptr() -> *mut u8 {
+    get_ptr() as *mut u8
+}
+
 /// Returns a raw pointer to the [`ThreadVars`] for the current thread.
 #[inline]
 pub fn thread_vars_ptr() -> *mut ThreadVars {
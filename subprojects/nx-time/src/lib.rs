@@ -6,6 +6,7 @@ mod ffi;
 
 pub mod common;
 mod sys;
+pub mod tick;
 
 pub use core::time::{Duration, TryFromFloatSecsError};
 use core::{
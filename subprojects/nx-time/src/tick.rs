@@ -0,0 +1,21 @@
+//! System counter-timer ticks.
+//!
+//! Several kernel counters (e.g. the idle and thread tick counts reported by `svcGetInfo`) are
+//! expressed in ticks of the system counter-timer. This module exposes the counter and its
+//! frequency so such values can be related to wall-clock time.
+
+use crate::sys::clock::aarch64;
+
+/// Returns the current value of the system counter-timer (`cntpct_el0`).
+#[inline]
+pub fn system_tick() -> u64 {
+    aarch64::get_system_tick()
+}
+
+/// Returns the frequency of the system counter-timer (`cntfrq_el0`), in Hz.
+///
+/// On the Nintendo Switch, this is 19.2MHz.
+#[inline]
+pub fn system_tick_freq() -> u64 {
+    aarch64::get_system_tick_freq()
+}